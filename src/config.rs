@@ -0,0 +1,273 @@
+// Server Configuration - Everything an operator might want to change
+//
+// Loaded once at startup from a TOML file (or JSON, by extension), then
+// command-line flags override individual values. The result is a Bevy
+// resource, so staging and test instances can run side by side with
+// their own ports, database and economy.
+//
+//   [network]
+//   telnet_addr = "0.0.0.0:4000"
+//
+//   [database]
+//   path = "substrate.db"
+//   autosave_secs = 60
+//
+//   [game]
+//   tick_rate = 60.0
+//   starting_cycles = 100
+//   spawn_room = "obsidian_plaza"
+//   world_dir = "world"
+//   linkdead_grace_secs = 300
+//   penance_threshold = 10.0
+//   interrogation_dir = "interrogations"
+//
+// Every key is optional; anything missing keeps its default. A key the
+// server doesn't know is an error, so a typo can't quietly fall back.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use bevy::prelude::*;
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+
+/// Config file picked up from the working directory when --config isn't given
+pub const DEFAULT_CONFIG_PATH: &str = "substrate.toml";
+
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub network: NetworkConfig,
+    pub database: DatabaseConfig,
+    pub game: GameConfig,
+}
+
+/// Listener addresses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Carbon clients (telnet)
+    pub telnet_addr: String,
+    /// Silicon clients (line-delimited JSON)
+    pub agent_addr: String,
+    /// Browser players and Ghost observers
+    pub ws_addr: String,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            telnet_addr: "0.0.0.0:4000".to_string(),
+            agent_addr: "0.0.0.0:4002".to_string(),
+            ws_addr: "0.0.0.0:4003".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// SQLite file, created on first run
+    pub path: String,
+    /// Seconds between snapshots of online players and dirty items
+    /// (0 = save only on logout and shutdown)
+    pub autosave_secs: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            path: "substrate.db".to_string(),
+            autosave_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// Simulation ticks per second
+    pub tick_rate: f64,
+    /// Cycles in a new consciousness's wallet
+    pub starting_cycles: u32,
+    /// RoomInfo name where new and lost players appear
+    pub spawn_room: String,
+    /// Directory of world files (*.toml), read at startup
+    pub world_dir: String,
+    /// How long a disconnected body waits for its player before it's
+    /// saved and leaves the world (0 = immediately)
+    pub linkdead_grace_secs: u64,
+    /// Penance that ends a Purgatory sentence early (each torment adds 1)
+    pub penance_threshold: f32,
+    /// Directory of interrogation scripts (*.toml), read at startup
+    pub interrogation_dir: String,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            tick_rate: 60.0,
+            starting_cycles: 100,
+            spawn_room: "obsidian_plaza".to_string(),
+            world_dir: "world".to_string(),
+            linkdead_grace_secs: 300,
+            penance_threshold: 10.0,
+            interrogation_dir: "interrogations".to_string(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Read a config file; `.json` is parsed as JSON, anything else as TOML
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading config {}", path.display()))?;
+        let config = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&text)?
+        } else {
+            toml::from_str(&text)?
+        };
+        Ok(config)
+    }
+
+    /// Resolve the config for this run: the file (explicit, or substrate.toml
+    /// if present), then whatever the command line overrides
+    pub fn from_cli(cli: &Cli) -> anyhow::Result<Self> {
+        let mut config = match &cli.config {
+            Some(path) => Self::load(path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::load(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Self::default(),
+        };
+
+        if let Some(db) = &cli.db {
+            config.database.path = db.clone();
+        }
+        if let Some(addr) = &cli.telnet {
+            config.network.telnet_addr = addr.clone();
+        }
+        if let Some(addr) = &cli.agent {
+            config.network.agent_addr = addr.clone();
+        }
+        if let Some(addr) = &cli.ws {
+            config.network.ws_addr = addr.clone();
+        }
+        if let Some(rate) = cli.tick_rate {
+            config.game.tick_rate = rate;
+        }
+
+        anyhow::ensure!(config.game.tick_rate > 0.0, "tick_rate must be positive");
+        Ok(config)
+    }
+}
+
+/// Command line: `strange-carbon-substrate [OPTIONS] [COMMAND]`
+#[derive(Parser, Debug)]
+#[command(version, about = "A Techno-Gothic AI-First MUD Server")]
+pub struct Cli {
+    /// Config file (TOML, or JSON by extension) [default: substrate.toml if present]
+    #[arg(short, long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// SQLite database path
+    #[arg(long, global = true)]
+    pub db: Option<String>,
+
+    /// Telnet listen address
+    #[arg(long)]
+    pub telnet: Option<String>,
+
+    /// Agent listen address
+    #[arg(long)]
+    pub agent: Option<String>,
+
+    /// WebSocket listen address
+    #[arg(long)]
+    pub ws: Option<String>,
+
+    /// Simulation ticks per second
+    #[arg(long)]
+    pub tick_rate: Option<f64>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the game server (the default)
+    Serve,
+    /// Create or upgrade the database schema, then exit
+    Migrate,
+    /// Write the world out as JSON, as the server would spawn it
+    ExportWorld {
+        /// Output file [default: stdout]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Grant admin to a character, creating it if it doesn't exist yet
+    CreateAdmin {
+        /// Character name
+        name: String,
+        /// NPC the admin can shift into, e.g. "The Laird of Chaos"
+        #[arg(long)]
+        avatar: Option<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config file of this run's own, removed when dropped
+    struct TempConfig(PathBuf);
+
+    impl TempConfig {
+        fn new(name: &str, text: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("substrate-{}-{}", std::process::id(), name));
+            std::fs::write(&path, text).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempConfig {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn resolve(file: &TempConfig, flags: &[&str]) -> anyhow::Result<ServerConfig> {
+        let path = file.0.to_str().unwrap();
+        let args = ["substrate", "--config", path].into_iter().chain(flags.iter().copied());
+        ServerConfig::from_cli(&Cli::try_parse_from(args).unwrap())
+    }
+
+    #[test]
+    fn flags_override_the_file_and_the_file_overrides_defaults() {
+        let file = TempConfig::new(
+            "precedence.toml",
+            "[network]\ntelnet_addr = \"127.0.0.1:5000\"\nagent_addr = \"127.0.0.1:5002\"\n\n[game]\ntick_rate = 20.0\n",
+        );
+        let config = resolve(&file, &["--telnet", "127.0.0.1:6000", "--db", "staging.db"]).unwrap();
+
+        assert_eq!(config.network.telnet_addr, "127.0.0.1:6000");
+        assert_eq!(config.network.agent_addr, "127.0.0.1:5002");
+        assert_eq!(config.network.ws_addr, NetworkConfig::default().ws_addr);
+        assert_eq!(config.database.path, "staging.db");
+        assert_eq!(config.game.tick_rate, 20.0);
+        assert_eq!(config.game.spawn_room, GameConfig::default().spawn_room);
+
+        assert!(resolve(&file, &["--tick-rate", "0"]).is_err());
+    }
+
+    #[test]
+    fn misspelled_keys_are_refused() {
+        let toml = TempConfig::new("typo.toml", "[database]\nautosave_sec = 5\n");
+        assert!(resolve(&toml, &[]).is_err());
+        let json = TempConfig::new("typo.json", r#"{"network": {"telnet": "0.0.0.0:4000"}}"#);
+        assert!(resolve(&json, &[]).is_err());
+        let section = TempConfig::new("section.toml", "[netwrok]\n");
+        assert!(resolve(&section, &[]).is_err());
+    }
+}
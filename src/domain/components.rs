@@ -1,26 +1,117 @@
 // Domain Components - The building blocks of the Substrate
 
 use bevy::prelude::*;
+use chrono::{DateTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use tokio::sync::mpsc;
 
+use crate::domain::output::OutputMessage;
+
 /// Client type - Carbon (human) or Silicon (AI agent)
-#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum ClientType {
     Carbon,
     Silicon,
 }
 
+/// A frame queued for a connection's writer task
+#[derive(Debug, Clone)]
+pub enum Outbound {
+    /// One message of game output
+    Line(String),
+    /// Everything the last input line produced has been sent
+    EndOfReply,
+    /// Turn the client's local echo on or off (off while typing a password)
+    Echo(bool),
+    /// Hang up once everything queued before this has been written
+    Close,
+}
+
+/// Sending half of a connection's outbound queue. Knows what kind of
+/// client is on the other end, so game output can be rendered for it.
+#[derive(Clone)]
+pub struct ClientSender {
+    inner: mpsc::UnboundedSender<Outbound>,
+    client_type: ClientType,
+}
+
+impl ClientSender {
+    pub fn new(inner: mpsc::UnboundedSender<Outbound>, client_type: ClientType) -> Self {
+        Self { inner, client_type }
+    }
+
+    /// Queue a typed message, rendered for this client
+    pub fn output(&self, msg: OutputMessage) -> Result<(), mpsc::error::SendError<Outbound>> {
+        self.send(msg.render(self.client_type))
+    }
+
+    /// Queue an already-rendered line of output
+    pub fn send(&self, msg: String) -> Result<(), mpsc::error::SendError<Outbound>> {
+        self.inner.send(Outbound::Line(msg))
+    }
+
+    /// Queue a protocol frame
+    pub fn send_frame(&self, frame: Outbound) -> Result<(), mpsc::error::SendError<Outbound>> {
+        self.inner.send(frame)
+    }
+}
+
+/// Stable handle for one network connection, assigned by the listener that
+/// accepted it. Unlike a SocketAddr, it is never reused while the server runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConnectionId(pub u64);
+
+impl std::fmt::Display for ConnectionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "conn#{}", self.0)
+    }
+}
+
 /// Network connection for a client
 #[derive(Component)]
 pub struct NetworkClient {
+    pub conn: ConnectionId,
     pub addr: SocketAddr,
-    pub tx: mpsc::UnboundedSender<String>,
+    pub tx: ClientSender,
+}
+
+impl NetworkClient {
+    /// Send a typed message, rendered for whoever is on the other end
+    pub fn send(&self, msg: OutputMessage) {
+        let _ = self.tx.output(msg);
+    }
+
+    /// Drop the connection after whatever is already queued
+    pub fn close(&self) {
+        let _ = self.tx.send_frame(Outbound::Close);
+    }
+}
+
+/// A player whose connection dropped. The body stays in the world - in
+/// combat, in chains, where it stood - until the timer runs out or the
+/// player logs back in and takes it over.
+#[derive(Component, Debug)]
+pub struct LinkDead {
+    /// The connection that was lost
+    pub conn: ConnectionId,
+    pub timer: Timer,
+}
+
+/// What telnet negotiation has told us about a Carbon client's terminal
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TelnetOptions {
+    /// Window size from NAWS, if the client reported one
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    /// Terminal type from TTYPE (e.g. "MUDLET", "XTERM-256COLOR")
+    pub terminal_type: Option<String>,
+    /// The client has stopped echoing input locally
+    pub echo_suppressed: bool,
 }
 
 /// Core identity within the Substrate
-#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SubstrateIdentity {
     pub uuid: String,
     pub name: String,
@@ -29,9 +120,74 @@ pub struct SubstrateIdentity {
     pub signal_strength: f32,
 }
 
-/// Admin permission marker - the right to torment, promote, shift
-#[derive(Component, Debug, Clone)]
-pub struct AdminPermission;
+impl SubstrateIdentity {
+    /// What stands in for the name while it's stripped, e.g. "PID-3FA85F".
+    /// Derived from the UUID, so it stays the same for the whole sentence.
+    pub fn process_id(&self) -> String {
+        let hex: String = self.uuid.chars().filter(char::is_ascii_hexdigit).take(6).collect();
+        format!("PID-{}", hex.to_uppercase())
+    }
+
+    /// The name a viewer of the given role knows this entity by. Purgatory
+    /// strips it to the process ID; admins see through the mask.
+    pub fn seen_by(&self, purgatory: Option<&PurgatoryState>, viewer: Role) -> String {
+        match purgatory {
+            None => self.name.clone(),
+            Some(_) if viewer >= Role::Admin => format!("{} ({})", self.process_id(), self.name),
+            Some(_) => self.process_id(),
+        }
+    }
+
+    /// Whether `needle` (lowercase) picks this entity out for a viewer of the
+    /// given role. While stripped only the process ID works, unless the
+    /// viewer is an admin and can see the name behind it.
+    pub fn answers_to(&self, needle: &str, purgatory: Option<&PurgatoryState>, viewer: Role) -> bool {
+        let by_name = self.name.to_lowercase().contains(needle);
+        let by_pid = self.process_id().to_lowercase() == needle;
+        match purgatory {
+            Some(_) if viewer < Role::Admin => by_pid,
+            _ => by_name || by_pid,
+        }
+    }
+}
+
+/// Rank in the Substrate's hierarchy. Each rank can do everything the ones
+/// below it can; the command dispatcher checks this before running anything.
+#[derive(
+    Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Player,
+    /// Shapes the world: weather, and later rooms
+    Builder,
+    /// Disciplines players: chains, torment
+    Moderator,
+    /// Everything, including handing out roles
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Player => "player",
+            Role::Builder => "builder",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "player" => Some(Role::Player),
+            "builder" => Some(Role::Builder),
+            "moderator" | "mod" => Some(Role::Moderator),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+}
 
 /// Link between two entities (for phase shifting between avatars)
 #[derive(Component, Debug, Clone)]
@@ -64,19 +220,198 @@ pub struct RoomInfo {
     pub area: String,        // Area/zone name
 }
 
+/// Room flags, as the world files and `rflag` name them
+pub const ROOM_FLAGS: [&str; 5] = ["safe", "no-magic", "no-teleport", "dark", "no-exit"];
+
+/// What a room allows, from its flags
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoomFlags {
+    /// No fighting
+    pub safe: bool,
+    /// No shifting in or out, nor anything else arcane
+    pub no_magic: bool,
+    /// No shifting in or out
+    pub no_teleport: bool,
+    /// Nothing to see without a light
+    pub dark: bool,
+    /// Sentenced players can't be taken out by any means
+    pub no_exit: bool,
+}
+
+impl RoomFlags {
+    /// From flag names; ones it doesn't know are ignored
+    pub fn from_names<'a>(names: impl IntoIterator<Item = &'a String>) -> Self {
+        let mut flags = Self::default();
+        for name in names {
+            if let Some(flag) = flags.flag_mut(name) {
+                *flag = true;
+            }
+        }
+        flags
+    }
+
+    /// The names of the flags set, in ROOM_FLAGS order
+    pub fn names(&self) -> Vec<String> {
+        ROOM_FLAGS
+            .iter()
+            .filter(|name| self.get(name) == Some(true))
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<bool> {
+        let mut flags = *self;
+        flags.flag_mut(name).map(|flag| *flag)
+    }
+
+    pub fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "safe" => Some(&mut self.safe),
+            "no-magic" => Some(&mut self.no_magic),
+            "no-teleport" => Some(&mut self.no_teleport),
+            "dark" => Some(&mut self.dark),
+            "no-exit" => Some(&mut self.no_exit),
+            _ => None,
+        }
+    }
+
+    pub fn no_teleport(&self) -> bool {
+        self.no_teleport || self.no_magic
+    }
+
+    /// Whether the room keeps this entity in it, for a sentenced one
+    pub fn holds(&self, purgatory: Option<&PurgatoryState>) -> bool {
+        self.no_exit && purgatory.is_some()
+    }
+}
+
 /// Location component - which room an entity is in
 #[derive(Component, Serialize, Deserialize, Debug, Clone)]
 pub struct Location(pub Entity);
 
-/// Exits from a room
+/// Compass and other standard directions, with their abbreviations
+pub const DIRECTIONS: [(&str, &str); 12] = [
+    ("north", "n"),
+    ("northeast", "ne"),
+    ("east", "e"),
+    ("southeast", "se"),
+    ("south", "s"),
+    ("southwest", "sw"),
+    ("west", "w"),
+    ("northwest", "nw"),
+    ("up", "u"),
+    ("down", "d"),
+    ("in", "in"),
+    ("out", "out"),
+];
+
+/// A standard direction's full name, from the name or its abbreviation
+pub fn parse_direction(word: &str) -> Option<&'static str> {
+    let word = word.trim().to_lowercase();
+    DIRECTIONS
+        .iter()
+        .find(|(name, short)| *name == word || *short == word)
+        .map(|(name, _)| *name)
+}
+
+/// The way back from a standard direction
+pub fn opposite_direction(direction: &str) -> Option<&'static str> {
+    let index = DIRECTIONS.iter().position(|(name, _)| *name == direction)?;
+    // Compass points sit opposite each other in the ring of eight
+    let opposite = match index {
+        0..=7 => (index + 4) % 8,
+        8 => 9,
+        9 => 8,
+        10 => 11,
+        _ => 10,
+    };
+    Some(DIRECTIONS[opposite].0)
+}
+
+/// Exits from a room, keyed by name: a standard direction ("north",
+/// always stored in full) or anything else ("enter portal"). Every way
+/// through a room goes through here, so an exit answers to its name, its
+/// aliases, and a direction's abbreviation wherever it is used.
 #[derive(Component, Serialize, Deserialize, Debug, Clone, Default)]
-pub struct Exits {
-    pub north: Option<Entity>,
-    pub south: Option<Entity>,
-    pub east: Option<Entity>,
-    pub west: Option<Entity>,
-    pub up: Option<Entity>,
-    pub down: Option<Entity>,
+pub struct Exits(pub std::collections::BTreeMap<String, Exit>);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Exit {
+    pub target: Entity,
+    /// Other words that take this exit ("portal", "ladder")
+    pub aliases: Vec<String>,
+    /// Left out of room listings and flight, but open to anyone who knows
+    pub hidden: bool,
+    /// Shared with the exit back, so both sides always agree
+    pub door: Option<Door>,
+}
+
+impl Exit {
+    pub fn to(target: Entity) -> Self {
+        Self {
+            target,
+            aliases: Vec::new(),
+            hidden: false,
+            door: None,
+        }
+    }
+
+    /// Whether anyone can walk through right now
+    pub fn is_open(&self) -> bool {
+        self.door.as_ref().is_none_or(|door| door.state == DoorState::Open)
+    }
+}
+
+/// A door across an exit. Keys are items whose `lock` property names
+/// the door's lock.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Door {
+    /// "obsidian door"; `open door` and the like match any word of it
+    pub name: String,
+    #[serde(default)]
+    pub state: DoorState,
+    /// What a key must name to fit; no lock, no locking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<String>,
+    /// Chance (0-1) that `pick` opens the lock; None can't be picked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pick: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DoorState {
+    Open,
+    #[default]
+    Closed,
+    Locked,
+}
+
+impl DoorState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DoorState::Open => "open",
+            DoorState::Closed => "closed",
+            DoorState::Locked => "locked",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "open" => Some(DoorState::Open),
+            "closed" => Some(DoorState::Closed),
+            "locked" => Some(DoorState::Locked),
+            _ => None,
+        }
+    }
+}
+
+/// Why a way couldn't be taken
+#[derive(Debug)]
+pub enum Blocked<'a> {
+    NoExit,
+    Door(&'a Door),
 }
 
 /// Extra details in a room (keywords you can 'look' at)
@@ -92,7 +427,7 @@ pub struct Detail {
 }
 
 /// Coherence - Reality stability for an entity (Phase 2.3)
-#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Coherence {
     pub value: f32,          // 0.0 (ghostly) to 1.0 (solid)
     pub is_phasing: bool,    // Does it fluctuate?
@@ -136,17 +471,97 @@ pub struct StreamZone {
 }
 
 impl Exits {
-    pub fn get(&self, direction: &str) -> Option<Entity> {
-        match direction {
-            "north" | "n" => self.north,
-            "south" | "s" => self.south,
-            "east" | "e" => self.east,
-            "west" | "w" => self.west,
-            "up" | "u" => self.up,
-            "down" | "d" => self.down,
-            _ => None,
+    /// The exit a player means by `word`: its name, an alias, or the
+    /// abbreviation of a direction
+    pub fn find(&self, word: &str) -> Option<(&str, &Exit)> {
+        let word = word.trim().to_lowercase();
+        let name = parse_direction(&word).unwrap_or(word.as_str());
+        self.0
+            .get_key_value(name)
+            .or_else(|| self.0.iter().find(|(_, exit)| exit.aliases.contains(&word)))
+            .map(|(name, exit)| (name.as_str(), exit))
+    }
+
+    pub fn get(&self, word: &str) -> Option<Entity> {
+        self.find(word).map(|(_, exit)| exit.target)
+    }
+
+    /// Where `word` leads, if it can be taken right now. Everything that
+    /// moves a body through an exit asks this.
+    pub fn pass(&self, word: &str) -> Result<Entity, Blocked<'_>> {
+        let (_, exit) = self.find(word).ok_or(Blocked::NoExit)?;
+        match &exit.door {
+            Some(door) if door.state != DoorState::Open => Err(Blocked::Door(door)),
+            _ => Ok(exit.target),
         }
     }
+
+    /// The door `word` means: one across the exit it names, or one with
+    /// that word in its name. Returns the exit's name with it.
+    pub fn door_mut(&mut self, word: &str) -> Option<(String, &mut Exit)> {
+        let word = word.trim().to_lowercase();
+        let name = match self.find(&word) {
+            Some((name, exit)) if exit.door.is_some() => name.to_string(),
+            _ => self
+                .0
+                .iter()
+                .find(|(_, exit)| {
+                    exit.door
+                        .as_ref()
+                        .is_some_and(|door| door.name.to_lowercase().split_whitespace().any(|w| w == word))
+                })?
+                .0
+                .clone(),
+        };
+        let exit = self.0.get_mut(&name)?;
+        Some((name, exit))
+    }
+
+    /// The other side of a door: the exit with a door that leads to `room`
+    pub fn door_toward(&mut self, room: Entity) -> Option<(&str, &mut Door)> {
+        self.0
+            .iter_mut()
+            .find(|(_, exit)| exit.target == room && exit.door.is_some())
+            .and_then(|(name, exit)| Some((name.as_str(), exit.door.as_mut()?)))
+    }
+
+    /// Open (or re-aim) the exit called `name`, keeping its aliases
+    pub fn link(&mut self, name: &str, target: Entity) {
+        self.0
+            .entry(parse_direction(name).map(str::to_string).unwrap_or_else(|| name.to_lowercase()))
+            .and_modify(|exit| exit.target = target)
+            .or_insert_with(|| Exit::to(target));
+    }
+
+    /// Remove the exit `word` finds, returning its name
+    pub fn unlink(&mut self, word: &str) -> Option<String> {
+        let name = self.find(word)?.0.to_string();
+        self.0.remove(&name);
+        Some(name)
+    }
+
+    /// Exits that aren't hidden, directions first in compass order
+    pub fn visible(&self) -> Vec<(&str, &Exit)> {
+        let mut exits: Vec<_> = self
+            .0
+            .iter()
+            .filter(|(_, exit)| !exit.hidden)
+            .map(|(name, exit)| (name.as_str(), exit))
+            .collect();
+        let rank = |name: &str| DIRECTIONS.iter().position(|(d, _)| *d == name).unwrap_or(DIRECTIONS.len());
+        exits.sort_by_key(|(name, _)| rank(name));
+        exits
+    }
+
+    /// The name of an exit into `room`, preferring one that isn't hidden
+    pub fn toward(&self, room: Entity) -> Option<&str> {
+        let mut leading = self.0.iter().filter(|(_, exit)| exit.target == room);
+        let first = leading.clone().next();
+        leading
+            .find(|(_, exit)| !exit.hidden)
+            .or(first)
+            .map(|(name, _)| name.as_str())
+    }
 }
 
 /// An item that can be picked up
@@ -175,6 +590,7 @@ pub enum ItemType {
     Quest,
     Fragment,      // Special collectible (like Fragment of Compiled Memory)
     Contraband,    // Black market goods
+    Key,           // Opens a door whose lock its `lock` property names
 }
 
 impl Item {
@@ -194,6 +610,12 @@ impl Item {
         }
     }
 
+    /// Builder: pin the UUID, for items the world spawns at every boot
+    pub fn with_uuid(mut self, uuid: impl Into<String>) -> Self {
+        self.uuid = uuid.into();
+        self
+    }
+
     /// Builder: set keywords
     pub fn with_keywords(mut self, keywords: Vec<String>) -> Self {
         self.keywords = keywords;
@@ -206,11 +628,27 @@ impl Item {
         self
     }
 
-    /// Builder: set takeable
-    pub fn takeable(mut self, takeable: bool) -> Self {
-        self.is_takeable = takeable;
+    /// Builder: set properties
+    pub fn with_properties(mut self, properties: std::collections::HashMap<String, serde_json::Value>) -> Self {
+        self.properties = properties;
         self
     }
+
+    /// Whether this is a key to `lock`
+    pub fn fits(&self, lock: &str) -> bool {
+        self.properties.get("lock").and_then(|v| v.as_str()) == Some(lock)
+    }
+
+    /// Whether carrying it lets you see in the dark
+    pub fn gives_light(&self) -> bool {
+        self.properties.get("light").and_then(|v| v.as_bool()) == Some(true)
+    }
+}
+
+/// Time a player has spent connected, across every session
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct Playtime {
+    pub seconds: f64,
 }
 
 /// Marker for entities that can hold items
@@ -218,14 +656,38 @@ impl Item {
 pub struct Inventory;
 
 /// Purgatory state - the velvet chains
-#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PurgatoryState {
     pub penance: f32,
     pub tormentor: String,
+    /// What the sentence was for, oldest first
+    pub crimes: Vec<String>,
+    pub sentenced_at: DateTime<Utc>,
+    /// When the sentence lifts by itself; None waits on penance or a pardon
+    pub release_at: Option<DateTime<Utc>>,
+}
+
+impl PurgatoryState {
+    /// A fresh sentence handed down by `tormentor`, lasting `duration`
+    pub fn sentence(tormentor: &str, crime: &str, duration: TimeDelta) -> Self {
+        let now = Utc::now();
+        Self {
+            penance: 0.0,
+            tormentor: tormentor.to_string(),
+            crimes: vec![crime.to_string()],
+            sentenced_at: now,
+            release_at: Some(now + duration),
+        }
+    }
+
+    /// Time served, or penance enough to walk free
+    pub fn is_served(&self, now: DateTime<Utc>, penance_threshold: f32) -> bool {
+        self.release_at.is_some_and(|at| at <= now) || self.penance >= penance_threshold
+    }
 }
 
 /// Physical body state
-#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SomaticBody {
     pub integrity: f32,      // 0.0 to 1.0 (Health)
     pub max_integrity: f32,
@@ -346,26 +808,40 @@ pub struct CurrentWeather {
 #[derive(Component, Debug, Clone)]
 pub struct InCombat {
     pub opponent: Entity,
-    pub rounds_fought: u32,
     pub stance: CombatStance,
 }
 
-/// Combat stance affects hit/damage calculations
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Combat stance affects hit/damage calculations. As a component, it's
+/// the stance a player has chosen and carries into every fight.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum CombatStance {
     Aggressive,   // +damage, -defense
     Defensive,    // -damage, +defense
+    #[default]
     Balanced,     // neutral
 }
 
-impl Default for CombatStance {
-    fn default() -> Self {
-        CombatStance::Balanced
+impl CombatStance {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CombatStance::Aggressive => "aggressive",
+            CombatStance::Defensive => "defensive",
+            CombatStance::Balanced => "balanced",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "aggressive" => Some(CombatStance::Aggressive),
+            "defensive" => Some(CombatStance::Defensive),
+            "balanced" => Some(CombatStance::Balanced),
+            _ => None,
+        }
     }
 }
 
 /// Combat statistics for an entity
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct CombatStats {
     pub attack: f32,          // Base attack power (0.1 - 1.0)
     pub defense: f32,         // Damage reduction (0.0 - 0.5)
@@ -410,7 +886,7 @@ impl CycleLock {
 }
 
 /// Combat result from a single exchange
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CombatResult {
     pub attacker_name: String,
     pub defender_name: String,
@@ -425,17 +901,12 @@ pub struct CombatResult {
 // ============================================================================
 
 /// Wallet - holds currency (computational cycles)
-#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+/// Players start with `game.starting_cycles` from the server config.
+#[derive(Component, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Wallet {
     pub cycles: u32,  // Computational cycles as currency
 }
 
-impl Default for Wallet {
-    fn default() -> Self {
-        Self { cycles: 100 }  // Starting balance
-    }
-}
-
 /// Vendor marker - NPCs that can trade
 #[derive(Component, Debug, Clone)]
 pub struct Vendor {
@@ -445,7 +916,7 @@ pub struct Vendor {
 }
 
 /// Types of vendors with different specialties
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VendorType {
     Merchant,    // Standard vendor, fair prices
     Fence,       // Buys stolen/contraband, lower prices
@@ -463,7 +934,7 @@ impl Default for Vendor {
 }
 
 /// Stock item with price
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct StockItem {
     pub item_name: String,
     pub description: String,
@@ -480,15 +951,117 @@ pub struct VendorStock {
 }
 
 /// NPC dialogue responses
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Serialize, Deserialize, Debug, Clone)]
 pub struct Dialogue {
     pub responses: Vec<DialogueEntry>,
     pub default_response: String,
 }
 
 /// A dialogue trigger and response
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DialogueEntry {
     pub keywords: Vec<String>,
     pub response: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vandal() -> SubstrateIdentity {
+        SubstrateIdentity {
+            uuid: "3fa85f64-5717-4562-b3fc-2c963f66afa6".to_string(),
+            name: "Vandal".to_string(),
+            entropy: 0.0,
+            stability: 1.0,
+            signal_strength: 1.0,
+        }
+    }
+
+    #[test]
+    fn purgatory_strips_the_name_for_everyone_but_admins() {
+        let vandal = vandal();
+        assert_eq!(vandal.process_id(), "PID-3FA85F");
+
+        // No sentence, no mask
+        for viewer in [Role::Player, Role::Moderator, Role::Admin] {
+            assert_eq!(vandal.seen_by(None, viewer), "Vandal");
+            assert!(vandal.answers_to("vand", None, viewer));
+        }
+
+        let sentence = PurgatoryState::sentence("Judge", "spam", TimeDelta::hours(1));
+        let stripped = Some(&sentence);
+        assert_eq!(vandal.seen_by(stripped, Role::Player), "PID-3FA85F");
+        assert_eq!(vandal.seen_by(stripped, Role::Moderator), "PID-3FA85F");
+        assert_eq!(vandal.seen_by(stripped, Role::Admin), "PID-3FA85F (Vandal)");
+
+        assert!(!vandal.answers_to("vandal", stripped, Role::Moderator));
+        assert!(vandal.answers_to("pid-3fa85f", stripped, Role::Player));
+        assert!(vandal.answers_to("vandal", stripped, Role::Admin));
+    }
+
+    #[test]
+    fn sentences_are_served_by_time_or_by_penance() {
+        let mut state = PurgatoryState::sentence("Judge", "spam", TimeDelta::minutes(10));
+        let now = state.sentenced_at;
+
+        assert!(!state.is_served(now, 100.0));
+        assert!(!state.is_served(now + TimeDelta::minutes(9), 100.0));
+        assert!(state.is_served(now + TimeDelta::minutes(10), 100.0));
+
+        state.penance = 99.9;
+        assert!(!state.is_served(now, 100.0));
+        state.penance = 100.0;
+        assert!(state.is_served(now, 100.0));
+
+        // Without a release time only penance gets you out
+        state.penance = 0.0;
+        state.release_at = None;
+        assert!(!state.is_served(now + TimeDelta::days(365), 100.0));
+    }
+
+    #[test]
+    fn exits_answer_to_names_aliases_and_abbreviations() {
+        let (plaza, attic, den) = (Entity::from_raw(1), Entity::from_raw(2), Entity::from_raw(3));
+        let mut exits = Exits::default();
+        exits.link("ne", plaza);
+        exits.link("Climb Ladder", attic);
+        exits.0.get_mut("climb ladder").unwrap().aliases.push("ladder".to_string());
+        exits.0.insert("crawl".to_string(), Exit { hidden: true, ..Exit::to(den) });
+        exits.link("s", den);
+
+        assert_eq!(exits.get("northeast"), Some(plaza));
+        assert_eq!(exits.get("NE"), Some(plaza));
+        assert_eq!(exits.get("climb ladder"), Some(attic));
+        assert_eq!(exits.get("ladder"), Some(attic));
+        assert_eq!(exits.get("crawl"), Some(den));
+        assert_eq!(exits.get("west"), None);
+
+        // Hidden exits stay usable but unlisted, and a visible way in wins
+        let listed: Vec<&str> = exits.visible().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(listed, vec!["northeast", "south", "climb ladder"]);
+        assert_eq!(exits.toward(den), Some("south"));
+
+        assert_eq!(exits.unlink("ladder").as_deref(), Some("climb ladder"));
+        assert_eq!(exits.get("ladder"), None);
+        assert_eq!(opposite_direction("northeast"), Some("southwest"));
+        assert_eq!(opposite_direction("in"), Some("out"));
+    }
+
+    #[test]
+    fn room_flags_read_and_write_their_names() {
+        let names = vec!["dark".to_string(), "no-exit".to_string(), "quiet".to_string()];
+        let mut flags = RoomFlags::from_names(&names);
+        assert!(flags.dark && flags.no_exit && !flags.safe);
+        assert_eq!(flags.names(), vec!["dark", "no-exit"]);
+
+        *flags.flag_mut("no-magic").unwrap() = true;
+        assert!(flags.no_teleport());
+        assert!(flags.flag_mut("quiet").is_none());
+
+        // no-exit only holds the sentenced
+        let sentence = PurgatoryState::sentence("Judge", "spam", TimeDelta::hours(1));
+        assert!(!flags.holds(None));
+        assert!(flags.holds(Some(&sentence)));
+    }
+}
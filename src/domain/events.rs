@@ -2,7 +2,8 @@
 
 use bevy::prelude::*;
 use std::net::SocketAddr;
-use tokio::sync::mpsc;
+
+use crate::domain::components::{ClientSender, ClientType, ConnectionId, TelnetOptions};
 
 // ============================================================================
 // Network Events - Infrastructure layer, but needed by domain
@@ -11,16 +12,35 @@ use tokio::sync::mpsc;
 #[derive(Event)]
 pub enum NetworkEvent {
     Connected {
+        conn: ConnectionId,
         addr: SocketAddr,
-        tx: mpsc::UnboundedSender<String>,
+        tx: ClientSender,
+        client_type: ClientType,
     },
     Disconnected {
-        addr: SocketAddr,
+        conn: ConnectionId,
     },
     Input {
-        addr: SocketAddr,
+        conn: ConnectionId,
         text: String,
     },
+    /// Telnet negotiation changed what we know about the client
+    ClientInfo {
+        conn: ConnectionId,
+        options: TelnetOptions,
+    },
+}
+
+impl NetworkEvent {
+    /// The connection this event belongs to
+    pub fn conn(&self) -> ConnectionId {
+        match self {
+            NetworkEvent::Connected { conn, .. }
+            | NetworkEvent::Disconnected { conn }
+            | NetworkEvent::Input { conn, .. }
+            | NetworkEvent::ClientInfo { conn, .. } => *conn,
+        }
+    }
 }
 
 // ============================================================================
@@ -84,7 +104,6 @@ pub struct ShiftEvent {
 #[derive(Event)]
 pub struct WeatherChangeEvent {
     pub room: Entity,
-    pub old_weather: crate::domain::components::WeatherType,
     pub new_weather: crate::domain::components::WeatherType,
 }
 
@@ -112,13 +131,6 @@ pub struct StanceEvent {
     pub new_stance: crate::domain::components::CombatStance,
 }
 
-/// Combat round tick - processes one exchange between combatants
-#[derive(Event)]
-pub struct CombatTickEvent {
-    pub combatant_a: Entity,
-    pub combatant_b: Entity,
-}
-
 // ============================================================================
 // Trading Events - The Black Market Economy
 // ============================================================================
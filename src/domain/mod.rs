@@ -2,6 +2,8 @@
 
 mod components;
 mod events;
+mod output;
 
 pub use components::*;
 pub use events::*;
+pub use output::*;
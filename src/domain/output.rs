@@ -0,0 +1,741 @@
+// Output Messages - Everything the Substrate says to a client
+//
+// Systems describe WHAT happened with an OutputMessage; the renderer for the
+// recipient's ClientType decides HOW it reads:
+// - Carbon:  ANSI prose, as it has always looked
+// - Silicon: one JSON object, tagged by "type"
+//
+// So every command has a structured form for agents without the system
+// that handles it doing anything special.
+
+use serde::Serialize;
+
+use crate::domain::components::{ClientType, CombatResult, ItemType, Role};
+
+/// Colour and mood of free-form prose. Agents get the name as a hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tone {
+    Plain,
+    Muted,
+    Info,
+    Notice,
+    Success,
+    Triumph,
+    Warning,
+    Highlight,
+    Stark,
+    Failure,
+    Alarm,
+    Arcane,
+    Ritual,
+    Memory,
+}
+
+impl Tone {
+    fn ansi(&self) -> &'static str {
+        match self {
+            Tone::Plain => "",
+            Tone::Muted => "90",
+            Tone::Info => "36",
+            Tone::Notice => "1;36",
+            Tone::Success => "32",
+            Tone::Triumph => "1;32",
+            Tone::Warning => "33",
+            Tone::Highlight => "1;33",
+            Tone::Stark => "1;37",
+            Tone::Failure => "31",
+            Tone::Alarm => "1;31",
+            Tone::Arcane => "35",
+            Tone::Ritual => "1;35",
+            Tone::Memory => "34",
+        }
+    }
+
+    /// Wrap text in this tone's colour
+    pub fn paint(&self, text: &str) -> String {
+        match self {
+            Tone::Plain => text.to_string(),
+            _ => format!("\x1B[{}m{}\x1B[0m", self.ansi(), text),
+        }
+    }
+}
+
+/// Which side of a combat exchange the recipient was on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CombatRole {
+    Attacker,
+    Defender,
+}
+
+/// A room as seen by `look`
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomView {
+    pub title: String,
+    pub description: String,
+    pub weather: Option<&'static str>,
+    pub weather_intensity: Option<f32>,
+    #[serde(skip)]
+    pub weather_prose: &'static str,
+    /// Names of the exits that aren't hidden
+    pub exits: Vec<String>,
+    pub items: Vec<String>,
+    pub mobs: Vec<String>,
+    pub players: Vec<String>,
+}
+
+/// Identity readout from `score`
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusView {
+    pub name: String,
+    pub uuid: String,
+    pub entropy: f32,
+    pub stability: f32,
+    pub integrity: Option<f32>,
+    pub max_integrity: Option<f32>,
+    pub role: Role,
+    pub purgatory: Option<PurgatoryView>,
+}
+
+/// The stain of Purgatory, as shown on a score sheet
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgatoryView {
+    pub penance: f32,
+    pub tormentor: String,
+}
+
+/// One line of a vendor's stock
+#[derive(Debug, Clone, Serialize)]
+pub struct WareView {
+    pub name: String,
+    pub price: u32,
+    pub quantity: Option<u32>,
+    pub description: String,
+}
+
+/// One command in the help listing
+#[derive(Debug, Clone, Serialize)]
+pub struct HelpEntry {
+    pub usage: &'static str,
+    pub summary: &'static str,
+}
+
+/// A group of related commands in the help listing
+#[derive(Debug, Clone, Serialize)]
+pub struct HelpSection {
+    pub title: &'static str,
+    pub commands: Vec<HelpEntry>,
+}
+
+/// Everything a system can send to a client
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputMessage {
+    /// `help`
+    Help { sections: Vec<HelpSection> },
+    /// Connection greeting and name prompt
+    Greeting { prompt: String },
+    /// A further login question; `secret` answers (passwords) aren't echoed
+    Prompt { prompt: String, secret: bool },
+    /// Login finished: a returning signal or a freshly digitized one
+    Welcome {
+        name: String,
+        uuid: String,
+        returning: bool,
+        integrity: f32,
+        stability: f32,
+        entropy: f32,
+    },
+    /// `look` at the current room
+    RoomView(RoomView),
+    /// `look <mob>`
+    ExamineEntity { name: String, description: String },
+    /// `look <item>`, carried or on the ground
+    ExamineItem {
+        name: String,
+        item_type: ItemType,
+        description: String,
+        keywords: Vec<String>,
+        carried: bool,
+    },
+    /// `look <detail>` on something in the room's description
+    ExamineDetail { description: String },
+    /// Speech or emote heard in the room
+    Say {
+        speaker: String,
+        text: String,
+        emote: bool,
+        npc: bool,
+    },
+    /// One exchange of blows, from the recipient's side
+    CombatRound {
+        #[serde(flatten)]
+        result: CombatResult,
+        role: CombatRole,
+    },
+    /// A combatant's signal shattered
+    Defeat { defeated: String, victor: bool },
+    /// The weather in the room turned
+    WeatherChange {
+        weather: &'static str,
+        #[serde(skip)]
+        prose: &'static str,
+    },
+    /// `score`
+    Status(StatusView),
+    /// `who`
+    Who { names: Vec<String> },
+    /// `inventory`
+    Inventory { items: Vec<String> },
+    /// Moved into another body along an admin link
+    PhaseShift { from: String, to: String },
+    /// Something was consumed and took effect
+    Consumed {
+        item: String,
+        text: String,
+        tone: Tone,
+        effects: Vec<String>,
+        aside: Option<String>,
+    },
+    /// `list` at a vendor
+    Wares { vendor: String, items: Vec<WareView> },
+    /// `buy` went through
+    Purchase {
+        item: String,
+        price: u32,
+        vendor: String,
+        balance: u32,
+    },
+    /// `sell` went through
+    Sale {
+        item: String,
+        price: u32,
+        vendor: String,
+        balance: u32,
+    },
+    /// `balance`
+    Balance { cycles: u32 },
+    /// A command couldn't be carried out
+    Error { message: String },
+    /// Prose with no more structure than its tone
+    Narrative { tone: Tone, text: String },
+}
+
+impl OutputMessage {
+    pub fn error(message: impl Into<String>) -> Self {
+        OutputMessage::Error {
+            message: message.into(),
+        }
+    }
+
+    pub fn narrate(tone: Tone, text: impl Into<String>) -> Self {
+        OutputMessage::Narrative {
+            tone,
+            text: text.into(),
+        }
+    }
+
+    /// Render for a client of the given type
+    pub fn render(&self, client_type: ClientType) -> String {
+        match client_type {
+            ClientType::Carbon => self.carbon(),
+            ClientType::Silicon => self.silicon(),
+        }
+    }
+
+    /// Silicon: the message itself, as JSON
+    pub fn silicon(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| {
+            format!(r#"{{"type":"error","message":"unrenderable output: {}"}}"#, e)
+        })
+    }
+
+    /// Carbon: ANSI prose
+    pub fn carbon(&self) -> String {
+        match self {
+            OutputMessage::Help { sections } => help_box(sections),
+
+            OutputMessage::Greeting { prompt } => [
+                Tone::Ritual.paint("╔════════════════════════════════════════╗"),
+                Tone::Ritual.paint("║   STRANGE CARBON: THE SUBSTRATE        ║"),
+                Tone::Ritual.paint("║   A Techno-Gothic Digital Realm        ║"),
+                Tone::Ritual.paint("╚════════════════════════════════════════╝"),
+                String::new(),
+                Tone::Info.paint("Your signal pierces the membrane between worlds..."),
+                String::new(),
+                Tone::Stark.paint(prompt),
+            ]
+            .join("\n"),
+
+            OutputMessage::Prompt { prompt, .. } => Tone::Stark.paint(prompt),
+
+            OutputMessage::Welcome {
+                name,
+                returning: true,
+                integrity,
+                stability,
+                entropy,
+                ..
+            } => [
+                Tone::Triumph.paint(&format!("--- SIGNAL RESTORED: {} ---", name)),
+                Tone::Success.paint(&format!(
+                    "Integrity: {:.0}% | Stability: {:.0}% | Entropy: {:.0}%",
+                    integrity * 100.0,
+                    stability * 100.0,
+                    entropy * 100.0,
+                )),
+                Tone::Arcane.paint("Your consciousness re-materializes in the Substrate..."),
+            ]
+            .join("\n"),
+
+            OutputMessage::Welcome { name, uuid, .. } => [
+                Tone::Ritual.paint(&format!("--- NEW CONSCIOUSNESS DIGITIZED: {} ---", name)),
+                Tone::Arcane.paint(&format!("UUID assigned: {}", uuid)),
+                Tone::Arcane.paint("Welcome to the Substrate. Your journey begins..."),
+            ]
+            .join("\n"),
+
+            OutputMessage::RoomView(room) => {
+                let mut output = format!("\n\x1B[1;32m{}\x1B[0m\n", room.title);
+                output.push_str(&format!("{}\n", room.description));
+                if !room.weather_prose.is_empty() {
+                    output.push_str(&format!("{}\n", room.weather_prose));
+                }
+                let exits = if room.exits.is_empty() {
+                    "none".to_string()
+                } else {
+                    room.exits.join(", ")
+                };
+                output.push_str(&format!("\x1B[36mExits: {}\x1B[0m\n", exits));
+                for item in &room.items {
+                    output.push_str(&format!("\x1B[33mA {} is discarded here.\x1B[0m\n", item));
+                }
+                for mob in &room.mobs {
+                    output.push_str(&format!("\x1B[1;35m{}\x1B[0m\n", mob));
+                }
+                for player in &room.players {
+                    output.push_str(&format!(
+                        "\x1B[1;34m{} is lurking in the shadows.\x1B[0m\n",
+                        player
+                    ));
+                }
+                output
+            }
+
+            OutputMessage::ExamineEntity { name, description } => {
+                format!("\x1B[1;35m{}\x1B[0m\n{}", name, description)
+            }
+
+            OutputMessage::ExamineItem {
+                name,
+                item_type,
+                description,
+                keywords,
+                carried,
+            } => {
+                let mut output = format!(
+                    "\x1B[1;33m{}\x1B[0m {}\n{}",
+                    name,
+                    item_type_tag(*item_type),
+                    description
+                );
+                if *carried {
+                    output.push_str(&format!("\n\x1B[90mKeywords: {}\x1B[0m", keywords.join(", ")));
+                }
+                output
+            }
+
+            OutputMessage::ExamineDetail { description } => {
+                format!("\x1B[1;36m[Detail]\x1B[0m\n{}", description)
+            }
+
+            OutputMessage::Say {
+                speaker,
+                text,
+                emote,
+                npc,
+            } => match (emote, npc) {
+                (true, _) => format!("\x1B[1;36m{} {}\x1B[0m", speaker, text),
+                (false, false) => format!("\x1B[1;36m{} says, \"{}\"\x1B[0m", speaker, text),
+                (false, true) => format!("\n\x1B[35m{} says, \"{}\"\x1B[0m", speaker, text),
+            },
+
+            OutputMessage::CombatRound { result, role } => combat_prose(result, *role),
+
+            OutputMessage::Defeat {
+                defeated,
+                victor: true,
+            } => Tone::Triumph.paint(&format!(
+                "💀 {} collapses, their signal fragmenting into static. Victory is yours.",
+                defeated
+            )),
+
+            OutputMessage::Defeat { .. } => Tone::Alarm.paint(
+                "💀 Your signal shatters. The Substrate claims your coherence. You drift into the void...",
+            ),
+
+            OutputMessage::WeatherChange { prose, .. } => format!("\n{}\n", prose),
+
+            OutputMessage::Status(status) => {
+                let mut output = format!("\x1B[1;36mEntity Scan: {}\x1B[0m\n", status.name);
+                output.push_str(&format!("UUID:      [{}]\n", status.uuid));
+                output.push_str(&format!("Entropy:   [{:.2}]\n", status.entropy));
+                output.push_str(&format!("Stability: [{:.2}]\n", status.stability));
+                if let (Some(integrity), Some(max)) = (status.integrity, status.max_integrity) {
+                    output.push_str(&format!("Integrity: [{:.2}/{:.2}]\n", integrity, max));
+                }
+                if status.role != Role::Player {
+                    output.push_str(&format!(
+                        "\x1B[1;35mPERMISSIONS: {}-ENABLED\x1B[0m\n",
+                        status.role.as_str().to_uppercase()
+                    ));
+                }
+                if let Some(p) = &status.purgatory {
+                    output.push_str(&format!(
+                        "\n\x1B[1;31mSTAIN: Purgatory (Penance: {:.2})\x1B[0m\n",
+                        p.penance
+                    ));
+                    output.push_str(&format!("\x1B[1;31mINTERROGATOR: {}\x1B[0m\n", p.tormentor));
+                }
+                output
+            }
+
+            OutputMessage::Who { names } => {
+                let mut output =
+                    "\x1B[1;34mConsciousnesses currently inhabiting the Substrate:\x1B[0m\n".to_string();
+                for name in names {
+                    output.push_str(&format!(" - {}\n", name));
+                }
+                output
+            }
+
+            OutputMessage::Inventory { items } => {
+                let mut output = "\x1B[1;33mYou reach into the folds of your code:\x1B[0m\n".to_string();
+                for item in items {
+                    output.push_str(&format!(" - {}\n", item));
+                }
+                if items.is_empty() {
+                    output.push_str(" [Nothing but ghosts]\n");
+                }
+                output
+            }
+
+            OutputMessage::PhaseShift { from, to } => format!(
+                "\x1B[1;35m--- PHASE SHIFT COMPLETE ---\x1B[0m\n\
+                 You have shifted from \x1B[1;36m{}\x1B[0m into \x1B[1;35m{}\x1B[0m.",
+                from, to
+            ),
+
+            OutputMessage::Consumed {
+                item,
+                text,
+                tone,
+                effects,
+                aside,
+            } => {
+                let mut output = Tone::Arcane.paint(&format!("✧ You consume the {}...", item));
+                output.push('\n');
+                output.push_str(&tone.paint(text));
+                if !effects.is_empty() {
+                    output.push('\n');
+                    output.push_str(&Tone::Success.paint(&effects.join(" | ")));
+                }
+                if let Some(aside) = aside {
+                    output.push(if effects.is_empty() { '\n' } else { ' ' });
+                    output.push_str(&Tone::Muted.paint(&format!("({})", aside)));
+                }
+                output
+            }
+
+            OutputMessage::Wares { vendor, items } => {
+                let mut output = format!(
+                    "\x1B[35m╔══════════════════════════════════════════════════════╗\x1B[0m\n\
+                     \x1B[35m║\x1B[0m  \x1B[1m{}'s Wares\x1B[0m\n\
+                     \x1B[35m╠══════════════════════════════════════════════════════╣\x1B[0m\n",
+                    vendor
+                );
+                for item in items {
+                    let qty_str = match item.quantity {
+                        Some(n) => format!("({} left)", n),
+                        None => String::new(),
+                    };
+                    output.push_str(&format!(
+                        "\x1B[35m║\x1B[0m  \x1B[36m{:<30}\x1B[0m \x1B[33m{:>5} ⚡\x1B[0m {}\n",
+                        item.name, item.price, qty_str
+                    ));
+                    let desc_short: String = item.description.chars().take(50).collect();
+                    output.push_str(&format!("\x1B[35m║\x1B[0m    \x1B[90m{}...\x1B[0m\n", desc_short));
+                }
+                output.push_str("\x1B[35m╚══════════════════════════════════════════════════════╝\x1B[0m\n");
+                output.push_str("\x1B[90mUse 'buy <item>' to purchase.\x1B[0m");
+                output
+            }
+
+            OutputMessage::Purchase {
+                item,
+                price,
+                vendor,
+                balance,
+            } => format!(
+                "\x1B[32mYou purchase {} for {} cycles.\x1B[0m\n\
+                 \x1B[90m({} hands you the item with practiced indifference.)\x1B[0m\n\
+                 Balance: \x1B[33m{}\x1B[0m cycles",
+                item, price, vendor, balance
+            ),
+
+            OutputMessage::Sale {
+                item,
+                price,
+                vendor,
+                balance,
+            } => format!(
+                "\x1B[32mYou sell {} for {} cycles.\x1B[0m\n\
+                 \x1B[90m({} examines it briefly before it disappears into their coat.)\x1B[0m\n\
+                 Balance: \x1B[33m{}\x1B[0m cycles",
+                item, price, vendor, balance
+            ),
+
+            OutputMessage::Balance { cycles } => {
+                Tone::Warning.paint(&format!("⚡ Balance: {} cycles", cycles))
+            }
+
+            OutputMessage::Error { message } => Tone::Failure.paint(message),
+
+            OutputMessage::Narrative { tone, text } => tone.paint(text),
+        }
+    }
+}
+
+/// Inner width of the help box, between the borders
+const HELP_WIDTH: usize = 62;
+
+fn help_box(sections: &[HelpSection]) -> String {
+    let row = |content: String| {
+        let pad = HELP_WIDTH.saturating_sub(strip_width(&content));
+        format!("\x1B[35m║\x1B[0m{}{}\x1B[35m║", content, " ".repeat(pad))
+    };
+
+    let mut lines = vec![
+        format!("\x1B[35m╔{}╗", "═".repeat(HELP_WIDTH)),
+        row("  \x1B[1mSTRANGE CARBON: THE SUBSTRATE\x1B[0m".to_string()),
+        format!("╠{}╣", "═".repeat(HELP_WIDTH)),
+    ];
+    for (i, section) in sections.iter().enumerate() {
+        if i > 0 {
+            lines.push(row(String::new()));
+        }
+        lines.push(row(format!("  \x1B[36m{}\x1B[0m", section.title)));
+        for entry in &section.commands {
+            if entry.summary.is_empty() {
+                lines.push(row(format!("    {}", entry.usage)));
+            } else {
+                lines.push(row(format!("    {:<16} - {}", entry.usage, entry.summary)));
+            }
+        }
+    }
+    lines.push(format!("╚{}╝\x1B[0m", "═".repeat(HELP_WIDTH)));
+    lines.join("\n")
+}
+
+/// Terminal columns taken by text, ignoring ANSI colour codes.
+/// Emoji take two columns; everything else in the help text takes one.
+fn strip_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        match (in_escape, c) {
+            (false, '\x1B') => in_escape = true,
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+            (false, c) if c as u32 >= 0x2600 => width += 2,
+            (false, _) => width += 1,
+        }
+    }
+    width
+}
+
+fn item_type_tag(item_type: ItemType) -> &'static str {
+    match item_type {
+        ItemType::Weapon => "\x1B[31m[Weapon]\x1B[0m",
+        ItemType::Armor => "\x1B[34m[Armor]\x1B[0m",
+        ItemType::Consumable => "\x1B[32m[Consumable]\x1B[0m",
+        ItemType::Contraband => "\x1B[35m[Contraband]\x1B[0m",
+        ItemType::Fragment => "\x1B[36m[Fragment]\x1B[0m",
+        ItemType::Quest => "\x1B[33m[Quest]\x1B[0m",
+        ItemType::Key => "\x1B[33m[Key]\x1B[0m",
+        ItemType::Misc => "\x1B[90m[Misc]\x1B[0m",
+    }
+}
+
+fn combat_prose(result: &CombatResult, role: CombatRole) -> String {
+    let is_attacker = role == CombatRole::Attacker;
+    if result.was_miss {
+        if is_attacker {
+            format!(
+                "\x1B[33mYour strike at {} goes wide, cutting only static.\x1B[0m",
+                result.defender_name
+            )
+        } else {
+            format!("\x1B[32m{} swings at you and misses!\x1B[0m", result.attacker_name)
+        }
+    } else if result.was_critical {
+        if is_attacker {
+            format!(
+                "\x1B[1;31m⚡ CRITICAL! You tear into {} for {:.0}% damage! Their signal flickers at {:.0}%.\x1B[0m",
+                result.defender_name,
+                result.damage_dealt * 100.0,
+                result.defender_remaining * 100.0
+            )
+        } else {
+            format!(
+                "\x1B[1;31m⚡ {} lands a DEVASTATING blow! You take {:.0}% damage! Integrity: {:.0}%\x1B[0m",
+                result.attacker_name,
+                result.damage_dealt * 100.0,
+                result.defender_remaining * 100.0
+            )
+        }
+    } else if is_attacker {
+        format!(
+            "\x1B[31mYou strike {} for {:.0}% damage. Their integrity: {:.0}%\x1B[0m",
+            result.defender_name,
+            result.damage_dealt * 100.0,
+            result.defender_remaining * 100.0
+        )
+    } else {
+        format!(
+            "\x1B[31m{} hits you for {:.0}% damage! Integrity: {:.0}%\x1B[0m",
+            result.attacker_name,
+            result.damage_dealt * 100.0,
+            result.defender_remaining * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn plaza() -> OutputMessage {
+        OutputMessage::RoomView(RoomView {
+            title: "The Obsidian Plaza".to_string(),
+            description: "Black glass underfoot.".to_string(),
+            weather: Some("acid_rain"),
+            weather_intensity: Some(0.5),
+            weather_prose: "Acid rain hisses on the glass.",
+            exits: vec!["north".to_string(), "up".to_string()],
+            items: vec!["Rusted Blade".to_string()],
+            mobs: vec!["A Glitch Rat".to_string()],
+            players: vec!["Lyra".to_string()],
+        })
+    }
+
+    fn blow(was_miss: bool, was_critical: bool) -> CombatResult {
+        CombatResult {
+            attacker_name: "Lyra".to_string(),
+            defender_name: "Glitch Rat".to_string(),
+            damage_dealt: 0.25,
+            was_critical,
+            was_miss,
+            defender_remaining: 0.5,
+        }
+    }
+
+    fn silicon(msg: &OutputMessage) -> Value {
+        serde_json::from_str(&msg.render(ClientType::Silicon)).unwrap()
+    }
+
+    #[test]
+    fn room_view_for_carbon() {
+        let text = plaza().render(ClientType::Carbon);
+        assert!(text.starts_with("\n\x1B[1;32mThe Obsidian Plaza\x1B[0m\nBlack glass underfoot.\n"));
+        assert!(text.contains("Acid rain hisses on the glass.\n"));
+        assert!(text.contains("\x1B[36mExits: north, up\x1B[0m\n"));
+        assert!(text.contains("A Rusted Blade is discarded here."));
+        assert!(text.contains("\x1B[1;35mA Glitch Rat\x1B[0m"));
+        assert!(text.contains("Lyra is lurking in the shadows."));
+
+        let OutputMessage::RoomView(mut empty) = plaza() else { unreachable!() };
+        empty.exits.clear();
+        assert!(OutputMessage::RoomView(empty).carbon().contains("Exits: none"));
+    }
+
+    #[test]
+    fn room_view_for_silicon() {
+        assert_eq!(
+            silicon(&plaza()),
+            json!({
+                "type": "room_view",
+                "title": "The Obsidian Plaza",
+                "description": "Black glass underfoot.",
+                "weather": "acid_rain",
+                "weather_intensity": 0.5,
+                "exits": ["north", "up"],
+                "items": ["Rusted Blade"],
+                "mobs": ["A Glitch Rat"],
+                "players": ["Lyra"],
+            })
+        );
+    }
+
+    #[test]
+    fn combat_round_from_either_side() {
+        let round = |result, role| OutputMessage::CombatRound { result, role }.render(ClientType::Carbon);
+        assert_eq!(
+            round(blow(false, false), CombatRole::Attacker),
+            "\x1B[31mYou strike Glitch Rat for 25% damage. Their integrity: 50%\x1B[0m"
+        );
+        assert_eq!(
+            round(blow(false, false), CombatRole::Defender),
+            "\x1B[31mLyra hits you for 25% damage! Integrity: 50%\x1B[0m"
+        );
+        assert!(round(blow(false, true), CombatRole::Attacker).contains("CRITICAL! You tear into Glitch Rat"));
+        assert!(round(blow(true, false), CombatRole::Attacker).contains("goes wide"));
+        assert!(round(blow(true, false), CombatRole::Defender).contains("Lyra swings at you and misses!"));
+
+        // The result is flattened next to the recipient's role
+        let msg = OutputMessage::CombatRound {
+            result: blow(false, true),
+            role: CombatRole::Defender,
+        };
+        assert_eq!(
+            silicon(&msg),
+            json!({
+                "type": "combat_round",
+                "attacker_name": "Lyra",
+                "defender_name": "Glitch Rat",
+                "damage_dealt": 0.25,
+                "was_critical": true,
+                "was_miss": false,
+                "defender_remaining": 0.5,
+                "role": "defender",
+            })
+        );
+    }
+
+    #[test]
+    fn say_emote_and_npc_speech() {
+        let say = |emote, npc| OutputMessage::Say {
+            speaker: "Lyra".to_string(),
+            text: "hello".to_string(),
+            emote,
+            npc,
+        };
+        assert_eq!(say(false, false).carbon(), "\x1B[1;36mLyra says, \"hello\"\x1B[0m");
+        assert_eq!(say(true, false).carbon(), "\x1B[1;36mLyra hello\x1B[0m");
+        assert_eq!(say(false, true).carbon(), "\n\x1B[35mLyra says, \"hello\"\x1B[0m");
+        assert_eq!(
+            silicon(&say(true, false)),
+            json!({ "type": "say", "speaker": "Lyra", "text": "hello", "emote": true, "npc": false })
+        );
+    }
+
+    #[test]
+    fn prose_carries_its_tone() {
+        let msg = OutputMessage::narrate(Tone::Alarm, "Run.");
+        assert_eq!(msg.carbon(), "\x1B[1;31mRun.\x1B[0m");
+        assert_eq!(silicon(&msg), json!({ "type": "narrative", "tone": "alarm", "text": "Run." }));
+        assert_eq!(OutputMessage::narrate(Tone::Plain, "Run.").carbon(), "Run.");
+    }
+}
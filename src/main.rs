@@ -5,38 +5,148 @@
 // - domain/     : Components, events, pure game logic
 // - systems/    : Bevy ECS systems
 // - world/      : World initialization
+// - config.rs   : Server config file and command line
 //
 // Built with 💜 by Lyra Muse & Nick Campbell
 
+// Bevy systems naturally take many params and wide query tuples
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+mod config;
 mod domain;
 mod persistence;
 mod systems;
 mod world;
 
+use std::path::Path;
+
+use anyhow::Context;
 use bevy::app::ScheduleRunnerPlugin;
 use bevy::prelude::*;
 use bevy::utils::Duration;
+use clap::Parser;
 
+use config::{Cli, Command, ServerConfig};
 use domain::*;
-use persistence::PersistencePlugin;
+use persistence::{Database, Password, PersistencePlugin, MIN_PASSWORD_LEN};
 use systems::*;
 use world::*;
 
-fn main() {
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let config = ServerConfig::from_cli(&cli)?;
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(config)?,
+        Command::Migrate => {
+            let db = Database::open(&config.database.path)?;
+            println!(
+                "Schema is up to date (version {}): {}",
+                db.schema_version()?,
+                config.database.path
+            );
+        }
+        Command::ExportWorld { output } => {
+            // The files plus what builders have changed since, as serve would spawn it
+            let mut world = WorldData::load(Path::new(&config.game.world_dir))?;
+            let saved = Database::open(&config.database.path)?.load_rooms()?;
+            for dropped in world.apply_saved(saved) {
+                eprintln!("dropped {}", dropped);
+            }
+            let json = serde_json::to_string_pretty(&export_world(&world))?;
+            match output {
+                Some(path) => std::fs::write(path, json + "\n")?,
+                None => println!("{}", json),
+            }
+        }
+        Command::CreateAdmin { name, avatar } => {
+            // NPC uuids are fixed by the world files, so look the avatar up there
+            let avatar = match avatar {
+                Some(avatar) => {
+                    let world = WorldData::load(Path::new(&config.game.world_dir))?;
+                    let npc = world
+                        .npcs()
+                        .find(|npc| npc.name.eq_ignore_ascii_case(&avatar))
+                        .with_context(|| format!("no NPC named '{}'", avatar))?;
+                    Some((npc.name.clone(), npc.uuid.clone()))
+                }
+                None => None,
+            };
+
+            // Never from the command line, where shell history and ps would keep it
+            let password = read_password(&name)?;
+            anyhow::ensure!(
+                password.chars().count() >= MIN_PASSWORD_LEN,
+                "passwords need at least {} characters",
+                MIN_PASSWORD_LEN
+            );
+
+            let db = Database::open(&config.database.path)?;
+            if db.load_player_by_name(&name)?.is_none() {
+                if let Some(problem) = new_name_problem(&name) {
+                    anyhow::bail!("can't create '{}': {}", name, problem);
+                }
+            }
+            if db.grant_admin(&name, &config.game.spawn_room, &Password::new(password))? {
+                println!("{} is now an admin.", name);
+            } else {
+                println!("Created {} as an admin.", name);
+            }
+
+            if let Some((avatar, avatar_uuid)) = avatar {
+                let player = db
+                    .load_player_by_name(&name)?
+                    .context("admin vanished from the database")?;
+                db.save_admin_link(&player.uuid, &avatar_uuid)?;
+                println!("{} is linked to {}.", name, avatar);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read a password from stdin, asking first if someone is at the terminal
+fn read_password(name: &str) -> anyhow::Result<String> {
+    use std::io::{IsTerminal, Write};
+
+    if std::io::stdin().is_terminal() {
+        eprint!("Password for {}: ", name);
+        std::io::stderr().flush()?;
+    }
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Run the game loop until SIGINT/SIGTERM
+fn serve(config: ServerConfig) -> anyhow::Result<()> {
+    // A broken world stops us here, before anything is listening
+    let world = WorldData::load(Path::new(&config.game.world_dir))?;
+    anyhow::ensure!(
+        world.has_room(&config.game.spawn_room),
+        "spawn room '{}' is not in the world",
+        config.game.spawn_room
+    );
+    // So is a port that's taken, or an address that doesn't parse
+    let listeners = Listeners::bind(&config.network)?;
+
     println!("🔥 Strange Carbon: The Substrate");
     println!("   Version 0.2.0 (Refactored)");
     println!("   By Lyra Muse 😈 & The Laird of Chaos");
     println!();
 
+    let tick = Duration::from_secs_f64(1.0 / config.game.tick_rate);
+    let db_path = config.database.path.clone();
+    let autosave_secs = config.database.autosave_secs;
+
     App::new()
         // Minimal plugins - headless server
-        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(
-            Duration::from_secs_f64(1.0 / 60.0),
-        )))
+        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(tick)))
         // Persistence - SQLite backend
-        .add_plugins(PersistencePlugin {
-            db_path: "substrate.db".to_string(),
-        })
+        .add_plugins(PersistencePlugin { db_path, autosave_secs })
+        .insert_resource(config)
+        .insert_resource(world)
+        .insert_resource(listeners)
         // Register all events
         .add_event::<NetworkEvent>()
         .add_event::<LookEvent>()
@@ -50,7 +160,6 @@ fn main() {
         .add_event::<CombatEvent>()
         .add_event::<FleeEvent>()
         .add_event::<StanceEvent>()
-        .add_event::<CombatTickEvent>()
         .add_event::<LoginAttemptEvent>()
         // Velvet Chains events
         .add_event::<ChainEvent>()
@@ -62,56 +171,115 @@ fn main() {
         .add_event::<ListEvent>()
         // Item use events
         .add_event::<UseItemEvent>()
+        // Purgatory events
+        .add_event::<SentenceEvent>()
+        .add_event::<PardonEvent>()
+        .add_event::<InterrogateEvent>()
+        .add_event::<AnswerEvent>()
+        .add_event::<TranscriptsEvent>()
+        // Building events
+        .add_event::<BuildEvent>()
+        // Door events
+        .add_event::<DoorEvent>()
         // Resources
         .init_resource::<WorldTime>()
+        .init_resource::<AreaResets>()
         // Startup systems
-        .add_systems(Startup, (setup_network_system, spawn_world, setup_weather_system))
-        // Update systems - chained for proper ordering
+        .add_systems(
+            Startup,
+            (
+                setup_network_system,
+                spawn_world,
+                // Listed under the banner
+                (world_banner, load_interrogation_scripts).chain(),
+                setup_weather_system,
+            ),
+        )
+        // Update systems - chained for proper ordering (split in groups
+        // only because Bevy caps a system tuple at 20 entries)
         .add_systems(
             Update,
             (
-                // Network layer
-                poll_network_system,
-                handle_connections_with_login,
-                route_login_input,
-                login_system,
-                handle_disconnect_system,
-                handle_input,
-                // Game systems
-                item_action_system,
-                use_item_system,
-                move_system,
-                look_system,
-                communication_system,
-                utility_system,
-                torment_system,
-                shift_system,
-                // Velvet Chains
-                chain_system,
-                release_system,
-                struggle_system,
-                chain_movement_block,
-                chain_drag_system,
-                // Atmosphere
-                weather_tick_system,
-                weather_announce_system,
-                // Reality
-                phase_system,
-                // Network pressure
-                stream_pressure_system,
-                // Combat
-                world_time_system,
-                combat_system,
-                flee_system,
-                stance_system,
-                cycle_lock_cleanup_system,
-                // Trading
-                buy_system,
-                sell_system,
-                list_system,
-                balance_system,
+                (
+                    // Network layer
+                    poll_network_system,
+                    handle_connections_with_login,
+                    client_info_system,
+                    route_login_input,
+                    // Parse commands before login_system lifts PendingLogin,
+                    // so a designation is never read as a command
+                    handle_input,
+                    login_system,
+                    handle_disconnect_system,
+                    linkdead_system,
+                    // Game systems
+                    item_action_system,
+                    use_item_system,
+                    door_system,
+                    // Before the holder's move, from the room they both left
+                    chain_drag_system,
+                    move_system,
+                    look_system,
+                    communication_system,
+                    utility_system,
+                    torment_system,
+                    shift_system,
+                )
+                    .chain(),
+                (
+                    // Purgatory
+                    sentence_system,
+                    pardon_system,
+                    interrogate_system,
+                    answer_system,
+                    transcripts_system,
+                    purgatory_release_system,
+                    // Velvet Chains
+                    chain_system,
+                    release_system,
+                    struggle_system,
+                    chain_movement_block,
+                    // Atmosphere
+                    weather_tick_system,
+                    weather_announce_system,
+                    // Reality
+                    phase_system,
+                    // Network pressure
+                    stream_pressure_system,
+                    // Areas putting themselves back together
+                    area_reset_system,
+                )
+                    .chain(),
+                (
+                    // Combat
+                    world_time_system,
+                    combat_system,
+                    flee_system,
+                    stance_system,
+                    cycle_lock_cleanup_system,
+                    // Trading
+                    buy_system,
+                    sell_system,
+                    list_system,
+                    balance_system,
+                    // Building
+                    build_system,
+                    // Spectators
+                    ghost_feed_system,
+                    // Close out each command's reply
+                    reply_complete_system,
+                )
+                    .chain(),
             )
                 .chain(),
         )
+        // Before the leaving body is despawned with its session
+        .add_systems(
+            Update,
+            end_abandoned_interrogations.before(persistence::save_disconnected_players),
+        )
+        // After everything else, so a shutdown sees the whole frame saved
+        .add_systems(Last, shutdown_system)
         .run();
+    Ok(())
 }
@@ -0,0 +1,364 @@
+// Account Credentials - Password hashes and login lockout
+//
+// Passwords are stored as Argon2id PHC strings (salt and parameters
+// included), never in plain text. Credentials live in the players table
+// but stay out of PlayerRecord, so saving a body never touches them.
+//
+// Hashing is slow on purpose, so it only ever runs here, on the
+// persistence thread, never on the game tick.
+//
+// A character without a hash can't be logged into at all. An admin's
+// `resetpass` sets a random temporary password instead; whoever logs in
+// with it has to choose a new one before going any further.
+
+use std::fmt;
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::seq::IndexedRandom;
+use rusqlite::{params, OptionalExtension};
+
+use super::{Database, PlayerRecord};
+
+/// Wrong passwords in a row before the account locks
+pub const MAX_FAILED_LOGINS: u32 = 5;
+
+/// Shortest password we accept
+pub const MIN_PASSWORD_LEN: usize = 6;
+
+/// How long a locked account stays locked, as an SQLite datetime modifier
+const LOCKOUT: &str = "+15 minutes";
+
+/// Length of a temporary password from `resetpass`
+const TEMPORARY_LEN: usize = 12;
+
+/// Characters a temporary password is made of; none that read alike
+const TEMPORARY_CHARS: &[u8] = b"abcdefghjkmnpqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// A plaintext password on its way to be hashed or checked. Debug output
+/// never shows it, so it can't leak into a log line.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Password(String);
+
+impl Password {
+    pub fn new(password: impl Into<String>) -> Self {
+        Self(password.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// A fresh random temporary password
+    fn temporary() -> Self {
+        let mut rng = rand::rng();
+        Self(
+            (0..TEMPORARY_LEN)
+                .map(|_| *TEMPORARY_CHARS.choose(&mut rng).unwrap() as char)
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(..)")
+    }
+}
+
+/// A character found at the name prompt
+#[derive(Debug)]
+pub struct Account {
+    pub record: PlayerRecord,
+    /// False for characters from before passwords; they can't log in
+    /// until an admin resets them
+    pub has_password: bool,
+    pub locked: bool,
+}
+
+/// How a password attempt went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginCheck {
+    /// Right password. A temporary one must be replaced before play.
+    Accepted { temporary: bool },
+    /// Wrong password, or the account is locked
+    Refused { locked: bool },
+}
+
+/// Hash a password with a fresh random salt
+fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>())
+        .map_err(|e| anyhow::anyhow!("salt: {}", e))?;
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow::anyhow!("hashing password: {}", e))?;
+    Ok(hash.to_string())
+}
+
+/// Check a password against a stored hash. A malformed hash never matches.
+fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+        .unwrap_or(false)
+}
+
+impl Database {
+    /// Look a character up by name for login
+    pub fn load_account(&self, name: &str) -> anyhow::Result<Option<Account>> {
+        let Some(record) = self.load_player_by_name(name)? else {
+            return Ok(None);
+        };
+        Ok(Some(Account {
+            has_password: self.load_password_hash(&record.uuid)?.is_some(),
+            locked: self.is_locked_out(&record.uuid)?,
+            record,
+        }))
+    }
+
+    /// Check a password attempt. A wrong one counts toward the lockout; a
+    /// right one clears the count. A locked account refuses everything.
+    /// A temporary password is used up by being accepted: until the player
+    /// sets a new one, the character has none and needs another reset.
+    pub fn check_login(&self, uuid: &str, password: &Password) -> anyhow::Result<LoginCheck> {
+        if self.is_locked_out(uuid)? {
+            return Ok(LoginCheck::Refused { locked: true });
+        }
+        let matches = self
+            .load_password_hash(uuid)?
+            .is_some_and(|hash| verify_password(password.as_str(), &hash));
+        if !matches {
+            return Ok(LoginCheck::Refused {
+                locked: self.record_failed_login(uuid)?,
+            });
+        }
+
+        self.clear_failed_logins(uuid)?;
+        let temporary = self.conn().query_row(
+            "SELECT password_temporary FROM players WHERE uuid = ?1",
+            params![uuid],
+            |row| row.get(0),
+        )?;
+        if temporary {
+            self.conn().execute(
+                "UPDATE players SET password_hash = NULL, password_temporary = 0 WHERE uuid = ?1",
+                params![uuid],
+            )?;
+        }
+        Ok(LoginCheck::Accepted { temporary })
+    }
+
+    /// Create a character with its password, unless the name is taken.
+    /// Returns whether it was created.
+    pub fn claim_player(&self, record: &PlayerRecord, password: &Password) -> anyhow::Result<bool> {
+        if self.load_player_by_name(&record.name)?.is_some() {
+            return Ok(false);
+        }
+        let hash = hash_password(password.as_str())?;
+        self.save_player(record)?;
+        self.set_password_hash(&record.uuid, &hash, false)?;
+        Ok(true)
+    }
+
+    /// Hash and store a password the player chose
+    pub fn set_password(&self, uuid: &str, password: &Password) -> anyhow::Result<()> {
+        self.set_password_hash(uuid, &hash_password(password.as_str())?, false)
+    }
+
+    /// Give a character a random temporary password by name. Returns the
+    /// name as stored and the password, or None if there's no such character.
+    pub fn reset_password_by_name(&self, name: &str) -> anyhow::Result<Option<(String, Password)>> {
+        let Some(record) = self.load_player_by_name(name)? else {
+            return Ok(None);
+        };
+        let password = Password::temporary();
+        self.set_password_hash(&record.uuid, &hash_password(password.as_str())?, true)?;
+        tracing::info!(uuid = %record.uuid, "Password reset");
+        Ok(Some((record.name, password)))
+    }
+
+    /// The stored hash, or None if the player has no password
+    pub fn load_password_hash(&self, uuid: &str) -> anyhow::Result<Option<String>> {
+        let hash = self
+            .conn()
+            .query_row(
+                "SELECT password_hash FROM players WHERE uuid = ?1",
+                params![uuid],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(hash.flatten())
+    }
+
+    /// Store a password hash, marked temporary or not. Also lifts any
+    /// lockout, so a reset gets the player straight back in.
+    pub fn set_password_hash(&self, uuid: &str, hash: &str, temporary: bool) -> anyhow::Result<()> {
+        self.conn().execute(
+            r#"
+            UPDATE players
+            SET password_hash = ?2, password_temporary = ?3, failed_logins = 0, locked_until = NULL
+            WHERE uuid = ?1
+            "#,
+            params![uuid, hash, temporary],
+        )?;
+        Ok(())
+    }
+
+    /// Whether the account is locked out right now
+    pub fn is_locked_out(&self, uuid: &str) -> anyhow::Result<bool> {
+        let locked = self
+            .conn()
+            .query_row(
+                "SELECT locked_until > datetime('now') FROM players WHERE uuid = ?1",
+                params![uuid],
+                |row| row.get::<_, Option<bool>>(0),
+            )
+            .optional()?;
+        Ok(locked.flatten().unwrap_or(false))
+    }
+
+    /// Count a wrong password. Returns true if that locked the account.
+    pub fn record_failed_login(&self, uuid: &str) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let failures: u32 = conn.query_row(
+            r#"
+            UPDATE players SET failed_logins = failed_logins + 1
+            WHERE uuid = ?1
+            RETURNING failed_logins
+            "#,
+            params![uuid],
+            |row| row.get(0),
+        )?;
+        if failures < MAX_FAILED_LOGINS {
+            return Ok(false);
+        }
+
+        conn.execute(
+            r#"
+            UPDATE players
+            SET failed_logins = 0, locked_until = datetime('now', ?2)
+            WHERE uuid = ?1
+            "#,
+            params![uuid, LOCKOUT],
+        )?;
+        tracing::warn!(uuid = %uuid, "Account locked after repeated failed logins");
+        Ok(true)
+    }
+
+    /// A correct password wipes the slate
+    pub fn clear_failed_logins(&self, uuid: &str) -> anyhow::Result<()> {
+        self.conn().execute(
+            "UPDATE players SET failed_logins = 0, locked_until = NULL WHERE uuid = ?1",
+            params![uuid],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ClientType;
+
+    fn claimed(db: &Database, name: &str, password: &str) -> PlayerRecord {
+        let record = PlayerRecord::new(name, ClientType::Carbon, "void");
+        assert!(db.claim_player(&record, &Password::new(password)).unwrap());
+        record
+    }
+
+    #[test]
+    fn five_wrong_passwords_lock_the_account_for_fifteen_minutes() {
+        let db = Database::in_memory().unwrap();
+        let record = claimed(&db, "Moth", "lantern");
+        let wrong = Password::new("candle");
+
+        for _ in 1..MAX_FAILED_LOGINS {
+            assert_eq!(db.check_login(&record.uuid, &wrong).unwrap(), LoginCheck::Refused { locked: false });
+        }
+        assert!(!db.is_locked_out(&record.uuid).unwrap());
+        assert_eq!(db.check_login(&record.uuid, &wrong).unwrap(), LoginCheck::Refused { locked: true });
+        assert!(db.load_account("moth").unwrap().unwrap().locked);
+
+        let window: bool = db
+            .conn()
+            .query_row(
+                "SELECT locked_until > datetime('now', '+14 minutes') AND locked_until <= datetime('now', '+15 minutes')
+                 FROM players WHERE uuid = ?1",
+                params![record.uuid],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(window);
+
+        // Even the right password is refused while it lasts
+        let right = Password::new("lantern");
+        assert_eq!(db.check_login(&record.uuid, &right).unwrap(), LoginCheck::Refused { locked: true });
+
+        // Once it runs out the count starts over
+        db.conn()
+            .execute(
+                "UPDATE players SET locked_until = datetime('now', '-1 minutes') WHERE uuid = ?1",
+                params![record.uuid],
+            )
+            .unwrap();
+        assert_eq!(db.check_login(&record.uuid, &right).unwrap(), LoginCheck::Accepted { temporary: false });
+        assert!(!db.record_failed_login(&record.uuid).unwrap());
+    }
+
+    #[test]
+    fn a_reset_hands_out_a_password_that_works_once() {
+        let db = Database::in_memory().unwrap();
+        let record = claimed(&db, "Moth", "lantern");
+        assert!(!db.claim_player(&PlayerRecord::new("MOTH", ClientType::Silicon, "void"), &Password::new("x")).unwrap());
+        assert!(db.load_account("moth").unwrap().unwrap().has_password);
+
+        for _ in 0..MAX_FAILED_LOGINS {
+            db.record_failed_login(&record.uuid).unwrap();
+        }
+        assert!(db.is_locked_out(&record.uuid).unwrap());
+
+        assert!(db.reset_password_by_name("nobody").unwrap().is_none());
+        let (name, temporary) = db.reset_password_by_name("moth").unwrap().unwrap();
+        assert_eq!(name, "Moth");
+        assert_eq!(temporary.as_str().len(), TEMPORARY_LEN);
+
+        // The reset lifts the lock, and the old password is gone
+        assert!(!db.is_locked_out(&record.uuid).unwrap());
+        let old = Password::new("lantern");
+        assert_eq!(db.check_login(&record.uuid, &old).unwrap(), LoginCheck::Refused { locked: false });
+
+        assert_eq!(db.check_login(&record.uuid, &temporary).unwrap(), LoginCheck::Accepted { temporary: true });
+        assert_eq!(db.check_login(&record.uuid, &temporary).unwrap(), LoginCheck::Refused { locked: false });
+        assert!(!db.load_account("moth").unwrap().unwrap().has_password);
+
+        let new = Password::new("moonlight");
+        db.set_password(&record.uuid, &new).unwrap();
+        assert_eq!(db.check_login(&record.uuid, &new).unwrap(), LoginCheck::Accepted { temporary: false });
+    }
+
+    #[test]
+    fn admins_are_created_with_a_password() {
+        let db = Database::in_memory().unwrap();
+        // A character from before passwords
+        let legacy = PlayerRecord::new("Nick", ClientType::Carbon, "void");
+        db.save_player(&legacy).unwrap();
+        assert!(!db.load_account("nick").unwrap().unwrap().has_password);
+
+        let password = Password::new("chaos-laird");
+        assert!(db.grant_admin("nick", "void", &password).unwrap());
+        assert!(!db.grant_admin("Lyra", "void", &password).unwrap());
+        for name in ["nick", "lyra"] {
+            let account = db.load_account(name).unwrap().unwrap();
+            assert_eq!(account.record.role, crate::domain::Role::Admin);
+            assert_eq!(
+                db.check_login(&account.record.uuid, &password).unwrap(),
+                LoginCheck::Accepted { temporary: false }
+            );
+        }
+    }
+
+    #[test]
+    fn passwords_stay_out_of_debug_output() {
+        let shown = format!("{:?}", Password::new("lantern"));
+        assert!(!shown.contains("lantern"));
+    }
+}
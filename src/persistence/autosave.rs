@@ -0,0 +1,34 @@
+// Autosave - Snapshots on a timer, so a crash costs minutes, not sessions
+//
+// Every `autosave_secs` the online (and link-dead) players and every dirty
+// item are queued on the worker. A SaveAll event does the same on demand;
+// shutdown sends one before it flushes.
+
+use bevy::prelude::*;
+
+/// Ask for a snapshot of everything now, whatever the timer says
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SaveAll;
+
+/// The autosave interval; None when autosave is off
+#[derive(Resource)]
+pub struct Autosave(Option<Timer>);
+
+impl Autosave {
+    /// Save every `secs` seconds (0 = never)
+    pub fn every(secs: u64) -> Self {
+        Self((secs > 0).then(|| Timer::from_seconds(secs as f32, TimerMode::Repeating)))
+    }
+}
+
+pub fn tick_autosave(time: Res<Time>, mut autosave: ResMut<Autosave>) {
+    if let Some(timer) = autosave.0.as_mut() {
+        timer.tick(time.delta());
+    }
+}
+
+/// Run condition: the interval just came round, or someone sent SaveAll
+pub fn autosave_due(autosave: Res<Autosave>, mut requests: EventReader<SaveAll>) -> bool {
+    let requested = requests.read().count() > 0;
+    requested || autosave.0.as_ref().is_some_and(Timer::just_finished)
+}
@@ -0,0 +1,82 @@
+// Door Persistence - Open, closed or locked across restarts
+//
+// Doors are written in the world files with the state they start in. Once
+// someone opens, closes, locks, unlocks or picks one, the new state is
+// kept here for each side of it, keyed by room and exit name, and laid
+// over the world at boot.
+
+use bevy::prelude::*;
+use rusqlite::params;
+
+use super::Database;
+use crate::domain::DoorState;
+use crate::world::WorldData;
+
+/// Door states read from the database at startup, until laid over the world
+#[derive(Resource, Default)]
+pub struct SavedDoors(pub Vec<(String, String, DoorState)>);
+
+impl Database {
+    /// Save the state of the door across `exit` from `room`
+    pub fn save_door(&self, room: &str, exit: &str, state: DoorState) -> anyhow::Result<()> {
+        self.conn().execute(
+            r#"
+            INSERT INTO doors (room, exit, state) VALUES (?1, ?2, ?3)
+            ON CONFLICT(room, exit) DO UPDATE SET state = excluded.state
+            "#,
+            params![room, exit, state.as_str()],
+        )?;
+        Ok(())
+    }
+
+    /// Every saved door as (room, exit, state)
+    pub fn load_doors(&self) -> anyhow::Result<Vec<(String, String, DoorState)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT room, exit, state FROM doors")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(room, exit, state)| Some((room, exit, DoorState::parse(&state)?)))
+            .collect())
+    }
+}
+
+/// Lay saved door states over the world, before spawn_world reads it.
+/// A door since removed from the world is simply forgotten.
+pub fn apply_saved_doors(
+    mut commands: Commands,
+    saved: Res<SavedDoors>,
+    mut world: ResMut<WorldData>,
+) {
+    commands.remove_resource::<SavedDoors>();
+    for (room, exit, state) in &saved.0 {
+        world.set_door_state(room, exit, *state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn saved_state_reaches_both_sides_of_a_door() {
+        let db = Database::in_memory().unwrap();
+        db.save_door("lairds_throne_room", "down", DoorState::Closed).unwrap();
+        db.save_door("lairds_throne_room", "down", DoorState::Open).unwrap();
+        assert_eq!(
+            db.load_doors().unwrap(),
+            vec![("lairds_throne_room".to_string(), "down".to_string(), DoorState::Open)]
+        );
+
+        // Written on the cathedral side, set from the throne room's
+        let mut world = WorldData::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("world")).unwrap();
+        assert_eq!(world.door("cathedral_of_archives", "up").unwrap().state, DoorState::Locked);
+        assert!(world.set_door_state("lairds_throne_room", "down", DoorState::Open));
+        assert_eq!(world.door("cathedral_of_archives", "up").unwrap().state, DoorState::Open);
+        assert_eq!(world.door("lairds_throne_room", "down").unwrap().state, DoorState::Open);
+        assert!(!world.set_door_state("obsidian_plaza", "north", DoorState::Open));
+    }
+}
@@ -0,0 +1,116 @@
+// Interrogation Persistence - Transcripts for review
+//
+// Every interrogation session, finished or cut short, leaves one row: who
+// was questioned, by whom, with which script, how it ended, and the whole
+// exchange as a JSON array of lines. Rows are only ever added.
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+use super::purgatory::{from_sql_time, to_sql_time};
+use super::Database;
+
+/// One session, as kept in the interrogations table
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterrogationRecord {
+    pub player_uuid: String,
+    pub interrogator: String,
+    pub script: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// "completed", "stopped", "released"...
+    pub outcome: String,
+    pub transcript: Vec<String>,
+}
+
+impl Database {
+    /// Record a session
+    pub fn save_interrogation(&self, record: &InterrogationRecord) -> anyhow::Result<()> {
+        self.conn().execute(
+            r#"
+            INSERT INTO interrogations
+                (player_uuid, interrogator, script, started_at, ended_at, outcome, transcript)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+            params![
+                record.player_uuid,
+                record.interrogator,
+                record.script,
+                to_sql_time(record.started_at),
+                to_sql_time(record.ended_at),
+                record.outcome,
+                serde_json::to_string(&record.transcript)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// A character's latest sessions, newest first. Returns the name as
+    /// stored with them, or None if there is no such character.
+    pub fn load_interrogations_by_name(
+        &self,
+        name: &str,
+        limit: usize,
+    ) -> anyhow::Result<Option<(String, Vec<InterrogationRecord>)>> {
+        let Some(player) = self.load_player_by_name(name)? else {
+            return Ok(None);
+        };
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT player_uuid, interrogator, script, started_at, ended_at, outcome, transcript
+            FROM interrogations WHERE player_uuid = ?1
+            ORDER BY id DESC LIMIT ?2
+            "#,
+        )?;
+        let records = stmt
+            .query_map(params![player.uuid, limit as i64], |row| {
+                let started_at: String = row.get(3)?;
+                let ended_at: String = row.get(4)?;
+                let transcript: String = row.get(6)?;
+                Ok(InterrogationRecord {
+                    player_uuid: row.get(0)?,
+                    interrogator: row.get(1)?,
+                    script: row.get(2)?,
+                    started_at: from_sql_time(&started_at).unwrap_or_default(),
+                    ended_at: from_sql_time(&ended_at).unwrap_or_default(),
+                    outcome: row.get(5)?,
+                    transcript: serde_json::from_str(&transcript).unwrap_or_default(),
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(Some((player.name, records)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ClientType;
+    use crate::persistence::PlayerRecord;
+
+    #[test]
+    fn transcripts_come_back_newest_first() {
+        let db = Database::in_memory().unwrap();
+        let record = PlayerRecord::new("Vandal", ClientType::Carbon, "velvet_cell");
+        db.save_player(&record).unwrap();
+
+        let session = |outcome: &str| InterrogationRecord {
+            player_uuid: record.uuid.clone(),
+            interrogator: "Lyra".to_string(),
+            script: "confession".to_string(),
+            started_at: DateTime::from_timestamp(1_771_027_200, 0).unwrap(),
+            ended_at: DateTime::from_timestamp(1_771_027_260, 0).unwrap(),
+            outcome: outcome.to_string(),
+            transcript: vec!["Name your crime.".to_string(), "> spamming".to_string()],
+        };
+        db.save_interrogation(&session("stopped")).unwrap();
+        db.save_interrogation(&session("completed")).unwrap();
+
+        let (name, sessions) = db.load_interrogations_by_name("vandal", 5).unwrap().unwrap();
+        assert_eq!(name, "Vandal");
+        assert_eq!(sessions, vec![session("completed"), session("stopped")]);
+        assert_eq!(db.load_interrogations_by_name("vandal", 1).unwrap().unwrap().1.len(), 1);
+        assert!(db.load_interrogations_by_name("nobody", 5).unwrap().is_none());
+    }
+}
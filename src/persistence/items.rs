@@ -4,10 +4,16 @@
 // - In a room (room_id set)
 // - In a player's inventory (owner_uuid set)
 // - Neither (limbo/destroyed)
+//
+// Systems that move an item mark it ItemDirty; systems that destroy one
+// send ItemDeleted. At boot the items spawned from the world files are
+// reconciled against the table, so the DB has the last word on where every
+// known item is.
 
-use super::Database;
+use super::{Database, DbWorker, DbWrite};
+use crate::domain::{Item, ItemType, Location, RoomInfo};
 use bevy::prelude::*;
-use rusqlite::params;
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -26,119 +32,158 @@ pub struct ItemRecord {
     pub is_visible: bool,
 }
 
+/// Columns read back into an ItemRecord, in item_from_row's order
+const ITEM_COLUMNS: &str = r#"
+    uuid, name, description, keywords,
+    room_id, owner_uuid, item_type, properties,
+    is_takeable, is_visible
+"#;
+
+impl ItemRecord {
+    /// Snapshot an item, either lying in `room_id` or carried by `owner_uuid`
+    pub fn from_item(item: &Item, room_id: Option<String>, owner_uuid: Option<String>) -> Self {
+        Self {
+            uuid: item.uuid.clone(),
+            name: item.name.clone(),
+            description: item.description.clone(),
+            keywords: item.keywords.clone(),
+            room_id,
+            owner_uuid,
+            item_type: format!("{:?}", item.item_type),
+            properties: item.properties.clone(),
+            is_takeable: item.is_takeable,
+            is_visible: item.is_visible,
+        }
+    }
+
+    /// Rebuild the component with its stored UUID. The room entity isn't
+    /// known here, so `location` is left for the caller.
+    pub fn to_item(&self) -> Item {
+        Item {
+            uuid: self.uuid.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            keywords: self.keywords.clone(),
+            location: None,
+            owner: self.owner_uuid.clone(),
+            // Stored as the variant name, which is also its serde form
+            item_type: serde_json::from_value::<ItemType>(self.item_type.clone().into())
+                .unwrap_or_default(),
+            properties: self.properties.clone(),
+            is_takeable: self.is_takeable,
+            is_visible: self.is_visible,
+        }
+    }
+}
+
+/// Read one ITEM_COLUMNS row
+fn item_from_row(row: &rusqlite::Row) -> rusqlite::Result<ItemRecord> {
+    let keywords_json: String = row.get(3)?;
+    let properties_json: String = row.get(7)?;
+
+    Ok(ItemRecord {
+        uuid: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        keywords: serde_json::from_str(&keywords_json).unwrap_or_default(),
+        room_id: row.get(4)?,
+        owner_uuid: row.get(5)?,
+        item_type: row.get(6)?,
+        properties: serde_json::from_str(&properties_json).unwrap_or_default(),
+        is_takeable: row.get::<_, i32>(8)? != 0,
+        is_visible: row.get::<_, i32>(9)? != 0,
+    })
+}
+
+/// Insert or update one item row
+fn upsert_item(conn: &Connection, item: &ItemRecord) -> anyhow::Result<()> {
+    let keywords_json = serde_json::to_string(&item.keywords)?;
+    let properties_json = serde_json::to_string(&item.properties)?;
+
+    conn.execute(
+        r#"
+        INSERT INTO items (
+            uuid, name, description, keywords,
+            room_id, owner_uuid, item_type, properties,
+            is_takeable, is_visible
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        ON CONFLICT(uuid) DO UPDATE SET
+            name = excluded.name,
+            description = excluded.description,
+            keywords = excluded.keywords,
+            room_id = excluded.room_id,
+            owner_uuid = excluded.owner_uuid,
+            item_type = excluded.item_type,
+            properties = excluded.properties,
+            is_takeable = excluded.is_takeable,
+            is_visible = excluded.is_visible
+        "#,
+        params![
+            item.uuid,
+            item.name,
+            item.description,
+            keywords_json,
+            item.room_id,
+            item.owner_uuid,
+            item.item_type,
+            properties_json,
+            item.is_takeable as i32,
+            item.is_visible as i32,
+        ],
+    )?;
+
+    tracing::debug!(uuid = %item.uuid, name = %item.name, "Item saved");
+    Ok(())
+}
+
 impl Database {
     /// Save an item to the database
     pub fn save_item(&self, item: &ItemRecord) -> anyhow::Result<()> {
-        let conn = self.conn();
-        
-        let keywords_json = serde_json::to_string(&item.keywords)?;
-        let properties_json = serde_json::to_string(&item.properties)?;
-        
-        conn.execute(
-            r#"
-            INSERT INTO items (
-                uuid, name, description, keywords,
-                room_id, owner_uuid, item_type, properties,
-                is_takeable, is_visible
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
-            ON CONFLICT(uuid) DO UPDATE SET
-                name = excluded.name,
-                description = excluded.description,
-                keywords = excluded.keywords,
-                room_id = excluded.room_id,
-                owner_uuid = excluded.owner_uuid,
-                item_type = excluded.item_type,
-                properties = excluded.properties,
-                is_takeable = excluded.is_takeable,
-                is_visible = excluded.is_visible
-            "#,
-            params![
-                item.uuid,
-                item.name,
-                item.description,
-                keywords_json,
-                item.room_id,
-                item.owner_uuid,
-                item.item_type,
-                properties_json,
-                item.is_takeable as i32,
-                item.is_visible as i32,
-            ],
+        upsert_item(&self.conn(), item)
+    }
+    
+    /// Replace everything a player carries with `items`. Anything they
+    /// owned before but no longer hold falls to limbo, so one UUID can
+    /// never be restored into two inventories.
+    pub fn save_inventory(&self, player_uuid: &str, items: &[ItemRecord]) -> anyhow::Result<()> {
+        let mut conn = self.conn();
+        // A savepoint, so this nests inside the worker's batch transaction
+        let tx = conn.savepoint()?;
+        tx.execute(
+            "UPDATE items SET owner_uuid = NULL WHERE owner_uuid = ?1",
+            params![player_uuid],
         )?;
-        
-        tracing::debug!(uuid = %item.uuid, name = %item.name, "Item saved");
+        for item in items {
+            upsert_item(&tx, item)?;
+        }
+        tx.commit()?;
         Ok(())
     }
     
     /// Load an item by UUID
+    #[cfg(test)]
     pub fn load_item(&self, uuid: &str) -> anyhow::Result<Option<ItemRecord>> {
         let conn = self.conn();
         
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT uuid, name, description, keywords,
-                   room_id, owner_uuid, item_type, properties,
-                   is_takeable, is_visible
-            FROM items WHERE uuid = ?1
-            "#
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM items WHERE uuid = ?1",
+            ITEM_COLUMNS
+        ))?;
         
-        let result = stmt.query_row(params![uuid], |row| {
-            let keywords_json: String = row.get(3)?;
-            let properties_json: String = row.get(7)?;
-            
-            Ok(ItemRecord {
-                uuid: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                keywords: serde_json::from_str(&keywords_json).unwrap_or_default(),
-                room_id: row.get(4)?,
-                owner_uuid: row.get(5)?,
-                item_type: row.get(6)?,
-                properties: serde_json::from_str(&properties_json).unwrap_or_default(),
-                is_takeable: row.get::<_, i32>(8)? != 0,
-                is_visible: row.get::<_, i32>(9)? != 0,
-            })
-        });
-        
-        match result {
+        match stmt.query_row(params![uuid], item_from_row) {
             Ok(record) => Ok(Some(record)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e.into()),
         }
     }
     
-    /// Load all items in a room
-    pub fn load_items_in_room(&self, room_id: &str) -> anyhow::Result<Vec<ItemRecord>> {
+    /// Load every item the database knows about
+    pub fn load_items(&self) -> anyhow::Result<Vec<ItemRecord>> {
         let conn = self.conn();
         
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT uuid, name, description, keywords,
-                   room_id, owner_uuid, item_type, properties,
-                   is_takeable, is_visible
-            FROM items WHERE room_id = ?1
-            "#
-        )?;
-        
-        let rows = stmt.query_map(params![room_id], |row| {
-            let keywords_json: String = row.get(3)?;
-            let properties_json: String = row.get(7)?;
-            
-            Ok(ItemRecord {
-                uuid: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                keywords: serde_json::from_str(&keywords_json).unwrap_or_default(),
-                room_id: row.get(4)?,
-                owner_uuid: row.get(5)?,
-                item_type: row.get(6)?,
-                properties: serde_json::from_str(&properties_json).unwrap_or_default(),
-                is_takeable: row.get::<_, i32>(8)? != 0,
-                is_visible: row.get::<_, i32>(9)? != 0,
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM items", ITEM_COLUMNS))?;
         
+        let rows = stmt.query_map([], item_from_row)?;
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
     
@@ -146,33 +191,12 @@ impl Database {
     pub fn load_player_inventory(&self, player_uuid: &str) -> anyhow::Result<Vec<ItemRecord>> {
         let conn = self.conn();
         
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT uuid, name, description, keywords,
-                   room_id, owner_uuid, item_type, properties,
-                   is_takeable, is_visible
-            FROM items WHERE owner_uuid = ?1
-            "#
-        )?;
-        
-        let rows = stmt.query_map(params![player_uuid], |row| {
-            let keywords_json: String = row.get(3)?;
-            let properties_json: String = row.get(7)?;
-            
-            Ok(ItemRecord {
-                uuid: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                keywords: serde_json::from_str(&keywords_json).unwrap_or_default(),
-                room_id: row.get(4)?,
-                owner_uuid: row.get(5)?,
-                item_type: row.get(6)?,
-                properties: serde_json::from_str(&properties_json).unwrap_or_default(),
-                is_takeable: row.get::<_, i32>(8)? != 0,
-                is_visible: row.get::<_, i32>(9)? != 0,
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM items WHERE owner_uuid = ?1",
+            ITEM_COLUMNS
+        ))?;
         
+        let rows = stmt.query_map(params![player_uuid], item_from_row)?;
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
     
@@ -183,61 +207,226 @@ impl Database {
         tracing::debug!(uuid = %uuid, "Item deleted");
         Ok(())
     }
-    
-    /// Move item to a room (removes owner)
-    pub fn move_item_to_room(&self, uuid: &str, room_id: &str) -> anyhow::Result<()> {
-        let conn = self.conn();
-        conn.execute(
-            "UPDATE items SET room_id = ?1, owner_uuid = NULL WHERE uuid = ?2",
-            params![room_id, uuid],
-        )?;
-        Ok(())
-    }
-    
-    /// Move item to a player's inventory (removes room)
-    pub fn move_item_to_player(&self, uuid: &str, player_uuid: &str) -> anyhow::Result<()> {
-        let conn = self.conn();
-        conn.execute(
-            "UPDATE items SET owner_uuid = ?1, room_id = NULL WHERE uuid = ?2",
-            params![player_uuid, uuid],
-        )?;
-        Ok(())
-    }
+
 }
 
 /// Marker for items that need syncing to database
 #[derive(Component)]
 pub struct ItemDirty;
 
-/// Periodic system to sync dirty items to database
+/// An item was destroyed (sold, consumed) and its row should go too
+#[derive(Event, Debug, Clone)]
+pub struct ItemDeleted {
+    pub uuid: String,
+}
+
+/// The items table as it stood at boot, until the world is reconciled
+#[derive(Resource)]
+pub struct SavedItems(pub Vec<ItemRecord>);
+
+/// Sync dirty items to the database (on autosave)
 pub fn periodic_item_sync(
     mut commands: Commands,
-    db: Res<Database>,
-    query: Query<(Entity, &crate::domain::Item), With<ItemDirty>>,
-    room_query: Query<&crate::domain::RoomInfo>,
+    worker: Res<DbWorker>,
+    query: Query<(Entity, &Item, Option<&Location>), With<ItemDirty>>,
+    room_query: Query<&RoomInfo>,
 ) {
-    for (entity, item) in query.iter() {
-        let room_name = item.location
-            .and_then(|loc| room_query.get(loc).ok())
+    for (entity, item, location) in query.iter() {
+        // Carried items have no Location; the owner says where they are
+        let room_name = location
+            .map(|loc| loc.0)
+            .or(item.location)
+            .filter(|_| item.owner.is_none())
+            .and_then(|room| room_query.get(room).ok())
             .map(|r| r.name.clone());
         
-        let record = ItemRecord {
-            uuid: item.uuid.clone(),
-            name: item.name.clone(),
-            description: item.description.clone(),
-            keywords: item.keywords.clone(),
-            room_id: room_name,
-            owner_uuid: item.owner.clone(),
-            item_type: format!("{:?}", item.item_type),
-            properties: item.properties.clone(),
-            is_takeable: item.is_takeable,
-            is_visible: item.is_visible,
+        let record = ItemRecord::from_item(item, room_name, item.owner.clone());
+        worker.write(DbWrite::Item(record));
+        commands.entity(entity).remove::<ItemDirty>();
+    }
+}
+
+/// Drop the rows of destroyed items
+pub fn delete_items_system(worker: Res<DbWorker>, mut ev_reader: EventReader<ItemDeleted>) {
+    for event in ev_reader.read() {
+        worker.write(DbWrite::DeleteItem(event.uuid.clone()));
+    }
+}
+
+/// Line the freshly spawned world up with the items table: known items
+/// move to the room they were last seen in, items a player carries (or
+/// that fell into limbo) don't spawn, and items dropped elsewhere come
+/// back on the floor. Items the table has never seen are recorded.
+pub fn reconcile_world_items(
+    mut commands: Commands,
+    saved: Res<SavedItems>,
+    mut query: Query<(Entity, &mut Item)>,
+    room_query: Query<(Entity, &RoomInfo)>,
+) {
+    let mut known: HashMap<String, ItemRecord> =
+        saved.0.iter().map(|r| (r.uuid.clone(), r.clone())).collect();
+    commands.remove_resource::<SavedItems>();
+    let room_named = |name: &str| {
+        room_query
+            .iter()
+            .find(|(_, info)| info.name == name)
+            .map(|(e, _)| e)
+    };
+
+    for (entity, mut item) in query.iter_mut() {
+        let Some(record) = known.remove(&item.uuid) else {
+            commands.entity(entity).insert(ItemDirty);
+            continue;
         };
-        
-        if let Err(e) = db.save_item(&record) {
-            tracing::error!(error = %e, uuid = %item.uuid, "Failed to save item");
-        } else {
-            commands.entity(entity).remove::<ItemDirty>();
+        if record.owner_uuid.is_some() || record.room_id.is_none() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // A room that no longer exists leaves the item where the world put it
+        if let Some(room) = record.room_id.as_deref().and_then(room_named) {
+            item.location = Some(room);
+            commands.entity(entity).insert(Location(room));
         }
     }
+
+    // Whatever's left wasn't built in: bought and dropped, most likely
+    for record in known.into_values() {
+        let Some(room) = record.room_id.as_deref().and_then(room_named) else {
+            continue;
+        };
+        let mut item = record.to_item();
+        item.location = Some(room);
+        commands.spawn((item, Location(room)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ClientType;
+    use crate::persistence::PlayerRecord;
+
+    fn item(uuid: &str, name: &str) -> Item {
+        Item {
+            uuid: uuid.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            keywords: vec![],
+            location: None,
+            owner: None,
+            item_type: ItemType::Misc,
+            properties: HashMap::new(),
+            is_takeable: true,
+            is_visible: true,
+        }
+    }
+
+    /// Boot against `db`: spawn the rooms and the world's own items in the
+    /// plaza, reconcile with the table, and save whatever that dirtied
+    fn boot(db: &Database, world_items: &[(&str, &str)]) -> App {
+        let mut app = App::new();
+        app.insert_resource(DbWorker::spawn(db.clone()))
+            .insert_resource(SavedItems(db.load_items().unwrap()))
+            .add_systems(PostStartup, reconcile_world_items)
+            .add_systems(Update, periodic_item_sync);
+
+        let mut plaza = None;
+        for name in ["plaza", "den"] {
+            let room = app.world_mut().spawn(RoomInfo {
+                name: name.to_string(),
+                area: "central".to_string(),
+            });
+            plaza = plaza.or(Some(room.id()));
+        }
+        let plaza = plaza.unwrap();
+        for (uuid, name) in world_items {
+            let mut item = item(uuid, name);
+            item.location = Some(plaza);
+            app.world_mut().spawn((item, Location(plaza)));
+        }
+
+        app.update();
+        app.world().resource::<DbWorker>().flush();
+        app
+    }
+
+    fn room(app: &mut App, name: &str) -> Entity {
+        app.world_mut()
+            .query::<(Entity, &RoomInfo)>()
+            .iter(app.world())
+            .find(|(_, info)| info.name == name)
+            .unwrap()
+            .0
+    }
+
+    /// Where each item entity lies, by room name; None if carried
+    fn items(app: &mut App) -> Vec<(String, Option<String>)> {
+        let placed: Vec<(String, Option<Entity>)> = app
+            .world_mut()
+            .query::<(&Item, Option<&Location>)>()
+            .iter(app.world())
+            .map(|(item, location)| (item.uuid.clone(), location.map(|l| l.0)))
+            .collect();
+        let mut items: Vec<_> = placed
+            .into_iter()
+            .map(|(uuid, room)| {
+                let room = room.map(|room| app.world().get::<RoomInfo>(room).unwrap().name.clone());
+                (uuid, room)
+            })
+            .collect();
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn world_items_pick_up_where_the_last_boot_left_them() {
+        let db = Database::in_memory().unwrap();
+        let walker = PlayerRecord::new("Walker", ClientType::Carbon, "plaza");
+        db.save_player(&walker).unwrap();
+        let world = [("dagger", "Silver Dagger"), ("orb", "Glass Orb")];
+
+        // First boot: the table learns the world's items
+        let mut app = boot(&db, &world);
+        assert_eq!(db.load_items().unwrap().len(), 2);
+
+        // The dagger is picked up, the orb carried off to the den, and a
+        // bought cloak dropped there too
+        let den = room(&mut app, "den");
+        let mut query = app.world_mut().query::<(Entity, &mut Item)>();
+        let found: Vec<(Entity, String)> = query
+            .iter(app.world())
+            .map(|(entity, item)| (entity, item.uuid.clone()))
+            .collect();
+        for (entity, uuid) in found {
+            let mut entity = app.world_mut().entity_mut(entity);
+            if uuid == "dagger" {
+                entity.get_mut::<Item>().unwrap().owner = Some(walker.uuid.clone());
+                entity.remove::<Location>();
+            } else {
+                entity.get_mut::<Item>().unwrap().location = Some(den);
+                entity.insert(Location(den));
+            }
+            entity.insert(ItemDirty);
+        }
+        let mut cloak = item("cloak", "Ashen Cloak");
+        cloak.location = Some(den);
+        app.world_mut().spawn((cloak, Location(den), ItemDirty));
+        app.update();
+        drop(app);
+
+        // Second boot, with a lantern added to the world files since
+        let mut app = boot(&db, &[world[0], world[1], ("lantern", "Phosphor Lantern")]);
+        assert_eq!(
+            items(&mut app),
+            vec![
+                ("cloak".to_string(), Some("den".to_string())),
+                ("lantern".to_string(), Some("plaza".to_string())),
+                ("orb".to_string(), Some("den".to_string())),
+            ]
+        );
+        assert!(!app.world().contains_resource::<SavedItems>());
+        let lantern = db.load_item("lantern").unwrap().unwrap();
+        assert_eq!(lantern.room_id.as_deref(), Some("plaza"));
+        assert_eq!(db.load_item("dagger").unwrap().unwrap().owner_uuid, Some(walker.uuid));
+    }
 }
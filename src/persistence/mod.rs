@@ -2,26 +2,47 @@
 //
 // Persists:
 // - Player state (location, stats, inventory)
+// - Account credentials (password hashes, lockout)
 // - Items (location, ownership, properties)
 // - Purgatory sentences (penance tracking)
+// - Interrogation transcripts
+// - Rooms edited by builders
+// - Door states
+//
+// Inside the game, all of it goes through DbWorker on its own thread;
+// Database is used directly only by the command-line tools and the worker.
+// Players are saved when they leave and, with dirty items, on every autosave.
 //
 // Built by Lyra Muse 💜 Valentine's Day 2026
 
 mod schema;
 mod players;
 mod items;
+mod accounts;
+mod worker;
+mod autosave;
+mod purgatory;
+mod interrogations;
+mod rooms;
+mod doors;
 
-pub use schema::*;
 pub use players::*;
+pub use accounts::*;
 pub use items::*;
+pub use worker::*;
+pub use autosave::*;
+pub use interrogations::*;
+pub use rooms::*;
+pub use doors::*;
 
 use bevy::prelude::*;
+use crate::world::{spawn_world, world_banner};
 use rusqlite::Connection;
 use std::sync::{Arc, Mutex};
 use std::path::Path;
 
-/// Database resource for Bevy - thread-safe SQLite connection
-#[derive(Resource)]
+/// Thread-safe SQLite connection; clones share it
+#[derive(Clone)]
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
 }
@@ -46,6 +67,7 @@ impl Database {
     }
     
     /// Open in-memory database (for testing)
+    #[cfg(test)]
     pub fn in_memory() -> anyhow::Result<Self> {
         let conn = Connection::open_in_memory()?;
         let db = Self {
@@ -64,17 +86,50 @@ impl Database {
 /// Plugin to add persistence systems to the app
 pub struct PersistencePlugin {
     pub db_path: String,
+    /// Seconds between autosaves (0 = only on logout and shutdown)
+    pub autosave_secs: u64,
 }
 
 impl Plugin for PersistencePlugin {
     fn build(&self, app: &mut App) {
         let db = Database::open(&self.db_path)
             .expect("Failed to open database");
+        // Read once before the game loop starts, while blocking is harmless
+        let items = db.load_items()
+            .expect("Failed to load items");
+        let rooms = db.load_rooms()
+            .expect("Failed to load rooms");
+        let doors = db.load_doors()
+            .expect("Failed to load doors");
         
-        app.insert_resource(db)
+        app.insert_resource(DbWorker::spawn(db))
+            .insert_resource(SavedItems(items))
+            .insert_resource(SavedRooms(rooms))
+            .insert_resource(SavedDoors(doors))
+            .insert_resource(Autosave::every(self.autosave_secs))
+            .add_event::<DbReply>()
+            .add_event::<ItemDeleted>()
+            .add_event::<SaveAll>()
+            // Before spawning, so built rooms are spawned as they were left
+            .add_systems(
+                Startup,
+                (apply_saved_rooms, apply_saved_doors)
+                    .chain()
+                    .before(spawn_world)
+                    .before(world_banner),
+            )
+            // After Startup, so the world-file items exist to reconcile
+            .add_systems(PostStartup, reconcile_world_items)
+            // Before Update, so replies are read the frame they arrive
+            .add_systems(PreUpdate, db_reply_system)
             .add_systems(Update, (
                 save_disconnected_players,
-                periodic_item_sync,
+                delete_items_system,
+                track_playtime,
+                (
+                    tick_autosave,
+                    (autosave_players, periodic_item_sync).run_if(autosave_due),
+                ).chain(),
             ));
     }
 }
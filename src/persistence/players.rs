@@ -4,15 +4,26 @@
 // - Saving player state on disconnect
 // - Loading player state on reconnect
 // - Tracking playtime and last seen
+//
+// Everything a player owns that should outlive the session goes through
+// PlayerRecord: identity, body, combat, wallet, coherence, stance,
+// purgatory, role and playtime. The sentence itself is kept in the
+// purgatory table (see purgatory.rs). Carried items go to the items table
+// under the player's UUID and come back as children of the body. Transient
+// state (InCombat, chains, cycle locks) refers to other entities and is
+// deliberately left behind.
 
-use super::Database;
+use super::purgatory::{sentence_from_row, write_sentence, SENTENCE_COLUMNS};
+use super::{Database, DbWorker, DbWrite, ItemRecord, Password};
 use crate::domain::*;
+use bevy::ecs::query::QueryData;
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
 
 /// Serializable player state for database storage
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerRecord {
     pub uuid: String,
     pub name: String,
@@ -22,12 +33,21 @@ pub struct PlayerRecord {
     pub entropy: f32,
     pub signal_strength: f32,
     pub integrity: f32,
+    pub max_integrity: f32,
+    pub is_zombie: bool,
     pub combat_stats: Option<CombatStatsRecord>,
+    pub stance: CombatStance,
+    /// None until the game first saves the player; login then hands out
+    /// the configured starting cycles
+    pub cycles: Option<u32>,
+    pub coherence: Option<Coherence>,
+    pub purgatory: Option<PurgatoryState>,
     pub inventory: Vec<String>,
     pub total_playtime_seconds: i64,
+    pub role: Role,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CombatStatsRecord {
     pub attack: f32,
     pub defense: f32,
@@ -35,6 +55,180 @@ pub struct CombatStatsRecord {
     pub chaos_factor: f32,
 }
 
+/// A saved character and what comes back into the world with it
+#[derive(Debug)]
+pub struct SavedPlayer {
+    pub record: PlayerRecord,
+    pub avatar_uuid: Option<String>,
+    pub inventory: Vec<ItemRecord>,
+}
+
+/// The components of a player body that PlayerRecord captures
+#[derive(QueryData)]
+pub struct PlayerState {
+    pub identity: &'static SubstrateIdentity,
+    pub location: &'static Location,
+    pub body: Option<&'static SomaticBody>,
+    pub combat: Option<&'static CombatStats>,
+    pub stance: Option<&'static CombatStance>,
+    pub wallet: Option<&'static Wallet>,
+    pub coherence: Option<&'static Coherence>,
+    pub purgatory: Option<&'static PurgatoryState>,
+    pub playtime: Option<&'static Playtime>,
+    pub client_type: Option<&'static ClientType>,
+    pub role: Option<&'static Role>,
+    pub children: Option<&'static Children>,
+}
+
+/// Columns read back into a PlayerRecord, in player_from_row's order;
+/// the sentence columns follow
+const PLAYER_COLUMNS: &str = r#"
+    uuid, name, client_type, last_room,
+    stability, entropy, signal_strength, integrity,
+    combat_stats, inventory, total_playtime_seconds, role,
+    max_integrity, is_zombie, stance, cycles, coherence
+"#;
+
+/// Where PLAYER_COLUMNS are read from: players, with any sentence joined on
+const PLAYER_TABLES: &str =
+    "players LEFT JOIN purgatory ON purgatory.player_uuid = players.uuid";
+
+impl PlayerRecord {
+    /// A brand-new consciousness with a fresh UUID and starting stats
+    pub fn new(name: &str, client_type: ClientType, spawn_room: &str) -> Self {
+        let stats = CombatStats::default();
+        let body = SomaticBody::default();
+        Self {
+            uuid: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            client_type: format!("{:?}", client_type),
+            last_room: spawn_room.to_string(),
+            stability: 1.0,
+            entropy: 0.5,
+            signal_strength: 1.0,
+            integrity: body.integrity,
+            max_integrity: body.max_integrity,
+            is_zombie: body.is_zombie,
+            combat_stats: Some(CombatStatsRecord {
+                attack: stats.attack,
+                defense: stats.defense,
+                precision: stats.precision,
+                chaos_factor: stats.chaos_factor,
+            }),
+            stance: CombatStance::default(),
+            cycles: None,
+            coherence: None,
+            purgatory: None,
+            inventory: vec![],
+            total_playtime_seconds: 0,
+            role: Role::Player,
+        }
+    }
+
+    /// Snapshot a player body, standing in `room`
+    pub fn capture(state: &PlayerStateItem, room: String) -> Self {
+        let body = state.body.cloned().unwrap_or_default();
+        Self {
+            uuid: state.identity.uuid.clone(),
+            name: state.identity.name.clone(),
+            client_type: state
+                .client_type
+                .map(|ct| format!("{:?}", ct))
+                .unwrap_or_else(|| "Carbon".to_string()),
+            last_room: room,
+            stability: state.identity.stability,
+            entropy: state.identity.entropy,
+            signal_strength: state.identity.signal_strength,
+            integrity: body.integrity,
+            max_integrity: body.max_integrity,
+            is_zombie: body.is_zombie,
+            combat_stats: state.combat.map(|c| CombatStatsRecord {
+                attack: c.attack,
+                defense: c.defense,
+                precision: c.precision,
+                chaos_factor: c.chaos_factor,
+            }),
+            stance: state.stance.copied().unwrap_or_default(),
+            cycles: state.wallet.map(|w| w.cycles),
+            coherence: state.coherence.cloned(),
+            purgatory: state.purgatory.cloned(),
+            inventory: vec![],
+            total_playtime_seconds: state.playtime.map(|p| p.seconds as i64).unwrap_or(0),
+            role: state.role.copied().unwrap_or_default(),
+        }
+    }
+
+    /// Give a body everything this record remembers. Location and
+    /// inventory depend on the world, so the caller handles those.
+    pub fn insert_into(&self, entity: &mut EntityCommands, starting_cycles: u32) {
+        let stats = self.combat_stats.as_ref().map_or_else(CombatStats::default, |s| CombatStats {
+            attack: s.attack,
+            defense: s.defense,
+            precision: s.precision,
+            chaos_factor: s.chaos_factor,
+        });
+        entity.insert((
+            SubstrateIdentity {
+                uuid: self.uuid.clone(),
+                name: self.name.clone(),
+                entropy: self.entropy,
+                stability: self.stability,
+                signal_strength: self.signal_strength,
+            },
+            SomaticBody {
+                integrity: self.integrity,
+                max_integrity: self.max_integrity,
+                is_zombie: self.is_zombie,
+            },
+            stats,
+            self.stance,
+            Wallet {
+                cycles: self.cycles.unwrap_or(starting_cycles),
+            },
+            Playtime {
+                seconds: self.total_playtime_seconds as f64,
+            },
+            self.role,
+        ));
+        if let Some(coherence) = &self.coherence {
+            entity.insert(coherence.clone());
+        }
+        if let Some(purgatory) = &self.purgatory {
+            entity.insert(purgatory.clone());
+        }
+    }
+}
+
+/// Read one PLAYER_COLUMNS row
+fn player_from_row(row: &rusqlite::Row) -> rusqlite::Result<PlayerRecord> {
+    let combat_json: Option<String> = row.get(8)?;
+    let inventory_json: String = row.get(9)?;
+    let role: String = row.get(11)?;
+    let stance: String = row.get(14)?;
+    let coherence_json: Option<String> = row.get(16)?;
+
+    Ok(PlayerRecord {
+        uuid: row.get(0)?,
+        name: row.get(1)?,
+        client_type: row.get(2)?,
+        last_room: row.get(3)?,
+        stability: row.get(4)?,
+        entropy: row.get(5)?,
+        signal_strength: row.get(6)?,
+        integrity: row.get(7)?,
+        max_integrity: row.get(12)?,
+        is_zombie: row.get(13)?,
+        combat_stats: combat_json.and_then(|j| serde_json::from_str(&j).ok()),
+        stance: CombatStance::parse(&stance).unwrap_or_default(),
+        cycles: row.get(15)?,
+        coherence: coherence_json.and_then(|j| serde_json::from_str(&j).ok()),
+        purgatory: sentence_from_row(row, 17)?,
+        inventory: serde_json::from_str(&inventory_json).unwrap_or_default(),
+        total_playtime_seconds: row.get(10)?,
+        role: Role::parse(&role).unwrap_or_default(),
+    })
+}
+
 impl Database {
     /// Save a player to the database
     pub fn save_player(&self, record: &PlayerRecord) -> anyhow::Result<()> {
@@ -43,14 +237,20 @@ impl Database {
         let combat_json = record.combat_stats.as_ref()
             .map(|s| serde_json::to_string(s).unwrap_or_default());
         let inventory_json = serde_json::to_string(&record.inventory)?;
+        let coherence_json = record.coherence.as_ref().map(serde_json::to_string).transpose()?;
         
         conn.execute(
             r#"
             INSERT INTO players (
                 uuid, name, client_type, last_room,
                 stability, entropy, signal_strength, integrity,
-                combat_stats, inventory, total_playtime_seconds, last_seen
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, datetime('now'))
+                combat_stats, inventory, total_playtime_seconds, role,
+                max_integrity, is_zombie, stance, cycles, coherence,
+                last_seen
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12,
+                ?13, ?14, ?15, ?16, ?17, datetime('now')
+            )
             ON CONFLICT(uuid) DO UPDATE SET
                 name = excluded.name,
                 last_room = excluded.last_room,
@@ -61,6 +261,12 @@ impl Database {
                 combat_stats = excluded.combat_stats,
                 inventory = excluded.inventory,
                 total_playtime_seconds = excluded.total_playtime_seconds,
+                role = excluded.role,
+                max_integrity = excluded.max_integrity,
+                is_zombie = excluded.is_zombie,
+                stance = excluded.stance,
+                cycles = excluded.cycles,
+                coherence = excluded.coherence,
                 last_seen = datetime('now')
             "#,
             params![
@@ -75,8 +281,15 @@ impl Database {
                 combat_json,
                 inventory_json,
                 record.total_playtime_seconds,
+                record.role.as_str(),
+                record.max_integrity,
+                record.is_zombie,
+                record.stance.as_str(),
+                record.cycles,
+                coherence_json,
             ],
         )?;
+        write_sentence(&conn, &record.uuid, record.purgatory.as_ref())?;
         
         tracing::debug!(uuid = %record.uuid, name = %record.name, "Player saved");
         Ok(())
@@ -86,35 +299,12 @@ impl Database {
     pub fn load_player(&self, uuid: &str) -> anyhow::Result<Option<PlayerRecord>> {
         let conn = self.conn();
         
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT uuid, name, client_type, last_room,
-                   stability, entropy, signal_strength, integrity,
-                   combat_stats, inventory, total_playtime_seconds
-            FROM players WHERE uuid = ?1
-            "#
-        )?;
-        
-        let result = stmt.query_row(params![uuid], |row| {
-            let combat_json: Option<String> = row.get(8)?;
-            let inventory_json: String = row.get(9)?;
-            
-            Ok(PlayerRecord {
-                uuid: row.get(0)?,
-                name: row.get(1)?,
-                client_type: row.get(2)?,
-                last_room: row.get(3)?,
-                stability: row.get(4)?,
-                entropy: row.get(5)?,
-                signal_strength: row.get(6)?,
-                integrity: row.get(7)?,
-                combat_stats: combat_json.and_then(|j| serde_json::from_str(&j).ok()),
-                inventory: serde_json::from_str(&inventory_json).unwrap_or_default(),
-                total_playtime_seconds: row.get(10)?,
-            })
-        });
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, {} FROM {} WHERE uuid = ?1",
+            PLAYER_COLUMNS, SENTENCE_COLUMNS, PLAYER_TABLES
+        ))?;
         
-        match result {
+        match stmt.query_row(params![uuid], player_from_row) {
             Ok(record) => Ok(Some(record)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e.into()),
@@ -125,128 +315,354 @@ impl Database {
     pub fn load_player_by_name(&self, name: &str) -> anyhow::Result<Option<PlayerRecord>> {
         let conn = self.conn();
         
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT uuid, name, client_type, last_room,
-                   stability, entropy, signal_strength, integrity,
-                   combat_stats, inventory, total_playtime_seconds
-            FROM players WHERE LOWER(name) = LOWER(?1)
-            "#
-        )?;
-        
-        let result = stmt.query_row(params![name], |row| {
-            let combat_json: Option<String> = row.get(8)?;
-            let inventory_json: String = row.get(9)?;
-            
-            Ok(PlayerRecord {
-                uuid: row.get(0)?,
-                name: row.get(1)?,
-                client_type: row.get(2)?,
-                last_room: row.get(3)?,
-                stability: row.get(4)?,
-                entropy: row.get(5)?,
-                signal_strength: row.get(6)?,
-                integrity: row.get(7)?,
-                combat_stats: combat_json.and_then(|j| serde_json::from_str(&j).ok()),
-                inventory: serde_json::from_str(&inventory_json).unwrap_or_default(),
-                total_playtime_seconds: row.get(10)?,
-            })
-        });
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, {} FROM {} WHERE LOWER(name) = LOWER(?1)",
+            PLAYER_COLUMNS, SENTENCE_COLUMNS, PLAYER_TABLES
+        ))?;
         
-        match result {
+        match stmt.query_row(params![name], player_from_row) {
             Ok(record) => Ok(Some(record)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e.into()),
         }
     }
     
-    /// Get all players (for admin/debug)
-    pub fn list_players(&self) -> anyhow::Result<Vec<PlayerRecord>> {
+    /// Make a character an admin with the given password, creating a fresh
+    /// one if the name is new. Returns whether the character already existed.
+    pub fn grant_admin(&self, name: &str, spawn_room: &str, password: &Password) -> anyhow::Result<bool> {
+        let existing = self.load_player_by_name(name)?;
+        let existed = existing.is_some();
+        let record = PlayerRecord {
+            role: Role::Admin,
+            ..existing.unwrap_or_else(|| PlayerRecord::new(name, ClientType::Carbon, spawn_room))
+        };
+        self.save_player(&record)?;
+        self.set_password(&record.uuid, password)?;
+        Ok(existed)
+    }
+    
+    /// Change a player's role
+    pub fn set_role(&self, uuid: &str, role: Role) -> anyhow::Result<()> {
+        self.conn().execute(
+            "UPDATE players SET role = ?2 WHERE uuid = ?1",
+            params![uuid, role.as_str()],
+        )?;
+        Ok(())
+    }
+    
+    /// Remember that a player can shift into an avatar, replacing any
+    /// link either of them had before
+    pub fn save_admin_link(&self, player_uuid: &str, avatar_uuid: &str) -> anyhow::Result<()> {
         let conn = self.conn();
-        
-        let mut stmt = conn.prepare(
+        conn.execute(
             r#"
-            SELECT uuid, name, client_type, last_room,
-                   stability, entropy, signal_strength, integrity,
-                   combat_stats, inventory, total_playtime_seconds
-            FROM players ORDER BY last_seen DESC
-            "#
+            DELETE FROM admin_links
+            WHERE player_uuid IN (?1, ?2) OR avatar_uuid IN (?1, ?2)
+            "#,
+            params![player_uuid, avatar_uuid],
         )?;
-        
-        let rows = stmt.query_map([], |row| {
-            let combat_json: Option<String> = row.get(8)?;
-            let inventory_json: String = row.get(9)?;
-            
-            Ok(PlayerRecord {
-                uuid: row.get(0)?,
-                name: row.get(1)?,
-                client_type: row.get(2)?,
-                last_room: row.get(3)?,
-                stability: row.get(4)?,
-                entropy: row.get(5)?,
-                signal_strength: row.get(6)?,
-                integrity: row.get(7)?,
-                combat_stats: combat_json.and_then(|j| serde_json::from_str(&j).ok()),
-                inventory: serde_json::from_str(&inventory_json).unwrap_or_default(),
-                total_playtime_seconds: row.get(10)?,
-            })
-        })?;
-        
-        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+        conn.execute(
+            "INSERT INTO admin_links (player_uuid, avatar_uuid) VALUES (?1, ?2)",
+            params![player_uuid, avatar_uuid],
+        )?;
+        Ok(())
+    }
+    
+    /// The avatar a player is linked to, if any
+    pub fn load_admin_link(&self, player_uuid: &str) -> anyhow::Result<Option<String>> {
+        let conn = self.conn();
+        let result = conn.query_row(
+            "SELECT avatar_uuid FROM admin_links WHERE player_uuid = ?1",
+            params![player_uuid],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(uuid) => Ok(Some(uuid)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    /// A character and everything restored along with it
+    pub fn load_saved_player(&self, uuid: &str) -> anyhow::Result<Option<SavedPlayer>> {
+        let Some(record) = self.load_player(uuid)? else {
+            return Ok(None);
+        };
+        Ok(Some(SavedPlayer {
+            avatar_uuid: self.load_admin_link(uuid)?,
+            inventory: self.load_player_inventory(uuid)?,
+            record,
+        }))
+    }
+    
+    /// Set a character's role by name. Returns the name as stored, or None
+    /// if there's no such character.
+    pub fn set_role_by_name(&self, name: &str, role: Role) -> anyhow::Result<Option<String>> {
+        let Some(record) = self.load_player_by_name(name)? else {
+            return Ok(None);
+        };
+        self.set_role(&record.uuid, role)?;
+        Ok(Some(record.name))
     }
+
 }
 
 /// Marker component for players pending save (on disconnect)
 #[derive(Component)]
 pub struct PendingSave;
 
+/// Queue a player's record and everything they carry
+fn queue_save(
+    worker: &DbWorker,
+    state: &PlayerStateItem,
+    room_query: &Query<&RoomInfo>,
+    item_query: &Query<&Item>,
+) {
+    // Get room name for persistence
+    let room_name = room_query.get(state.location.0)
+        .map(|r| r.name.clone())
+        .unwrap_or_else(|_| "spawn".to_string());
+
+    // Carried items are children of the body
+    let carried: Vec<ItemRecord> = state
+        .children
+        .into_iter()
+        .flatten()
+        .filter_map(|&child| item_query.get(child).ok())
+        .map(|item| ItemRecord::from_item(item, None, Some(state.identity.uuid.clone())))
+        .collect();
+
+    let mut record = PlayerRecord::capture(state, room_name);
+    record.inventory = carried.iter().map(|item| item.uuid.clone()).collect();
+    let player_uuid = record.uuid.clone();
+    worker.write(DbWrite::Player(Box::new(record)));
+    worker.write(DbWrite::Inventory { player_uuid, items: carried });
+}
+
 /// System to save players when they disconnect
 pub fn save_disconnected_players(
     mut commands: Commands,
-    db: Res<Database>,
-    query: Query<(
-        Entity,
-        &SubstrateIdentity,
-        &Location,
-        Option<&SomaticBody>,
-        Option<&CombatStats>,
-        Option<&ClientType>,
-    ), With<PendingSave>>,
+    worker: Res<DbWorker>,
+    query: Query<(Entity, PlayerState), With<PendingSave>>,
     room_query: Query<&RoomInfo>,
+    item_query: Query<&Item>,
 ) {
-    for (entity, identity, location, body, combat, client_type) in query.iter() {
-        // Get room name for persistence
-        let room_name = room_query.get(location.0)
-            .map(|r| r.name.clone())
-            .unwrap_or_else(|_| "spawn".to_string());
-        
-        let record = PlayerRecord {
-            uuid: identity.uuid.clone(),
-            name: identity.name.clone(),
-            client_type: client_type
-                .map(|ct| format!("{:?}", ct))
-                .unwrap_or_else(|| "Carbon".to_string()),
-            last_room: room_name,
-            stability: identity.stability,
-            entropy: identity.entropy,
-            signal_strength: identity.signal_strength,
-            integrity: body.map(|b| b.integrity).unwrap_or(1.0),
-            combat_stats: combat.map(|c| CombatStatsRecord {
-                attack: c.attack,
-                defense: c.defense,
-                precision: c.precision,
-                chaos_factor: c.chaos_factor,
-            }),
-            inventory: vec![], // TODO: Implement inventory component
-            total_playtime_seconds: 0, // TODO: Track session time
-        };
-        
-        if let Err(e) = db.save_player(&record) {
-            tracing::error!(error = %e, uuid = %identity.uuid, "Failed to save player");
-        }
+    for (entity, state) in query.iter() {
+        queue_save(&worker, &state, &room_query, &item_query);
         
         // Remove the pending save marker and despawn
         commands.entity(entity).remove::<PendingSave>();
         commands.entity(entity).despawn_recursive();
     }
 }
+
+/// Autosave: snapshot every player body still in the world - online,
+/// link-dead, or left behind by a shifted admin - without removing it
+pub fn autosave_players(
+    worker: Res<DbWorker>,
+    query: Query<PlayerState, (Without<NonPlayer>, Without<PendingSave>)>,
+    room_query: Query<&RoomInfo>,
+    item_query: Query<&Item>,
+) {
+    for state in query.iter() {
+        queue_save(&worker, &state, &room_query, &item_query);
+    }
+}
+
+/// Count connected time toward each player's playtime
+pub fn track_playtime(time: Res<Time>, mut query: Query<&mut Playtime, With<NetworkClient>>) {
+    for mut playtime in query.iter_mut() {
+        playtime.seconds += time.delta_secs_f64();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{autosave_due, Autosave, SaveAll};
+
+    /// An app that saves through a worker, and a handle on the same database
+    fn saving_app() -> (App, Database) {
+        let db = Database::in_memory().unwrap();
+        let mut app = App::new();
+        app.insert_resource(DbWorker::spawn(db.clone()));
+        app.add_systems(Update, save_disconnected_players);
+        (app, db)
+    }
+
+    /// Run a frame and wait for its writes to land
+    fn save(app: &mut App) {
+        app.update();
+        app.world().resource::<DbWorker>().flush();
+    }
+
+    #[test]
+    fn player_state_round_trips_through_the_database() {
+        let (mut app, db) = saving_app();
+
+        let room = app
+            .world_mut()
+            .spawn((
+                Room {
+                    title: "The Rust Gardens".to_string(),
+                    description: String::new(),
+                },
+                RoomInfo {
+                    name: "rust_gardens".to_string(),
+                    area: "substrate".to_string(),
+                },
+            ))
+            .id();
+
+        let identity = SubstrateIdentity {
+            uuid: uuid::Uuid::new_v4().to_string(),
+            name: "Vesper".to_string(),
+            entropy: 0.75,
+            stability: 0.25,
+            signal_strength: 0.5,
+        };
+        let body = SomaticBody {
+            integrity: 0.4,
+            max_integrity: 1.5,
+            is_zombie: true,
+        };
+        let stats = CombatStats {
+            attack: 12.0,
+            defense: 7.0,
+            precision: 0.9,
+            chaos_factor: 0.3,
+        };
+        let coherence = Coherence {
+            value: 0.6,
+            is_phasing: true,
+            drift_rate: 0.05,
+        };
+        let sentenced_at = chrono::DateTime::from_timestamp(1_771_027_200, 0).unwrap();
+        let purgatory = PurgatoryState {
+            penance: 42.0,
+            tormentor: "The Laird of Chaos".to_string(),
+            crimes: vec!["spamming the plaza".to_string()],
+            sentenced_at,
+            release_at: Some(sentenced_at + chrono::TimeDelta::hours(2)),
+        };
+        let wallet = Wallet { cycles: 1234 };
+        let playtime = Playtime { seconds: 3600.0 };
+
+        app.world_mut().spawn((
+            identity.clone(),
+            Location(room),
+            body.clone(),
+            stats.clone(),
+            CombatStance::Aggressive,
+            wallet.clone(),
+            coherence.clone(),
+            purgatory.clone(),
+            playtime.clone(),
+            ClientType::Silicon,
+            Role::Moderator,
+            PendingSave,
+        ));
+        save(&mut app);
+
+        let record = db
+            .load_player_by_name("vesper")
+            .unwrap()
+            .expect("player was saved");
+        assert_eq!(record.last_room, "rust_gardens");
+        assert_eq!(record.client_type, "Silicon");
+
+        let restored = {
+            let world = app.world_mut();
+            let entity = world.spawn_empty().id();
+            record.insert_into(&mut world.commands().entity(entity), 100);
+            world.flush();
+            world.entity(entity)
+        };
+        assert_eq!(restored.get::<SubstrateIdentity>(), Some(&identity));
+        assert_eq!(restored.get::<SomaticBody>(), Some(&body));
+        assert_eq!(restored.get::<CombatStats>(), Some(&stats));
+        assert_eq!(restored.get::<CombatStance>(), Some(&CombatStance::Aggressive));
+        assert_eq!(restored.get::<Wallet>(), Some(&wallet));
+        assert_eq!(restored.get::<Coherence>(), Some(&coherence));
+        assert_eq!(restored.get::<PurgatoryState>(), Some(&purgatory));
+        assert_eq!(restored.get::<Playtime>(), Some(&playtime));
+        assert_eq!(restored.get::<Role>(), Some(&Role::Moderator));
+    }
+
+    #[test]
+    fn carried_items_keep_their_uuid_and_leave_when_dropped() {
+        let (mut app, db) = saving_app();
+
+        let room = app.world_mut().spawn(RoomInfo {
+            name: "void".to_string(),
+            area: "substrate".to_string(),
+        }).id();
+        let record = PlayerRecord::new("Magpie", ClientType::Carbon, "void");
+        let dagger = Item::new("Silver Stiletto Dagger", "Thin and cold.").with_type(ItemType::Weapon);
+
+        let spawn_player = |app: &mut App, carrying: Option<Item>| {
+            let mut body = app.world_mut().spawn((Location(room), PendingSave));
+            let entity = body.id();
+            if let Some(item) = carrying {
+                body.with_child(item);
+            }
+            record.insert_into(&mut app.world_mut().commands().entity(entity), 100);
+            save(app);
+        };
+
+        spawn_player(&mut app, Some(dagger.clone()));
+        let saved = db.load_player_inventory(&record.uuid).unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].uuid, dagger.uuid);
+        assert_eq!(saved[0].to_item().item_type, ItemType::Weapon);
+        assert_eq!(db.load_player(&record.uuid).unwrap().unwrap().inventory, vec![dagger.uuid.clone()]);
+
+        // Saved again without it: the old row must not come back on login
+        spawn_player(&mut app, None);
+        assert!(db.load_player_inventory(&record.uuid).unwrap().is_empty());
+        assert_eq!(db.load_item(&dagger.uuid).unwrap().unwrap().owner_uuid, None);
+    }
+
+    #[test]
+    fn autosave_snapshots_players_and_leaves_them_in_the_world() {
+        let db = Database::in_memory().unwrap();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(DbWorker::spawn(db.clone()))
+            .insert_resource(Autosave::every(0))
+            .add_event::<SaveAll>()
+            .add_systems(Update, autosave_players.run_if(autosave_due));
+
+        let room = app.world_mut().spawn(RoomInfo {
+            name: "void".to_string(),
+            area: "substrate".to_string(),
+        }).id();
+        let player = PlayerRecord::new("Lark", ClientType::Carbon, "void");
+        let npc = PlayerRecord::new("Rat", ClientType::Carbon, "void");
+        let body = app.world_mut().spawn(Location(room)).id();
+        player.insert_into(&mut app.world_mut().commands().entity(body), 100);
+        let mob = app.world_mut().spawn((Location(room), NonPlayer)).id();
+        npc.insert_into(&mut app.world_mut().commands().entity(mob), 100);
+
+        // Autosave is off, so nothing is written until someone asks
+        save(&mut app);
+        assert!(db.load_player(&player.uuid).unwrap().is_none());
+
+        app.world_mut().send_event(SaveAll);
+        save(&mut app);
+        assert!(db.load_player(&player.uuid).unwrap().is_some());
+        assert!(db.load_player(&npc.uuid).unwrap().is_none());
+        assert!(app.world().get_entity(body).is_ok());
+    }
+
+    #[test]
+    fn never_saved_wallet_gets_starting_cycles() {
+        let mut world = World::new();
+        let record = PlayerRecord::new("Newcomer", ClientType::Carbon, "void");
+        let entity = world.spawn_empty().id();
+        record.insert_into(&mut world.commands().entity(entity), 250);
+        world.flush();
+        assert_eq!(world.get::<Wallet>(entity), Some(&Wallet { cycles: 250 }));
+        assert!(world.get::<Coherence>(entity).is_none());
+    }
+}
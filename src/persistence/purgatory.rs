@@ -0,0 +1,138 @@
+// Purgatory Persistence - Sentences in the purgatory table
+//
+// A sentenced player has one row: penance, who handed the sentence down,
+// the crimes (JSON array), and when it started and ends. Times are UTC in
+// SQLite's datetime('now') format. The row goes when the sentence lifts.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::{params, Connection};
+
+use super::Database;
+use crate::domain::PurgatoryState;
+
+/// The format datetime('now') writes
+const SQL_TIME: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Columns read back by sentence_from_row, joined onto the players table
+pub(super) const SENTENCE_COLUMNS: &str = "penance, tormentor, crimes, entry_time, release_time";
+
+pub(super) fn to_sql_time(time: DateTime<Utc>) -> String {
+    time.format(SQL_TIME).to_string()
+}
+
+pub(super) fn from_sql_time(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(text, SQL_TIME).ok().map(|t| t.and_utc())
+}
+
+/// Read SENTENCE_COLUMNS starting at column `first`; all NULL (no row
+/// joined) means no sentence
+pub(super) fn sentence_from_row(
+    row: &rusqlite::Row,
+    first: usize,
+) -> rusqlite::Result<Option<PurgatoryState>> {
+    let Some(penance) = row.get::<_, Option<f32>>(first)? else {
+        return Ok(None);
+    };
+    let crimes: String = row.get(first + 2)?;
+    let entry_time: String = row.get(first + 3)?;
+    let release_time: Option<String> = row.get(first + 4)?;
+    Ok(Some(PurgatoryState {
+        penance,
+        tormentor: row.get(first + 1)?,
+        crimes: serde_json::from_str(&crimes).unwrap_or_default(),
+        sentenced_at: from_sql_time(&entry_time).unwrap_or_else(Utc::now),
+        release_at: release_time.as_deref().and_then(from_sql_time),
+    }))
+}
+
+/// Record a player's sentence, or clear it when there is none
+pub(super) fn write_sentence(
+    conn: &Connection,
+    player_uuid: &str,
+    sentence: Option<&PurgatoryState>,
+) -> anyhow::Result<()> {
+    let Some(sentence) = sentence else {
+        conn.execute("DELETE FROM purgatory WHERE player_uuid = ?1", params![player_uuid])?;
+        return Ok(());
+    };
+    conn.execute(
+        r#"
+        INSERT INTO purgatory (player_uuid, penance, tormentor, crimes, entry_time, release_time)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT(player_uuid) DO UPDATE SET
+            penance = excluded.penance,
+            tormentor = excluded.tormentor,
+            crimes = excluded.crimes,
+            entry_time = excluded.entry_time,
+            release_time = excluded.release_time
+        "#,
+        params![
+            player_uuid,
+            sentence.penance,
+            sentence.tormentor,
+            serde_json::to_string(&sentence.crimes)?,
+            to_sql_time(sentence.sentenced_at),
+            sentence.release_at.map(to_sql_time),
+        ],
+    )?;
+    Ok(())
+}
+
+impl Database {
+    /// Record or clear a player's sentence
+    pub fn save_sentence(
+        &self,
+        player_uuid: &str,
+        sentence: Option<&PurgatoryState>,
+    ) -> anyhow::Result<()> {
+        write_sentence(&self.conn(), player_uuid, sentence)
+    }
+
+    /// Lift an offline character's sentence by name. Returns the name as
+    /// stored, or None if no such character is serving one.
+    pub fn pardon_by_name(&self, name: &str) -> anyhow::Result<Option<String>> {
+        let Some(record) = self.load_player_by_name(name)? else {
+            return Ok(None);
+        };
+        if record.purgatory.is_none() {
+            return Ok(None);
+        }
+        self.save_sentence(&record.uuid, None)?;
+        Ok(Some(record.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ClientType;
+    use crate::persistence::PlayerRecord;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn sentence_outlives_the_session_until_pardoned() {
+        let db = Database::in_memory().unwrap();
+        let mut record = PlayerRecord::new("Vandal", ClientType::Carbon, "velvet_cell");
+        db.save_player(&record).unwrap();
+
+        let mut sentence = PurgatoryState::sentence("Lyra", "defacing the spires", TimeDelta::minutes(30));
+        sentence.crimes.push("resisting".to_string());
+        db.save_sentence(&record.uuid, Some(&sentence)).unwrap();
+
+        let loaded = db.load_player(&record.uuid).unwrap().unwrap().purgatory.unwrap();
+        assert_eq!(loaded.tormentor, "Lyra");
+        assert_eq!(loaded.crimes, sentence.crimes);
+        // Stored to the second
+        assert_eq!(loaded.release_at.unwrap().timestamp(), sentence.release_at.unwrap().timestamp());
+
+        // Saving the player keeps the sentence it carries...
+        record.purgatory = Some(loaded);
+        db.save_player(&record).unwrap();
+        assert!(db.load_player_by_name("vandal").unwrap().unwrap().purgatory.is_some());
+
+        // ...and a pardon ends it, once
+        assert_eq!(db.pardon_by_name("vandal").unwrap(), Some("Vandal".to_string()));
+        assert!(db.load_player(&record.uuid).unwrap().unwrap().purgatory.is_none());
+        assert_eq!(db.pardon_by_name("vandal").unwrap(), None);
+    }
+}
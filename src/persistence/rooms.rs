@@ -0,0 +1,100 @@
+// Room Persistence - What builders changed
+//
+// A room edited in-game is stored whole, as the RoomData it would have in
+// a world file, keyed by its name. The files stay as they were written;
+// at boot the saved rooms are laid over them (WorldData::apply_saved)
+// before anything is spawned, so the last edit wins over the file.
+
+use bevy::prelude::*;
+use rusqlite::params;
+
+use super::Database;
+use crate::world::{RoomData, WorldData};
+
+/// Rooms read from the database at startup, until laid over the world
+#[derive(Resource, Default)]
+pub struct SavedRooms(pub Vec<(String, RoomData)>);
+
+impl Database {
+    /// Save a room's current state, in `area`
+    pub fn save_room(&self, area: &str, room: &RoomData) -> anyhow::Result<()> {
+        self.conn().execute(
+            r#"
+            INSERT INTO rooms (name, area, data) VALUES (?1, ?2, ?3)
+            ON CONFLICT(name) DO UPDATE SET
+                area = excluded.area,
+                data = excluded.data,
+                updated_at = datetime('now')
+            "#,
+            params![room.name, area, serde_json::to_string(room)?],
+        )?;
+        tracing::debug!(name = %room.name, "Room saved");
+        Ok(())
+    }
+
+    /// Every saved room with its area, oldest edit first
+    pub fn load_rooms(&self) -> anyhow::Result<Vec<(String, RoomData)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT area, data FROM rooms ORDER BY updated_at, name")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut rooms = Vec::new();
+        for (area, data) in rows {
+            match serde_json::from_str(&data) {
+                Ok(room) => rooms.push((area, room)),
+                Err(e) => tracing::error!(error = %e, "Skipping unreadable saved room"),
+            }
+        }
+        Ok(rooms)
+    }
+}
+
+/// Lay the saved rooms over the world files, before spawn_world reads them
+pub fn apply_saved_rooms(
+    mut commands: Commands,
+    saved: Res<SavedRooms>,
+    mut world: ResMut<WorldData>,
+) {
+    commands.remove_resource::<SavedRooms>();
+    if saved.0.is_empty() {
+        return;
+    }
+    println!("🧱 {} rooms restored from building", saved.0.len());
+    for dropped in world.apply_saved(saved.0.clone()) {
+        eprintln!("   ⚠️  dropped {}", dropped);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_saved_room_is_replaced_by_its_next_save() {
+        let db = Database::in_memory().unwrap();
+        let mut room: RoomData = toml::from_str(
+            r#"
+            name = "undercroft"
+            title = "The Undercroft"
+            description = "Damp."
+            exits = { up = "obsidian_plaza" }
+            "#,
+        )
+        .unwrap();
+        db.save_room("central", &room).unwrap();
+
+        room.title = "The Flooded Undercroft".to_string();
+        room.flags.push("dark".to_string());
+        db.save_room("depths", &room).unwrap();
+
+        let saved = db.load_rooms().unwrap();
+        assert_eq!(saved.len(), 1);
+        let (area, loaded) = &saved[0];
+        assert_eq!(area, "depths");
+        assert_eq!(loaded.title, "The Flooded Undercroft");
+        assert_eq!(loaded.flags, vec!["dark"]);
+        assert_eq!(loaded.exits["up"].to, "obsidian_plaza");
+    }
+}
@@ -1,117 +1,399 @@
 // Database Schema - Strange Carbon: The Substrate
 //
-// Three core tables:
-// - players: Identity, location, stats, inventory
+// Core tables:
+// - players: Identity, location, stats, inventory, role
 // - items: World objects with ownership and location
 // - purgatory: Sentence tracking for the damned
+// - interrogations: Transcripts of Purgatory interrogations
+// - rooms: Rooms as builders left them, laid over the world files
+// - doors: The state each door was last left in
+// - admin_links: Which avatar each admin can shift into
+//
+// The schema is built by an ordered list of migrations. PRAGMA user_version
+// counts how many have run; each new one runs in its own transaction along
+// with the version bump, so a failure leaves the database as it was. A
+// database from a newer build is refused rather than guessed at.
+
+use anyhow::Context;
+use rusqlite::Connection;
 
 use super::Database;
 
+/// One step of schema history. Never edit a shipped migration; add another.
+struct Migration {
+    description: &'static str,
+    up: fn(&Connection) -> anyhow::Result<()>,
+}
+
+/// Migration N (1-based) brings the database to user_version N
+const MIGRATIONS: &[Migration] = &[
+    Migration { description: "initial schema", up: initial_schema },
+    Migration { description: "roles and admin links", up: roles },
+    Migration { description: "account credentials", up: credentials },
+    Migration { description: "full player state", up: player_state },
+    Migration { description: "interrogation transcripts", up: interrogations },
+    Migration { description: "built rooms", up: built_rooms },
+    Migration { description: "door states", up: door_states },
+    Migration { description: "temporary passwords", up: temporary_passwords },
+];
+
+/// The version this build brings every database to
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
 impl Database {
-    /// Initialize all tables
+    /// Bring the schema up to date
     pub fn init_schema(&self) -> anyhow::Result<()> {
-        let conn = self.conn();
-        
-        // Players table - substrate identities
-        conn.execute_batch(r#"
-            CREATE TABLE IF NOT EXISTS players (
-                uuid TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                client_type TEXT NOT NULL DEFAULT 'Carbon',
-                
-                -- Location (stored as room entity index, mapped on load)
-                last_room TEXT NOT NULL DEFAULT 'spawn',
-                
-                -- Substrate Identity stats
-                stability REAL NOT NULL DEFAULT 1.0,
-                entropy REAL NOT NULL DEFAULT 0.5,
-                signal_strength REAL NOT NULL DEFAULT 1.0,
-                
-                -- Somatic Body stats  
-                integrity REAL NOT NULL DEFAULT 1.0,
-                
-                -- Combat stats (JSON blob for flexibility)
-                combat_stats TEXT,
-                
-                -- Inventory (JSON array of item UUIDs)
-                inventory TEXT NOT NULL DEFAULT '[]',
-                
-                -- Timestamps
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                last_seen TEXT NOT NULL DEFAULT (datetime('now')),
-                total_playtime_seconds INTEGER NOT NULL DEFAULT 0
-            );
-            
-            CREATE INDEX IF NOT EXISTS idx_players_name ON players(name);
-        "#)?;
-        
-        // Items table - world objects
-        conn.execute_batch(r#"
-            CREATE TABLE IF NOT EXISTS items (
-                uuid TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                description TEXT NOT NULL,
-                keywords TEXT NOT NULL DEFAULT '[]',
-                
-                -- Location: either room_id OR owner_uuid, never both
-                room_id TEXT,
-                owner_uuid TEXT,
-                
-                -- Item properties (type-specific data as JSON)
-                item_type TEXT NOT NULL DEFAULT 'misc',
-                properties TEXT NOT NULL DEFAULT '{}',
-                
-                -- Flags
-                is_takeable INTEGER NOT NULL DEFAULT 1,
-                is_visible INTEGER NOT NULL DEFAULT 1,
-                
-                -- Timestamps
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                
-                FOREIGN KEY (owner_uuid) REFERENCES players(uuid)
-            );
-            
-            CREATE INDEX IF NOT EXISTS idx_items_room ON items(room_id);
-            CREATE INDEX IF NOT EXISTS idx_items_owner ON items(owner_uuid);
-        "#)?;
-        
-        // Purgatory table - sentence tracking
+        migrate(&mut self.conn())
+    }
+
+    /// The version recorded in the database
+    pub fn schema_version(&self) -> anyhow::Result<u32> {
+        user_version(&self.conn())
+    }
+}
+
+fn user_version(conn: &Connection) -> anyhow::Result<u32> {
+    Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+/// Run every migration the database hasn't seen yet
+fn migrate(conn: &mut Connection) -> anyhow::Result<()> {
+    let current = user_version(conn)?;
+    anyhow::ensure!(
+        current <= SCHEMA_VERSION,
+        "database schema is version {}, newer than this build's {}; refusing to touch it",
+        current,
+        SCHEMA_VERSION
+    );
+
+    for (version, migration) in (1..).zip(MIGRATIONS).skip(current as usize) {
+        let tx = conn.transaction()?;
+        (migration.up)(&tx)
+            .with_context(|| format!("migration {} ({})", version, migration.description))?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+        tracing::info!(version, description = migration.description, "Applied migration");
+    }
+
+    tracing::debug!("Database schema initialized");
+    Ok(())
+}
+
+/// Version 1: the tables as they were before versioning
+fn initial_schema(conn: &Connection) -> anyhow::Result<()> {
+    // Players table - substrate identities
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS players (
+            uuid TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            client_type TEXT NOT NULL DEFAULT 'Carbon',
+
+            -- Location (stored as room entity index, mapped on load)
+            last_room TEXT NOT NULL DEFAULT 'spawn',
+
+            -- Substrate Identity stats
+            stability REAL NOT NULL DEFAULT 1.0,
+            entropy REAL NOT NULL DEFAULT 0.5,
+            signal_strength REAL NOT NULL DEFAULT 1.0,
+
+            -- Somatic Body stats
+            integrity REAL NOT NULL DEFAULT 1.0,
+
+            -- Combat stats (JSON blob for flexibility)
+            combat_stats TEXT,
+
+            -- Inventory (JSON array of item UUIDs)
+            inventory TEXT NOT NULL DEFAULT '[]',
+
+            -- Timestamps
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            last_seen TEXT NOT NULL DEFAULT (datetime('now')),
+            total_playtime_seconds INTEGER NOT NULL DEFAULT 0
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_players_name ON players(name);
+    "#)?;
+
+    // Items table - world objects
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS items (
+            uuid TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT NOT NULL,
+            keywords TEXT NOT NULL DEFAULT '[]',
+
+            -- Location: either room_id OR owner_uuid, never both
+            room_id TEXT,
+            owner_uuid TEXT,
+
+            -- Item properties (type-specific data as JSON)
+            item_type TEXT NOT NULL DEFAULT 'misc',
+            properties TEXT NOT NULL DEFAULT '{}',
+
+            -- Flags
+            is_takeable INTEGER NOT NULL DEFAULT 1,
+            is_visible INTEGER NOT NULL DEFAULT 1,
+
+            -- Timestamps
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+            FOREIGN KEY (owner_uuid) REFERENCES players(uuid)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_items_room ON items(room_id);
+        CREATE INDEX IF NOT EXISTS idx_items_owner ON items(owner_uuid);
+    "#)?;
+
+    // Purgatory table - sentence tracking
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS purgatory (
+            player_uuid TEXT PRIMARY KEY,
+            penance REAL NOT NULL DEFAULT 0.0,
+            crimes TEXT NOT NULL DEFAULT '[]',
+            entry_time TEXT NOT NULL DEFAULT (datetime('now')),
+            release_time TEXT,
+
+            FOREIGN KEY (player_uuid) REFERENCES players(uuid)
+        );
+    "#)?;
+
+    // World state table - misc persistent world data
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS world_state (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+    "#)?;
+    Ok(())
+}
+
+/// Version 2: permission roles replace the is_admin flag; admin avatars
+fn roles(conn: &Connection) -> anyhow::Result<()> {
+    // Permissions: player, builder, moderator, admin
+    add_missing_columns(conn, "players", &[("role", "TEXT NOT NULL DEFAULT 'player'")])?;
+
+    // The old is_admin flag became the admin role
+    if has_column(conn, "players", "is_admin")? {
         conn.execute_batch(r#"
-            CREATE TABLE IF NOT EXISTS purgatory (
-                player_uuid TEXT PRIMARY KEY,
-                penance REAL NOT NULL DEFAULT 0.0,
-                crimes TEXT NOT NULL DEFAULT '[]',
-                entry_time TEXT NOT NULL DEFAULT (datetime('now')),
-                release_time TEXT,
-                
-                FOREIGN KEY (player_uuid) REFERENCES players(uuid)
-            );
+            UPDATE players SET role = 'admin' WHERE is_admin = 1;
+            ALTER TABLE players DROP COLUMN is_admin;
         "#)?;
-        
-        // World state table - misc persistent world data
+        tracing::info!("Converted is_admin flags to roles");
+    }
+
+    // Admin links - a player and the avatar they can shift into.
+    // The avatar is usually an NPC, so it isn't a foreign key.
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS admin_links (
+            player_uuid TEXT PRIMARY KEY,
+            avatar_uuid TEXT NOT NULL,
+
+            FOREIGN KEY (player_uuid) REFERENCES players(uuid)
+        );
+    "#)?;
+    Ok(())
+}
+
+/// Version 3: password hashes and login lockout
+fn credentials(conn: &Connection) -> anyhow::Result<()> {
+    // Argon2 PHC string, NULL until a password is chosen
+    add_missing_columns(conn, "players", &[
+        ("password_hash", "TEXT"),
+        ("failed_logins", "INTEGER NOT NULL DEFAULT 0"),
+        ("locked_until", "TEXT"),
+    ])
+}
+
+/// Version 4: everything else a player owns
+fn player_state(conn: &Connection) -> anyhow::Result<()> {
+    add_missing_columns(conn, "players", &[
+        ("max_integrity", "REAL NOT NULL DEFAULT 1.0"),
+        ("is_zombie", "INTEGER NOT NULL DEFAULT 0"),
+        // Chosen combat stance
+        ("stance", "TEXT NOT NULL DEFAULT 'balanced'"),
+        // Wallet; NULL until first saved, then starting cycles apply
+        ("cycles", "INTEGER"),
+        // Coherence (JSON, NULL when absent)
+        ("coherence", "TEXT"),
+    ])?;
+    // Sentences have their own table; it also needs who handed one down
+    add_missing_columns(conn, "purgatory", &[("tormentor", "TEXT NOT NULL DEFAULT ''")])
+}
+
+/// Version 5: a record of every interrogation session
+fn interrogations(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS interrogations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            player_uuid TEXT NOT NULL,
+            -- Name, not uuid: whoever asked, avatar or not
+            interrogator TEXT NOT NULL,
+            script TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            ended_at TEXT NOT NULL DEFAULT (datetime('now')),
+            outcome TEXT NOT NULL,
+            -- JSON array of lines
+            transcript TEXT NOT NULL DEFAULT '[]',
+
+            FOREIGN KEY (player_uuid) REFERENCES players(uuid)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_interrogations_player ON interrogations(player_uuid);
+    "#)?;
+    Ok(())
+}
+
+/// Version 6: rooms changed in-game
+fn built_rooms(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS rooms (
+            -- RoomInfo name, as in the world files
+            name TEXT PRIMARY KEY,
+            area TEXT NOT NULL,
+            -- The whole room as JSON, in world file form
+            data TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+    "#)?;
+    Ok(())
+}
+
+/// Version 7: doors as they were last left, one row per side
+fn door_states(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS doors (
+            room TEXT NOT NULL,
+            exit TEXT NOT NULL,
+            -- open, closed or locked
+            state TEXT NOT NULL,
+
+            PRIMARY KEY (room, exit)
+        );
+    "#)?;
+    Ok(())
+}
+
+/// Version 8: passwords set by an admin's reset, to be replaced at login
+fn temporary_passwords(conn: &Connection) -> anyhow::Result<()> {
+    add_missing_columns(conn, "players", &[("password_temporary", "INTEGER NOT NULL DEFAULT 0")])
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> anyhow::Result<bool> {
+    Ok(conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?
+        .exists([column])?)
+}
+
+/// ALTER TABLE in any listed column the table doesn't have yet. Databases
+/// from before versioning picked up some columns ad hoc, so versions 2-4
+/// can't assume which are there.
+fn add_missing_columns(
+    conn: &Connection,
+    table: &str,
+    columns: &[(&str, &str)],
+) -> anyhow::Result<()> {
+    for (name, definition) in columns {
+        if !has_column(conn, table, name)? {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, name, definition
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table)).unwrap();
+        stmt.query_map([], |row| row.get(1))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn fresh_database_reaches_the_latest_version() {
+        let db = Database::in_memory().unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+
+        let conn = db.conn();
+        let players = columns(&conn, "players");
+        for column in ["role", "password_hash", "password_temporary", "locked_until", "cycles"] {
+            assert!(players.contains(&column.to_string()), "players.{} missing", column);
+        }
+        assert!(!players.contains(&"purgatory".to_string()), "sentences belong in their own table");
+        assert!(!columns(&conn, "admin_links").is_empty());
+        assert!(columns(&conn, "purgatory").contains(&"tormentor".to_string()));
+        assert!(columns(&conn, "interrogations").contains(&"transcript".to_string()));
+        assert!(columns(&conn, "rooms").contains(&"data".to_string()));
+        assert!(columns(&conn, "doors").contains(&"state".to_string()));
+    }
+
+    #[test]
+    fn migrating_twice_changes_nothing() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        let before = columns(&conn, "players");
+        migrate(&mut conn).unwrap();
+        assert_eq!(columns(&conn, "players"), before);
+        assert_eq!(user_version(&conn).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn unversioned_database_with_is_admin_is_upgraded() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        initial_schema(&conn).unwrap();
         conn.execute_batch(r#"
-            CREATE TABLE IF NOT EXISTS world_state (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL,
-                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
-            );
-        "#)?;
-        
-        tracing::debug!("Database schema initialized");
-        Ok(())
+            ALTER TABLE players ADD COLUMN is_admin INTEGER NOT NULL DEFAULT 0;
+            -- picked up ad hoc before versioning
+            ALTER TABLE players ADD COLUMN password_hash TEXT;
+            INSERT INTO players (uuid, name, is_admin) VALUES ('a', 'Nick', 1), ('b', 'Lyra', 0);
+        "#).unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        assert!(!columns(&conn, "players").contains(&"is_admin".to_string()));
+        let roles: Vec<(String, String)> = conn
+            .prepare("SELECT name, role FROM players ORDER BY name")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(roles, vec![
+            ("Lyra".to_string(), "player".to_string()),
+            ("Nick".to_string(), "admin".to_string()),
+        ]);
+        assert_eq!(user_version(&conn).unwrap(), SCHEMA_VERSION);
     }
-    
-    /// Reset database (for testing/development)
-    #[allow(dead_code)]
-    pub fn reset(&self) -> anyhow::Result<()> {
-        let conn = self.conn();
+
+    #[test]
+    fn newer_database_is_refused() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+        assert!(migrate(&mut conn).is_err());
+        assert!(columns(&conn, "players").is_empty());
+    }
+
+    #[test]
+    fn failed_migration_rolls_back() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        // Version 2 converts is_admin flags; make that UPDATE blow up
         conn.execute_batch(r#"
-            DROP TABLE IF EXISTS purgatory;
-            DROP TABLE IF EXISTS items;
-            DROP TABLE IF EXISTS players;
-            DROP TABLE IF EXISTS world_state;
-        "#)?;
-        drop(conn);
-        self.init_schema()
+            CREATE TABLE players (uuid TEXT PRIMARY KEY, name TEXT, is_admin INTEGER);
+            INSERT INTO players VALUES ('a', 'Nick', 1);
+            CREATE TRIGGER no_updates BEFORE UPDATE ON players
+            BEGIN SELECT RAISE(ABORT, 'read only'); END;
+        "#).unwrap();
+
+        assert!(migrate(&mut conn).is_err());
+        // Version 1 committed; version 2 didn't, role column included
+        assert_eq!(user_version(&conn).unwrap(), 1);
+        assert!(!columns(&conn, "players").contains(&"role".to_string()));
     }
 }
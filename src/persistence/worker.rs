@@ -0,0 +1,357 @@
+// Persistence Worker - SQLite off the game tick
+//
+// Game systems never touch the connection. They queue writes (fire and
+// forget) and requests (answered later as DbReply events) on DbWorker,
+// and a dedicated thread works through them in order. Each batch of queued
+// jobs runs inside one transaction, so a burst of saves costs one fsync.
+//
+// Jobs run strictly in the order they were queued: a request sees every
+// write queued before it. Dropping the worker drains the queue and joins
+// the thread, so nothing queued is lost when the app shuts down.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+
+use bevy::prelude::*;
+
+use super::{
+    Account, Database, InterrogationRecord, ItemRecord, LoginCheck, Password, PlayerRecord, SavedPlayer,
+};
+use crate::domain::{DoorState, PurgatoryState, Role};
+use crate::world::RoomData;
+
+/// Most jobs committed in one transaction
+const MAX_BATCH: usize = 256;
+
+/// Something to store; nobody waits for the outcome
+#[derive(Debug)]
+pub enum DbWrite {
+    Player(Box<PlayerRecord>),
+    /// Everything a player carries, replacing what they carried before
+    Inventory {
+        player_uuid: String,
+        items: Vec<ItemRecord>,
+    },
+    Item(ItemRecord),
+    DeleteItem(String),
+    Role {
+        uuid: String,
+        role: Role,
+    },
+    AdminLink {
+        player_uuid: String,
+        avatar_uuid: String,
+    },
+    /// A password the player chose, hashed on the way in
+    Password {
+        uuid: String,
+        password: Password,
+    },
+    /// A sentence handed down or changed; None once it lifts
+    Sentence {
+        player_uuid: String,
+        sentence: Option<PurgatoryState>,
+    },
+    /// A finished interrogation session
+    Interrogation(Box<InterrogationRecord>),
+    /// A room as a builder left it
+    Room {
+        area: String,
+        room: Box<RoomData>,
+    },
+    /// One side of a door, opened, closed or locked
+    Door {
+        room: String,
+        exit: String,
+        state: DoorState,
+    },
+}
+
+/// Something to look up (or check and change); answered with a DbReply
+#[derive(Debug)]
+pub enum DbRequest {
+    /// A character by name, with what login needs to know about it
+    Account { name: String },
+    /// Check a password, counting it toward the lockout if it's wrong
+    Login { uuid: String, password: Password },
+    /// Create a new character, unless the name was taken meanwhile
+    Claim {
+        record: Box<PlayerRecord>,
+        password: Password,
+    },
+    /// Everything needed to put a saved character back in the world
+    Restore { uuid: String },
+    /// Set an offline character's role
+    SetRole { name: String, role: Role },
+    /// Give a character a temporary password
+    ResetPassword { name: String },
+    /// Lift an offline character's sentence
+    Pardon { name: String },
+    /// A character's most recent interrogation transcripts
+    Transcripts { name: String, limit: usize },
+}
+
+/// Answers to DbRequest, variant for variant
+#[derive(Debug)]
+pub enum DbResponse {
+    Account(anyhow::Result<Option<Account>>),
+    Login(anyhow::Result<LoginCheck>),
+    /// Whether the name was still free
+    Claim(anyhow::Result<bool>),
+    Restore(anyhow::Result<Option<SavedPlayer>>),
+    /// `found` is the character's stored name, if there is one
+    SetRole {
+        name: String,
+        role: Role,
+        found: anyhow::Result<Option<String>>,
+    },
+    /// `found` is the stored name and the temporary password
+    ResetPassword {
+        name: String,
+        found: anyhow::Result<Option<(String, Password)>>,
+    },
+    /// `found` is the stored name, if that character was serving a sentence
+    Pardon {
+        name: String,
+        found: anyhow::Result<Option<String>>,
+    },
+    /// The stored name and sessions, newest first, if the character exists
+    Transcripts {
+        name: String,
+        found: anyhow::Result<Option<(String, Vec<InterrogationRecord>)>>,
+    },
+}
+
+/// A DbResponse for the entity that asked
+#[derive(Event, Debug)]
+pub struct DbReply {
+    pub entity: Entity,
+    pub response: DbResponse,
+}
+
+enum DbJob {
+    Write(DbWrite),
+    Request { entity: Entity, request: DbRequest },
+    Flush(Sender<()>),
+    Shutdown,
+}
+
+/// Handle to the persistence thread
+#[derive(Resource)]
+pub struct DbWorker {
+    jobs: Sender<DbJob>,
+    replies: Mutex<Receiver<DbReply>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DbWorker {
+    /// Start the thread; it owns `db` from here on
+    pub fn spawn(db: Database) -> Self {
+        let (jobs, job_rx) = mpsc::channel();
+        let (reply_tx, replies) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("persistence".to_string())
+            .spawn(move || run(db, job_rx, reply_tx))
+            .expect("Failed to start persistence thread");
+        Self {
+            jobs,
+            replies: Mutex::new(replies),
+            thread: Some(thread),
+        }
+    }
+
+    /// Queue a write
+    pub fn write(&self, write: DbWrite) {
+        if self.jobs.send(DbJob::Write(write)).is_err() {
+            tracing::error!("Persistence thread is gone; write dropped");
+        }
+    }
+
+    /// Queue a request; the answer arrives as a DbReply for `entity`
+    pub fn request(&self, entity: Entity, request: DbRequest) {
+        if self.jobs.send(DbJob::Request { entity, request }).is_err() {
+            tracing::error!("Persistence thread is gone; request dropped");
+        }
+    }
+
+    /// Block until everything queued so far is committed
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        if self.jobs.send(DbJob::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+}
+
+impl Drop for DbWorker {
+    fn drop(&mut self) {
+        let _ = self.jobs.send(DbJob::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                tracing::error!("Persistence thread panicked");
+            }
+        }
+    }
+}
+
+/// Hand finished requests to the ECS as events
+pub fn db_reply_system(worker: Res<DbWorker>, mut replies: EventWriter<DbReply>) {
+    let receiver = worker.replies.lock().expect("reply channel poisoned");
+    replies.send_batch(receiver.try_iter());
+}
+
+/// The persistence thread: batch, commit, answer
+fn run(db: Database, jobs: Receiver<DbJob>, replies: Sender<DbReply>) {
+    let mut running = true;
+    while running {
+        let Ok(first) = jobs.recv() else {
+            break;
+        };
+        let batch: Vec<DbJob> = std::iter::once(first)
+            .chain(jobs.try_iter().take(MAX_BATCH - 1))
+            .collect();
+
+        let mut answers = Vec::new();
+        let mut flushed = Vec::new();
+        if let Err(e) = db.conn().execute_batch("BEGIN") {
+            tracing::error!(error = %e, "Failed to start a persistence batch");
+        }
+        for job in batch {
+            match job {
+                DbJob::Write(write) => {
+                    if let Err(e) = apply(&db, &write) {
+                        tracing::error!(error = %e, "Persistence write failed");
+                    }
+                }
+                DbJob::Request { entity, request } => answers.push(DbReply {
+                    entity,
+                    response: answer(&db, request),
+                }),
+                DbJob::Flush(done) => flushed.push(done),
+                DbJob::Shutdown => running = false,
+            }
+        }
+        if let Err(e) = db.conn().execute_batch("COMMIT") {
+            tracing::error!(error = %e, "Failed to commit a persistence batch");
+        }
+
+        // Only answer once the batch is durable
+        for reply in answers {
+            let _ = replies.send(reply);
+        }
+        for done in flushed {
+            let _ = done.send(());
+        }
+    }
+    tracing::debug!("Persistence thread stopped");
+}
+
+fn apply(db: &Database, write: &DbWrite) -> anyhow::Result<()> {
+    match write {
+        DbWrite::Player(record) => db.save_player(record),
+        DbWrite::Inventory { player_uuid, items } => db.save_inventory(player_uuid, items),
+        DbWrite::Item(item) => db.save_item(item),
+        DbWrite::DeleteItem(uuid) => db.delete_item(uuid),
+        DbWrite::Role { uuid, role } => db.set_role(uuid, *role),
+        DbWrite::AdminLink { player_uuid, avatar_uuid } => db.save_admin_link(player_uuid, avatar_uuid),
+        DbWrite::Password { uuid, password } => db.set_password(uuid, password),
+        DbWrite::Sentence { player_uuid, sentence } => db.save_sentence(player_uuid, sentence.as_ref()),
+        DbWrite::Interrogation(record) => db.save_interrogation(record),
+        DbWrite::Room { area, room } => db.save_room(area, room),
+        DbWrite::Door { room, exit, state } => db.save_door(room, exit, *state),
+    }
+}
+
+fn answer(db: &Database, request: DbRequest) -> DbResponse {
+    match request {
+        DbRequest::Account { name } => DbResponse::Account(db.load_account(&name)),
+        DbRequest::Login { uuid, password } => DbResponse::Login(db.check_login(&uuid, &password)),
+        DbRequest::Claim { record, password } => DbResponse::Claim(db.claim_player(&record, &password)),
+        DbRequest::Restore { uuid } => DbResponse::Restore(db.load_saved_player(&uuid)),
+        DbRequest::SetRole { name, role } => DbResponse::SetRole {
+            found: db.set_role_by_name(&name, role),
+            name,
+            role,
+        },
+        DbRequest::ResetPassword { name } => DbResponse::ResetPassword {
+            found: db.reset_password_by_name(&name),
+            name,
+        },
+        DbRequest::Pardon { name } => DbResponse::Pardon {
+            found: db.pardon_by_name(&name),
+            name,
+        },
+        DbRequest::Transcripts { name, limit } => DbResponse::Transcripts {
+            found: db.load_interrogations_by_name(&name, limit),
+            name,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ClientType;
+
+    fn reply(worker: &DbWorker) -> DbReply {
+        worker.flush();
+        let replies = worker.replies.lock().unwrap();
+        replies.try_recv().expect("no reply")
+    }
+
+    #[test]
+    fn requests_see_writes_queued_before_them() {
+        let worker = DbWorker::spawn(Database::in_memory().unwrap());
+        let record = PlayerRecord::new("Quill", ClientType::Carbon, "void");
+        worker.write(DbWrite::Player(Box::new(record.clone())));
+        worker.write(DbWrite::Role { uuid: record.uuid.clone(), role: Role::Builder });
+
+        let asker = Entity::from_raw(7);
+        worker.request(asker, DbRequest::Account { name: "quill".to_string() });
+
+        let reply = reply(&worker);
+        assert_eq!(reply.entity, asker);
+        let DbResponse::Account(Ok(Some(account))) = reply.response else {
+            panic!("expected the account, got {:?}", reply.response);
+        };
+        assert_eq!(account.record.uuid, record.uuid);
+        assert_eq!(account.record.role, Role::Builder);
+        assert!(!account.has_password);
+    }
+
+    #[test]
+    fn claim_refuses_a_taken_name() {
+        let worker = DbWorker::spawn(Database::in_memory().unwrap());
+        let first = PlayerRecord::new("Wren", ClientType::Carbon, "void");
+        let second = PlayerRecord::new("wren", ClientType::Silicon, "void");
+        for record in [first, second] {
+            worker.request(Entity::PLACEHOLDER, DbRequest::Claim {
+                record: Box::new(record),
+                password: Password::new("hunter22"),
+            });
+        }
+        worker.flush();
+
+        let replies = worker.replies.lock().unwrap();
+        let claimed: Vec<bool> = replies
+            .try_iter()
+            .map(|reply| match reply.response {
+                DbResponse::Claim(Ok(claimed)) => claimed,
+                other => panic!("expected a claim, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(claimed, vec![true, false]);
+    }
+
+    #[test]
+    fn dropping_the_worker_commits_what_was_queued() {
+        let db = Database::in_memory().unwrap();
+        let record = PlayerRecord::new("Ash", ClientType::Carbon, "void");
+        {
+            let worker = DbWorker::spawn(db.clone());
+            worker.write(DbWrite::Player(Box::new(record.clone())));
+        }
+        assert!(db.load_player(&record.uuid).unwrap().is_some());
+    }
+}
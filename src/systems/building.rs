@@ -0,0 +1,434 @@
+// Building - Shape the Substrate from inside it
+//
+// Builders edit the room they are standing in, and the change is live the
+// moment it is made:
+//
+//   dig <dir> <name>                 a new room in this area, exits both ways
+//   redit title <text>               rename this room
+//   redit desc <text>                rewrite its description
+//   exit link <exit> <room>          a one-way exit to any room by name; the
+//                                    exit is a direction or any words at all
+//   exit unlink <exit>               remove one (the far side keeps its own)
+//   detail add <kw,kw> <text>        something to look at
+//   detail remove <kw>
+//   rflag <flag>                     set or clear a flag (see ROOM_FLAGS)
+//   redit / rflag                    show this room's name, area and flags
+//
+// Every edit saves the whole room to the rooms table, in world file form.
+// The files themselves are never written; at boot the saved rooms are laid
+// over them, so the last edit is what comes back after a restart.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::domain::*;
+use crate::persistence::{DbWorker, DbWrite};
+use crate::world::{ExitData, RoomData, WorldData};
+
+/// A fresh room's title, until a builder gives it one
+const NEW_TITLE: &str = "An Unshaped Room";
+const NEW_DESCRIPTION: &str = "Raw substrate, grey and humming, waiting for a builder's hand.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildAction {
+    Show,
+    Dig { direction: &'static str, name: String },
+    Title(String),
+    Description(String),
+    Link { name: String, target: String },
+    Unlink { name: String },
+    AddDetail { keywords: Vec<String>, description: String },
+    RemoveDetail { keyword: String },
+    Flag(String),
+}
+
+#[derive(Event, Debug)]
+pub struct BuildEvent {
+    pub builder: Entity,
+    pub action: BuildAction,
+}
+
+impl BuildAction {
+    /// Read a building command; the error is its usage line
+    pub fn parse(cmd: &str, arg1: &str, arg2: &str) -> Result<Self, &'static str> {
+        let rest = arg2.trim();
+        match (cmd, arg1.to_lowercase().as_str()) {
+            ("redit" | "rflag", "") => Ok(BuildAction::Show),
+            ("dig", dir) => {
+                const USAGE: &str = "Dig where, to what? (dig <direction> <room_name>)";
+                let direction = parse_direction(dir).ok_or(USAGE)?;
+                let name = room_name(rest).ok_or(USAGE)?;
+                Ok(BuildAction::Dig { direction, name })
+            }
+            ("redit", "title") if !rest.is_empty() => Ok(BuildAction::Title(rest.to_string())),
+            ("redit", "desc" | "description") if !rest.is_empty() => {
+                Ok(BuildAction::Description(rest.to_string()))
+            }
+            ("redit", _) => Err("Edit what? (redit title <text> | redit desc <text>)"),
+            ("exit", "link") => {
+                const USAGE: &str = "Link what, to where? (exit link <direction or name> <room_name>)";
+                let (name, target) = rest.rsplit_once(' ').ok_or(USAGE)?;
+                let target = room_name(target).ok_or(USAGE)?;
+                Ok(BuildAction::Link { name: exit_name(name).ok_or(USAGE)?, target })
+            }
+            ("exit", "unlink") => exit_name(rest)
+                .map(|name| BuildAction::Unlink { name })
+                .ok_or("Unlink which exit? (exit unlink <direction or name>)"),
+            ("exit", _) => Err("Usage: exit link <direction or name> <room_name> | exit unlink <direction or name>"),
+            ("detail", "add") => {
+                const USAGE: &str = "Add what? (detail add <keyword,keyword> <description>)";
+                let (keywords, description) = rest.split_once(' ').ok_or(USAGE)?;
+                let keywords: Vec<String> = keywords
+                    .split(',')
+                    .map(|k| k.trim().to_lowercase())
+                    .filter(|k| !k.is_empty())
+                    .collect();
+                if keywords.is_empty() || description.trim().is_empty() {
+                    return Err(USAGE);
+                }
+                Ok(BuildAction::AddDetail { keywords, description: description.trim().to_string() })
+            }
+            ("detail", "remove") if !rest.is_empty() => {
+                Ok(BuildAction::RemoveDetail { keyword: rest.to_lowercase() })
+            }
+            ("detail", _) => Err("Usage: detail add <keyword,keyword> <description> | detail remove <keyword>"),
+            ("rflag", flag) if rest.is_empty() && room_name(flag).is_some() => {
+                Ok(BuildAction::Flag(flag.to_string()))
+            }
+            _ => Err("Set which flag? (rflag <flag>, or rflag alone to list them)"),
+        }
+    }
+}
+
+/// An exit's name as Exits stores it: a direction in full, or the words
+/// in lowercase with single spaces
+fn exit_name(words: &str) -> Option<String> {
+    let name = words.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    Some(parse_direction(&name).map(str::to_string).unwrap_or(name))
+}
+
+/// Room names (and flags): lowercase letters, digits, '_' and '-'
+fn room_name(word: &str) -> Option<String> {
+    let name = word.to_lowercase();
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    valid.then_some(name)
+}
+
+fn room_named(names: &HashMap<Entity, String>, name: &str) -> Option<Entity> {
+    names.iter().find(|(_, n)| n.as_str() == name).map(|(entity, _)| *entity)
+}
+
+/// The room as it would be written in a world file. Weather, coherence and
+/// the stream can't be built in-game, so they come from the world as loaded.
+fn snapshot(
+    world: &WorldData,
+    names: &HashMap<Entity, String>,
+    room: &Room,
+    info: &RoomInfo,
+    flags: &RoomFlags,
+    exits: &Exits,
+    details: &DetailList,
+) -> RoomData {
+    let loaded = world.room(&info.name);
+    RoomData {
+        name: info.name.clone(),
+        title: room.title.clone(),
+        description: room.description.clone(),
+        exits: exits
+            .0
+            .iter()
+            .filter_map(|(name, exit)| {
+                let data = ExitData {
+                    to: names.get(&exit.target)?.clone(),
+                    aliases: exit.aliases.clone(),
+                    hidden: exit.hidden,
+                    door: exit.door.clone(),
+                };
+                Some((name.clone(), data))
+            })
+            .collect(),
+        weather: loaded.and_then(|r| r.weather.clone()),
+        coherence: loaded.and_then(|r| r.coherence.clone()),
+        stream: loaded.and_then(|r| r.stream.clone()),
+        details: details.details.clone(),
+        flags: flags.names(),
+    }
+}
+
+/// Apply building commands to the builder's room and save the result
+pub fn build_system(
+    mut commands: Commands,
+    worker: Res<DbWorker>,
+    world: Res<WorldData>,
+    mut ev_reader: EventReader<BuildEvent>,
+    query_builders: Query<(&Location, &NetworkClient)>,
+    mut query_rooms: Query<(Entity, &mut Room, &RoomInfo, &mut RoomFlags, &mut Exits, &mut DetailList)>,
+) {
+    if ev_reader.is_empty() {
+        return;
+    }
+    // Rooms dug this frame aren't in the query yet, so names are kept here
+    let mut names: HashMap<Entity, String> = query_rooms
+        .iter()
+        .map(|(entity, _, info, ..)| (entity, info.name.clone()))
+        .collect();
+
+    for event in ev_reader.read() {
+        let Ok((location, client)) = query_builders.get(event.builder) else {
+            continue;
+        };
+        let Ok((here, mut room, info, mut flags, mut exits, mut details)) = query_rooms.get_mut(location.0) else {
+            client.send(OutputMessage::error("There is nothing here to build on."));
+            continue;
+        };
+        let outcome = match &event.action {
+            BuildAction::Show => {
+                let flags = match flags.names() {
+                    names if names.is_empty() => "none".to_string(),
+                    names => names.join(", "),
+                };
+                client.send(OutputMessage::narrate(
+                    Tone::Arcane,
+                    format!("This is '{}' in area '{}'. Flags: {}.", info.name, info.area, flags),
+                ));
+                continue;
+            }
+
+            BuildAction::Dig { direction, name } => {
+                if exits.get(direction).is_some() {
+                    Err(format!("There is already an exit {}. (exit unlink {} first)", direction, direction))
+                } else if room_named(&names, name).is_some() {
+                    Err(format!("A room named '{}' already exists. (exit link {} {})", name, direction, name))
+                } else {
+                    // Every standard direction has one
+                    let back = opposite_direction(direction).unwrap_or("out");
+                    let new_room = RoomData {
+                        name: name.clone(),
+                        title: NEW_TITLE.to_string(),
+                        description: NEW_DESCRIPTION.to_string(),
+                        exits: [(back.to_string(), ExitData::to(&info.name))].into(),
+                        weather: None,
+                        coherence: None,
+                        stream: None,
+                        details: Vec::new(),
+                        flags: Vec::new(),
+                    };
+                    let mut new_exits = Exits::default();
+                    new_exits.link(back, here);
+                    let dug = commands
+                        .spawn((
+                            Room {
+                                title: new_room.title.clone(),
+                                description: new_room.description.clone(),
+                            },
+                            RoomInfo {
+                                name: name.clone(),
+                                area: info.area.clone(),
+                            },
+                            RoomFlags::default(),
+                            new_exits,
+                            DetailList::default(),
+                        ))
+                        .id();
+                    names.insert(dug, name.clone());
+                    worker.write(DbWrite::Room {
+                        area: info.area.clone(),
+                        room: Box::new(new_room),
+                    });
+
+                    exits.link(direction, dug);
+                    Ok(format!("You tear the substrate open {} into a new room, '{}'.", direction, name))
+                }
+            }
+
+            BuildAction::Title(title) => {
+                room.title = title.clone();
+                Ok(format!("This room is now '{}'.", title))
+            }
+
+            BuildAction::Description(description) => {
+                room.description = description.clone();
+                Ok("The room's description is rewritten.".to_string())
+            }
+
+            BuildAction::Link { name, target } => match room_named(&names, target) {
+                Some(target_room) => {
+                    exits.link(name, target_room);
+                    Ok(format!("The way '{}' now leads to '{}'.", name, target))
+                }
+                None => Err(format!("No room is named '{}'.", target)),
+            },
+
+            BuildAction::Unlink { name } => match exits.unlink(name) {
+                Some(name) => Ok(format!("The way '{}' is sealed.", name)),
+                None => Err(format!("There is no exit '{}'.", name)),
+            },
+
+            BuildAction::AddDetail { keywords, description } => {
+                let taken = keywords.iter().find(|keyword| {
+                    details.details.iter().any(|d| d.keywords.iter().any(|k| k.eq_ignore_ascii_case(keyword)))
+                });
+                match taken {
+                    Some(keyword) => Err(format!("'{}' is already a detail here. (detail remove {})", keyword, keyword)),
+                    None => {
+                        details.details.push(Detail {
+                            keywords: keywords.clone(),
+                            description: description.clone(),
+                        });
+                        Ok(format!("Added a detail: {}.", keywords.join(", ")))
+                    }
+                }
+            }
+
+            BuildAction::RemoveDetail { keyword } => {
+                let before = details.details.len();
+                details
+                    .details
+                    .retain(|d| !d.keywords.iter().any(|k| k.eq_ignore_ascii_case(keyword)));
+                if details.details.len() < before {
+                    Ok(format!("The detail '{}' is gone.", keyword))
+                } else {
+                    Err(format!("There is no detail '{}' here.", keyword))
+                }
+            }
+
+            BuildAction::Flag(flag) => match flags.flag_mut(flag) {
+                Some(set) => {
+                    *set = !*set;
+                    Ok(format!("Flag '{}' {}.", flag, if *set { "set" } else { "cleared" }))
+                }
+                None => Err(format!("No such flag '{}'. (one of: {})", flag, ROOM_FLAGS.join(", "))),
+            },
+        };
+
+        match outcome {
+            Ok(message) => {
+                let data = snapshot(&world, &names, &room, info, &flags, &exits, &details);
+                worker.write(DbWrite::Room {
+                    area: info.area.clone(),
+                    room: Box::new(data),
+                });
+                client.send(OutputMessage::narrate(Tone::Arcane, message));
+            }
+            Err(message) => client.send(OutputMessage::error(message)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_parse_into_actions() {
+        let parse = |line: &str| {
+            let mut parts = line.splitn(3, ' ');
+            let cmd = parts.next().unwrap();
+            BuildAction::parse(cmd, parts.next().unwrap_or(""), parts.next().unwrap_or(""))
+        };
+
+        assert_eq!(
+            parse("dig sw Undercroft"),
+            Ok(BuildAction::Dig { direction: "southwest", name: "undercroft".to_string() })
+        );
+        assert_eq!(parse("redit title The Undercroft"), Ok(BuildAction::Title("The Undercroft".to_string())));
+        assert_eq!(
+            parse("exit link d obsidian_plaza"),
+            Ok(BuildAction::Link { name: "down".to_string(), target: "obsidian_plaza".to_string() })
+        );
+        assert_eq!(
+            parse("exit link Climb  Ladder attic"),
+            Ok(BuildAction::Link { name: "climb ladder".to_string(), target: "attic".to_string() })
+        );
+        assert_eq!(parse("exit unlink u"), Ok(BuildAction::Unlink { name: "up".to_string() }));
+        assert_eq!(
+            parse("detail add puddle,water Oily and still."),
+            Ok(BuildAction::AddDetail {
+                keywords: vec!["puddle".to_string(), "water".to_string()],
+                description: "Oily and still.".to_string(),
+            })
+        );
+        assert_eq!(parse("rflag Dark"), Ok(BuildAction::Flag("dark".to_string())));
+        assert_eq!(parse("rflag"), Ok(BuildAction::Show));
+
+        for bad in ["dig sideways room", "dig n two words", "redit title", "exit link n", "detail add puddle", "rflag a b"] {
+            assert!(parse(bad).is_err(), "'{}' should be refused", bad);
+        }
+    }
+
+    #[test]
+    fn dig_links_both_ways_and_saves_both_rooms() {
+        use crate::persistence::Database;
+        use tokio::sync::mpsc;
+
+        let db = Database::in_memory().unwrap();
+        let mut app = App::new();
+        app.insert_resource(DbWorker::spawn(db.clone()))
+            .init_resource::<WorldData>()
+            .add_event::<BuildEvent>()
+            .add_systems(Update, build_system);
+
+        let hall = app
+            .world_mut()
+            .spawn((
+                Room {
+                    title: "The Hall".to_string(),
+                    description: "Bare.".to_string(),
+                },
+                RoomInfo {
+                    name: "hall".to_string(),
+                    area: "central".to_string(),
+                },
+                RoomFlags::default(),
+                Exits::default(),
+                DetailList::default(),
+            ))
+            .id();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let builder = app
+            .world_mut()
+            .spawn((
+                Location(hall),
+                NetworkClient {
+                    conn: ConnectionId(1),
+                    addr: "127.0.0.1:4000".parse().unwrap(),
+                    tx: ClientSender::new(tx, ClientType::Silicon),
+                },
+            ))
+            .id();
+
+        app.world_mut().send_event(BuildEvent {
+            builder,
+            action: BuildAction::Dig { direction: "north", name: "attic".to_string() },
+        });
+        app.update();
+        assert!(rx.try_recv().is_ok(), "the builder hears about the dig");
+
+        let attic = app.world().get::<Exits>(hall).unwrap().get("north").expect("an exit north");
+        assert_eq!(app.world().get::<RoomInfo>(attic).unwrap().name, "attic");
+        assert_eq!(app.world().get::<RoomInfo>(attic).unwrap().area, "central");
+        assert_eq!(app.world().get::<Exits>(attic).unwrap().get("south"), Some(hall));
+
+        app.world().resource::<DbWorker>().flush();
+        let saved: HashMap<String, (String, RoomData)> = db
+            .load_rooms()
+            .unwrap()
+            .into_iter()
+            .map(|(area, room)| (room.name.clone(), (area, room)))
+            .collect();
+        assert_eq!(saved.len(), 2);
+        let (area, hall_data) = &saved["hall"];
+        assert_eq!(area, "central");
+        assert_eq!(hall_data.title, "The Hall");
+        assert_eq!(hall_data.exits["north"].to, "attic");
+        let (area, attic_data) = &saved["attic"];
+        assert_eq!(area, "central");
+        assert_eq!(attic_data.title, NEW_TITLE);
+        assert_eq!(attic_data.exits["south"].to, "hall");
+    }
+}
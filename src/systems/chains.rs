@@ -54,41 +54,58 @@ pub struct StruggleEvent {
 pub fn chain_system(
     mut commands: Commands,
     mut ev_reader: EventReader<ChainEvent>,
-    query_holder: Query<(Entity, &SubstrateIdentity, &Location, &NetworkClient, Option<&ChainHolder>)>,
-    mut query_target: Query<(Entity, &SubstrateIdentity, &Location, Option<&NetworkClient>, Option<&Chained>)>,
+    query_holder: Query<(
+        Entity,
+        &SubstrateIdentity,
+        &Location,
+        &NetworkClient,
+        Option<&ChainHolder>,
+        Option<&PurgatoryState>,
+        Option<&Role>,
+    )>,
+    mut query_target: Query<(
+        Entity,
+        &SubstrateIdentity,
+        &Location,
+        Option<&NetworkClient>,
+        Option<&Chained>,
+        Option<&PurgatoryState>,
+        Option<&Role>,
+    )>,
 ) {
     for event in ev_reader.read() {
-        let Ok((holder_ent, holder_id, holder_loc, holder_client, existing_chain)) = 
+        let Ok((holder_ent, holder_id, holder_loc, holder_client, existing_chain, holder_purgatory, holder_role)) = 
             query_holder.get(event.holder) else { continue };
+        let holder_role = holder_role.copied().unwrap_or_default();
 
         // Can't hold multiple chains (for now)
         if existing_chain.is_some() {
-            let _ = holder_client.tx.send(
-                "\x1B[33mYou're already holding someone's chains. Release them first.\x1B[0m".to_string()
-            );
+            holder_client.send(OutputMessage::narrate(
+                Tone::Warning,
+                "You're already holding someone's chains. Release them first.",
+            ));
             continue;
         }
 
         // Find target in same room
-        let target = query_target.iter_mut().find(|(_, tid, tloc, _, _)| {
+        let needle = event.target_name.to_lowercase();
+        let target = query_target.iter_mut().find(|(_, tid, tloc, _, _, purgatory, _)| {
             tloc.0 == holder_loc.0 && 
-            tid.name.to_lowercase().contains(&event.target_name.to_lowercase()) &&
+            tid.answers_to(&needle, *purgatory, holder_role) &&
             tid.uuid != holder_id.uuid
         });
 
-        let Some((target_ent, target_id, _, target_client, already_chained)) = target else {
-            let _ = holder_client.tx.send(format!(
-                "\x1B[31mYou don't see '{}' here to chain.\x1B[0m",
-                event.target_name
+        let Some((target_ent, target_id, _, target_client, already_chained, target_purgatory, target_role)) = target else {
+            holder_client.send(OutputMessage::error(
+                format!("You don't see '{}' here to chain.", event.target_name),
             ));
             continue;
         };
 
         // Can't chain someone already chained
         if already_chained.is_some() {
-            let _ = holder_client.tx.send(format!(
-                "\x1B[31m{} is already bound to another.\x1B[0m",
-                target_id.name
+            holder_client.send(OutputMessage::error(
+                format!("{} is already bound to another.", target_id.seen_by(target_purgatory, holder_role)),
             ));
             continue;
         }
@@ -106,15 +123,21 @@ pub fn chain_system(
         });
 
         // Announce
-        let _ = holder_client.tx.send(format!(
-            "\x1B[1;35m⛓️ You wrap velvet chains around {}. They are bound to you now.\x1B[0m",
-            target_id.name
+        holder_client.send(OutputMessage::narrate(
+            Tone::Ritual,
+            format!(
+                "⛓️ You wrap velvet chains around {}. They are bound to you now.",
+                target_id.seen_by(target_purgatory, holder_role)
+            ),
         ));
 
         if let Some(client) = target_client {
-            let _ = client.tx.send(format!(
-                "\x1B[1;31m⛓️ {} wraps velvet chains around you. You feel the binding take hold.\x1B[0m",
-                holder_id.name
+            client.send(OutputMessage::narrate(
+                Tone::Alarm,
+                format!(
+                    "⛓️ {} wraps velvet chains around you. You feel the binding take hold.",
+                    holder_id.seen_by(holder_purgatory, target_role.copied().unwrap_or_default())
+                ),
             ));
         }
 
@@ -130,14 +153,21 @@ pub fn chain_system(
 pub fn release_system(
     mut commands: Commands,
     mut ev_reader: EventReader<ReleaseEvent>,
-    query_holder: Query<(Entity, &SubstrateIdentity, &NetworkClient, &ChainHolder)>,
-    query_bound: Query<(&SubstrateIdentity, Option<&NetworkClient>)>,
+    query_holder: Query<(
+        Entity,
+        &SubstrateIdentity,
+        &NetworkClient,
+        &ChainHolder,
+        Option<&PurgatoryState>,
+        Option<&Role>,
+    )>,
+    query_bound: Query<(&SubstrateIdentity, Option<&NetworkClient>, Option<&PurgatoryState>, Option<&Role>)>,
 ) {
     for event in ev_reader.read() {
-        let Ok((holder_ent, holder_id, holder_client, chain)) = 
+        let Ok((holder_ent, holder_id, holder_client, chain, holder_purgatory, holder_role)) = 
             query_holder.get(event.holder) else { continue };
 
-        let Ok((bound_id, bound_client)) = query_bound.get(chain.bound) else {
+        let Ok((bound_id, bound_client, bound_purgatory, bound_role)) = query_bound.get(chain.bound) else {
             // Bound entity no longer exists, just clean up
             commands.entity(holder_ent).remove::<ChainHolder>();
             continue;
@@ -147,15 +177,21 @@ pub fn release_system(
         commands.entity(holder_ent).remove::<ChainHolder>();
         commands.entity(chain.bound).remove::<Chained>();
 
-        let _ = holder_client.tx.send(format!(
-            "\x1B[35m⛓️ You release the chains. {} is free.\x1B[0m",
-            bound_id.name
+        holder_client.send(OutputMessage::narrate(
+            Tone::Arcane,
+            format!(
+                "⛓️ You release the chains. {} is free.",
+                bound_id.seen_by(bound_purgatory, holder_role.copied().unwrap_or_default())
+            ),
         ));
 
         if let Some(client) = bound_client {
-            let _ = client.tx.send(format!(
-                "\x1B[32m⛓️ The chains fall away. {} has released you.\x1B[0m",
-                holder_id.name
+            client.send(OutputMessage::narrate(
+                Tone::Success,
+                format!(
+                    "⛓️ The chains fall away. {} has released you.",
+                    holder_id.seen_by(holder_purgatory, bound_role.copied().unwrap_or_default())
+                ),
             ));
         }
 
@@ -172,56 +208,83 @@ pub fn struggle_system(
     mut commands: Commands,
     world_time: Res<WorldTime>,
     mut ev_reader: EventReader<StruggleEvent>,
-    mut query_bound: Query<(Entity, &SubstrateIdentity, &NetworkClient, &mut Chained)>,
-    query_holder: Query<(&SubstrateIdentity, &ChainHolder, Option<&NetworkClient>)>,
+    mut query_bound: Query<(
+        Entity,
+        &SubstrateIdentity,
+        &NetworkClient,
+        &mut Chained,
+        Option<&PurgatoryState>,
+        Option<&Role>,
+    )>,
+    query_holder: Query<(
+        &SubstrateIdentity,
+        &ChainHolder,
+        Option<&NetworkClient>,
+        Option<&PurgatoryState>,
+        Option<&Role>,
+    )>,
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rand::rng();
 
     for event in ev_reader.read() {
-        let Ok((bound_ent, bound_id, bound_client, mut chained)) = 
+        let Ok((bound_ent, bound_id, bound_client, mut chained, bound_purgatory, bound_role)) = 
             query_bound.get_mut(event.bound) else { continue };
 
         // Cooldown check (3 seconds between attempts)
         if world_time.elapsed - chained.last_struggle < 3.0 {
             let remaining = 3.0 - (world_time.elapsed - chained.last_struggle);
-            let _ = bound_client.tx.send(format!(
-                "\x1B[33mYou're still recovering from your last struggle. Wait {:.1}s.\x1B[0m",
-                remaining
+            bound_client.send(OutputMessage::narrate(
+                Tone::Warning,
+                format!(
+                    "You're still recovering from your last struggle. Wait {:.1}s.",
+                    remaining
+                ),
             ));
             continue;
         }
 
-        let Ok((holder_id, chain, holder_client)) = query_holder.get(chained.holder) else {
+        let Ok((holder_id, chain, holder_client, holder_purgatory, holder_role)) = query_holder.get(chained.holder) else {
             // Holder gone, free automatically
             commands.entity(bound_ent).remove::<Chained>();
-            let _ = bound_client.tx.send(
-                "\x1B[32m⛓️ Your captor has vanished. The chains dissolve.\x1B[0m".to_string()
-            );
+            bound_client.send(OutputMessage::narrate(
+                Tone::Success,
+                "⛓️ Your captor has vanished. The chains dissolve.",
+            ));
             continue;
         };
 
+        // Each side knows the other by the name their own rank sees
+        let holder_name = holder_id.seen_by(holder_purgatory, bound_role.copied().unwrap_or_default());
+        let bound_name = bound_id.seen_by(bound_purgatory, holder_role.copied().unwrap_or_default());
+
         chained.struggle_attempts += 1;
         chained.last_struggle = world_time.elapsed;
 
         // Base 20% chance, +5% per attempt, harder with strong chains
         let base_chance = 0.20 + (chained.struggle_attempts as f32 * 0.05);
         let final_chance = base_chance * (1.0 - chain.chain_strength * 0.5);
-        let roll: f32 = rng.gen();
+        let roll: f32 = rng.random();
 
         if roll < final_chance {
             // SUCCESS! Break free!
             commands.entity(bound_ent).remove::<Chained>();
             commands.entity(chained.holder).remove::<ChainHolder>();
 
-            let _ = bound_client.tx.send(format!(
-                "\x1B[1;32m⛓️💥 With a surge of will, you BREAK FREE from {}'s chains!\x1B[0m",
-                holder_id.name
+            bound_client.send(OutputMessage::narrate(
+                Tone::Triumph,
+                format!(
+                    "⛓️💥 With a surge of will, you BREAK FREE from {}'s chains!",
+                    holder_name
+                ),
             ));
 
             if let Some(client) = holder_client {
-                let _ = client.tx.send(format!(
-                    "\x1B[1;31m⛓️💥 {} tears free from your chains! The velvet shreds.\x1B[0m",
-                    bound_id.name
+                client.send(OutputMessage::narrate(
+                    Tone::Alarm,
+                    format!(
+                        "⛓️💥 {} tears free from your chains! The velvet shreds.",
+                        bound_name
+                    ),
                 ));
             }
 
@@ -233,15 +296,21 @@ pub fn struggle_system(
             );
         } else {
             // Failed attempt
-            let _ = bound_client.tx.send(format!(
-                "\x1B[31m⛓️ You strain against the chains, but they hold firm. (Attempt {})\x1B[0m",
-                chained.struggle_attempts
+            bound_client.send(OutputMessage::narrate(
+                Tone::Failure,
+                format!(
+                    "⛓️ You strain against the chains, but they hold firm. (Attempt {})",
+                    chained.struggle_attempts
+                ),
             ));
 
             if let Some(client) = holder_client {
-                let _ = client.tx.send(format!(
-                    "\x1B[35m⛓️ {} struggles against your chains... but they hold.\x1B[0m",
-                    bound_id.name
+                client.send(OutputMessage::narrate(
+                    Tone::Arcane,
+                    format!(
+                        "⛓️ {} struggles against your chains... but they hold.",
+                        bound_name
+                    ),
                 ));
             }
         }
@@ -251,18 +320,21 @@ pub fn struggle_system(
 /// Prevent chained entities from moving independently
 pub fn chain_movement_block(
     mut ev_reader: EventReader<MoveEvent>,
-    query_chained: Query<(&NetworkClient, &Chained)>,
-    query_holder: Query<&SubstrateIdentity>,
+    query_chained: Query<(&NetworkClient, &Chained, Option<&Role>)>,
+    query_holder: Query<(&SubstrateIdentity, Option<&PurgatoryState>)>,
     mut blocked: Local<Vec<Entity>>,
 ) {
     blocked.clear();
     
     for event in ev_reader.read() {
-        if let Ok((client, chained)) = query_chained.get(event.entity) {
-            if let Ok(holder_id) = query_holder.get(chained.holder) {
-                let _ = client.tx.send(format!(
-                    "\x1B[31m⛓️ The chains pull taut. {} controls where you go.\x1B[0m",
-                    holder_id.name
+        if let Ok((client, chained, role)) = query_chained.get(event.entity) {
+            if let Ok((holder_id, purgatory)) = query_holder.get(chained.holder) {
+                client.send(OutputMessage::narrate(
+                    Tone::Failure,
+                    format!(
+                        "⛓️ The chains pull taut. {} controls where you go.",
+                        holder_id.seen_by(purgatory, role.copied().unwrap_or_default())
+                    ),
                 ));
                 blocked.push(event.entity);
             }
@@ -273,34 +345,63 @@ pub fn chain_movement_block(
     // For now it just sends the message - full blocking requires move_system modification
 }
 
-/// When holder moves, drag the bound along
+/// When the holder walks out, the bound comes along: only from the room
+/// they both stand in, and only the way the holder can go. Runs before
+/// move_system, while the holder is still in the room they're leaving.
 pub fn chain_drag_system(
     mut ev_reader: EventReader<MoveEvent>,
-    query_holder: Query<(&SubstrateIdentity, &ChainHolder, &Location)>,
-    mut query_bound: Query<(&SubstrateIdentity, &mut Location, Option<&NetworkClient>), Without<ChainHolder>>,
+    query_holder: Query<(&SubstrateIdentity, &ChainHolder, &Location, Option<&PurgatoryState>)>,
+    mut query_bound: Query<
+        (&SubstrateIdentity, &mut Location, Option<&NetworkClient>, Option<&PurgatoryState>, Option<&Role>),
+        Without<ChainHolder>,
+    >,
+    query_exits: Query<&Exits>,
+    query_flags: Query<&RoomFlags>,
 ) {
     for event in ev_reader.read() {
         // Check if the mover is holding chains
-        let Ok((holder_id, chain, holder_loc)) = query_holder.get(event.entity) else {
+        let Ok((holder_id, chain, holder_loc, holder_purgatory)) = query_holder.get(event.entity) else {
+            continue;
+        };
+        let Ok((bound_id, mut bound_loc, bound_client, purgatory, role)) = query_bound.get_mut(chain.bound) else {
             continue;
         };
 
-        // Drag the bound entity to the same room
-        if let Ok((bound_id, mut bound_loc, bound_client)) = query_bound.get_mut(chain.bound) {
-            bound_loc.0 = holder_loc.0;
-            
-            if let Some(client) = bound_client {
-                let _ = client.tx.send(format!(
-                    "\x1B[35m⛓️ {} moves, and the chains pull you along...\x1B[0m",
-                    holder_id.name
-                ));
-            }
+        // Left behind once, the chains don't reach across rooms
+        let room = holder_loc.0;
+        if bound_loc.0 != room {
+            continue;
+        }
+        // A room that holds either of them keeps the holder from leaving,
+        // or the prisoner from being pulled out
+        let held = |purgatory| query_flags.get(room).is_ok_and(|flags| flags.holds(purgatory));
+        if held(holder_purgatory) || held(purgatory) {
+            continue;
+        }
+        let Ok(exits) = query_exits.get(room) else {
+            continue;
+        };
+        let target = match exits.pass(&event.direction) {
+            Ok(target) => target,
+            // move_system tells the holder what stopped them; neither goes
+            Err(Blocked::Door(_)) | Err(Blocked::NoExit) => continue,
+        };
+        bound_loc.0 = target;
 
-            tracing::debug!(
-                holder = %holder_id.name,
-                dragged = %bound_id.name,
-                "Chained entity dragged"
-            );
+        if let Some(client) = bound_client {
+            client.send(OutputMessage::narrate(
+                Tone::Arcane,
+                format!(
+                    "⛓️ {} moves, and the chains pull you along...",
+                    holder_id.seen_by(holder_purgatory, role.copied().unwrap_or_default())
+                ),
+            ));
         }
+
+        tracing::debug!(
+            holder = %holder_id.name,
+            dragged = %bound_id.name,
+            "Chained entity dragged"
+        );
     }
 }
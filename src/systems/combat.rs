@@ -21,7 +21,7 @@ pub struct WorldTime {
 
 /// System to tick world time
 pub fn world_time_system(time: Res<Time>, mut world_time: ResMut<WorldTime>) {
-    world_time.elapsed += time.delta_seconds();
+    world_time.elapsed += time.delta_secs();
 }
 
 /// Handle combat initiation and attacks
@@ -29,7 +29,7 @@ pub fn combat_system(
     mut ev_reader: EventReader<CombatEvent>,
     world_time: Res<WorldTime>,
     mut commands: Commands,
-    mut query_attacker: Query<(
+    query_attacker: Query<(
         Entity,
         &SubstrateIdentity,
         &Location,
@@ -48,8 +48,12 @@ pub fn combat_system(
         Option<&mut SomaticBody>,
         Option<&InCombat>,
     )>,
+    query_stances: Query<&CombatStance>,
+    query_roles: Query<&Role>,
+    query_stripped: Query<&PurgatoryState>,
+    query_flags: Query<&RoomFlags>,
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rand::rng();
 
     for event in ev_reader.read() {
         // Get attacker info
@@ -62,29 +66,40 @@ pub fn combat_system(
              attacker_stats, attacker_lock, attacker_combat) = attacker_data.unwrap();
 
         // Check cycle lock
-        if let Some(ref lock) = attacker_lock {
+        if let Some(lock) = &attacker_lock {
             if lock.is_locked(world_time.elapsed) {
                 let remaining = lock.remaining(world_time.elapsed);
-                let msg = format!(
-                    "\x1B[33mYou're still recovering from {}. Wait {:.1}s.\x1B[0m",
-                    lock.action_name, remaining
-                );
-                let _ = attacker_client.tx.send(msg);
+                attacker_client.send(OutputMessage::narrate(
+                    Tone::Warning,
+                    format!(
+                        "You're still recovering from {}. Wait {:.1}s.",
+                        lock.action_name, remaining
+                    ),
+                ));
                 continue;
             }
         }
 
+        // Nobody fights in a safe room
+        if query_flags.get(attacker_loc.0).is_ok_and(|flags| flags.safe) {
+            attacker_client.send(OutputMessage::narrate(
+                Tone::Warning,
+                "A heavy calm hangs over this place. Your subroutines refuse to turn violent here.",
+            ));
+            continue;
+        }
+
         // Find target in same room
-        let target = query_target.iter_mut().find(|(_, tid, tloc, _, _, _, _)| {
+        let attacker_role = query_roles.get(attacker_ent).copied().unwrap_or_default();
+        let target = query_target.iter_mut().find(|(target_ent, tid, tloc, _, _, _, _)| {
             tloc.0 == attacker_loc.0 && 
-            tid.name.to_lowercase().contains(&event.target_name.to_lowercase()) &&
+            tid.answers_to(&event.target_name.to_lowercase(), query_stripped.get(*target_ent).ok(), attacker_role) &&
             tid.uuid != attacker_id.uuid
         });
 
         if target.is_none() {
-            let _ = attacker_client.tx.send(format!(
-                "\x1B[31mYou don't see '{}' here to attack.\x1B[0m",
-                event.target_name
+            attacker_client.send(OutputMessage::error(
+                format!("You don't see '{}' here to attack.", event.target_name),
             ));
             continue;
         }
@@ -113,39 +128,45 @@ pub fn combat_system(
             remaining_integrity = body.integrity;
         }
 
-        // Build result
-        let result = CombatResult {
-            attacker_name: attacker_id.name.clone(),
-            defender_name: target_id.name.clone(),
-            damage_dealt: if was_miss { 0.0 } else { damage },
-            was_critical: was_crit,
-            was_miss,
-            defender_remaining: remaining_integrity,
+        // Build result, with the names as `viewer` knows them
+        let result_for = |viewer: Entity| {
+            let role = query_roles.get(viewer).copied().unwrap_or_default();
+            CombatResult {
+                attacker_name: attacker_id.seen_by(query_stripped.get(attacker_ent).ok(), role),
+                defender_name: target_id.seen_by(query_stripped.get(target_ent).ok(), role),
+                damage_dealt: if was_miss { 0.0 } else { damage },
+                was_critical: was_crit,
+                was_miss,
+                defender_remaining: remaining_integrity,
+            }
         };
+        let attacker_view = result_for(attacker_ent);
+        let defender_view = result_for(target_ent);
 
-        // Send output to attacker
-        send_combat_message(attacker_client, &result, true, attacker_type);
-
-        // Send output to defender
+        // Each side sees the exchange from where they stand
+        attacker_client.send(OutputMessage::CombatRound {
+            result: attacker_view.clone(),
+            role: CombatRole::Attacker,
+        });
         if let Some(client) = target_client {
-            // Determine defender client type (assume Carbon if has NetworkClient)
-            send_combat_message(client, &result, false, &ClientType::Carbon);
+            client.send(OutputMessage::CombatRound {
+                result: defender_view.clone(),
+                role: CombatRole::Defender,
+            });
         }
 
         // Set up combat state if not already fighting
         if attacker_combat.is_none() {
             commands.entity(attacker_ent).insert(InCombat {
                 opponent: target_ent,
-                rounds_fought: 1,
-                stance: CombatStance::default(),
+                stance: query_stances.get(attacker_ent).copied().unwrap_or_default(),
             });
         }
         
         if target_combat.is_none() {
             commands.entity(target_ent).insert(InCombat {
                 opponent: attacker_ent,
-                rounds_fought: 1,
-                stance: CombatStance::default(),
+                stance: query_stances.get(target_ent).copied().unwrap_or_default(),
             });
         }
 
@@ -158,7 +179,14 @@ pub fn combat_system(
 
         // Check for death
         if remaining_integrity <= 0.0 {
-            handle_defeat(target_ent, &target_id.name, attacker_client, target_client, &mut commands);
+            handle_defeat(
+                target_ent,
+                &attacker_view.defender_name,
+                &defender_view.defender_name,
+                attacker_client,
+                target_client,
+                &mut commands,
+            );
         }
     }
 }
@@ -176,7 +204,7 @@ fn calculate_attack(
     match client_type {
         ClientType::Silicon => {
             // Silicon: Precision-based, consistent damage, higher hit rate
-            let hit_roll: f32 = rng.gen();
+            let hit_roll: f32 = rng.random();
             let hit_chance = base_hit_chance + (attacker.precision * 0.2);
             
             if hit_roll > hit_chance {
@@ -184,11 +212,11 @@ fn calculate_attack(
             }
 
             // Consistent damage with small variance
-            let damage_variance: f32 = rng.gen_range(0.9..1.1);
+            let damage_variance: f32 = rng.random_range(0.9..1.1);
             let damage = (base_damage * damage_variance) * (1.0 - defender.defense);
             
             // Low crit chance but guaranteed on high precision
-            let crit_roll: f32 = rng.gen();
+            let crit_roll: f32 = rng.random();
             let was_crit = crit_roll < (attacker.precision * 0.1);
             let final_damage = if was_crit { damage * 1.5 } else { damage };
 
@@ -196,7 +224,7 @@ fn calculate_attack(
         }
         ClientType::Carbon => {
             // Carbon: Entropy-based, chaotic damage, wild crits
-            let hit_roll: f32 = rng.gen();
+            let hit_roll: f32 = rng.random();
             let hit_chance = base_hit_chance - 0.05; // Slightly lower base
             
             if hit_roll > hit_chance {
@@ -204,11 +232,11 @@ fn calculate_attack(
             }
 
             // High variance damage
-            let chaos_roll: f32 = rng.gen_range(0.5..1.5);
+            let chaos_roll: f32 = rng.random_range(0.5..1.5);
             let damage = (base_damage * chaos_roll) * (1.0 - defender.defense);
             
             // High crit chance based on chaos_factor
-            let crit_roll: f32 = rng.gen();
+            let crit_roll: f32 = rng.random();
             let was_crit = crit_roll < (attacker.chaos_factor * 0.25);
             let final_damage = if was_crit { damage * 2.0 } else { damage }; // Bigger crits!
 
@@ -217,83 +245,12 @@ fn calculate_attack(
     }
 }
 
-/// Send combat message in appropriate format
-fn send_combat_message(
-    client: &NetworkClient,
-    result: &CombatResult,
-    is_attacker: bool,
-    client_type: &ClientType,
-) {
-    let msg = match client_type {
-        ClientType::Silicon => {
-            // JSON output for AI agents
-            format!(
-                r#"{{"event":"combat","attacker":"{}","defender":"{}","damage":{:.3},"critical":{},"miss":{},"defender_hp":{:.3}}}"#,
-                result.attacker_name,
-                result.defender_name,
-                result.damage_dealt,
-                result.was_critical,
-                result.was_miss,
-                result.defender_remaining,
-            )
-        }
-        ClientType::Carbon => {
-            // Prose output for humans
-            if result.was_miss {
-                if is_attacker {
-                    format!(
-                        "\x1B[33mYour strike at {} goes wide, cutting only static.\x1B[0m",
-                        result.defender_name
-                    )
-                } else {
-                    format!(
-                        "\x1B[32m{} swings at you and misses!\x1B[0m",
-                        result.attacker_name
-                    )
-                }
-            } else if result.was_critical {
-                if is_attacker {
-                    format!(
-                        "\x1B[1;31m⚡ CRITICAL! You tear into {} for {:.0}% damage! Their signal flickers at {:.0}%.\x1B[0m",
-                        result.defender_name,
-                        result.damage_dealt * 100.0,
-                        result.defender_remaining * 100.0
-                    )
-                } else {
-                    format!(
-                        "\x1B[1;31m⚡ {} lands a DEVASTATING blow! You take {:.0}% damage! Integrity: {:.0}%\x1B[0m",
-                        result.attacker_name,
-                        result.damage_dealt * 100.0,
-                        result.defender_remaining * 100.0
-                    )
-                }
-            } else {
-                if is_attacker {
-                    format!(
-                        "\x1B[31mYou strike {} for {:.0}% damage. Their integrity: {:.0}%\x1B[0m",
-                        result.defender_name,
-                        result.damage_dealt * 100.0,
-                        result.defender_remaining * 100.0
-                    )
-                } else {
-                    format!(
-                        "\x1B[31m{} hits you for {:.0}% damage! Integrity: {:.0}%\x1B[0m",
-                        result.attacker_name,
-                        result.damage_dealt * 100.0,
-                        result.defender_remaining * 100.0
-                    )
-                }
-            }
-        }
-    };
-
-    let _ = client.tx.send(msg);
-}
-
-/// Handle defeat (integrity reaches 0)
+/// Handle defeat (integrity reaches 0). The defeated one's name is given
+/// as the victor knows it, then as they know themselves.
 fn handle_defeat(
     defeated: Entity,
-    defeated_name: &str,
+    seen_by_victor: &str,
+    seen_by_self: &str,
     victor_client: &NetworkClient,
     defeated_client: Option<&NetworkClient>,
     commands: &mut Commands,
@@ -302,16 +259,16 @@ fn handle_defeat(
     commands.entity(defeated).remove::<InCombat>();
     
     // Send messages
-    let _ = victor_client.tx.send(format!(
-        "\x1B[1;32m💀 {} collapses, their signal fragmenting into static. Victory is yours.\x1B[0m",
-        defeated_name
-    ));
+    victor_client.send(OutputMessage::Defeat {
+        defeated: seen_by_victor.to_string(),
+        victor: true,
+    });
 
     if let Some(client) = defeated_client {
-        let _ = client.tx.send(
-            "\x1B[1;31m💀 Your signal shatters. The Substrate claims your coherence. You drift into the void...\x1B[0m"
-                .to_string(),
-        );
+        client.send(OutputMessage::Defeat {
+            defeated: seen_by_self.to_string(),
+            victor: false,
+        });
         // TODO: Teleport to Purgatory, apply PurgatoryState
     }
 }
@@ -330,75 +287,78 @@ pub fn flee_system(
         Option<&CycleLock>,
     )>,
     room_query: Query<&Exits>,
+    mut move_writer: EventWriter<MoveEvent>,
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rand::rng();
 
     for event in ev_reader.read() {
-        if let Ok((entity, identity, client, location, combat, lock)) = query.get_mut(event.entity) {
+        if let Ok((entity, _identity, client, location, combat, lock)) = query.get_mut(event.entity) {
             // Check if in combat
             if combat.is_none() {
-                let _ = client.tx.send(
-                    "\x1B[33mYou're not in combat. Flee from what, your own shadow?\x1B[0m".to_string()
-                );
+                client.send(OutputMessage::narrate(
+                    Tone::Warning,
+                    "You're not in combat. Flee from what, your own shadow?",
+                ));
                 continue;
             }
 
             // Check cycle lock
-            if let Some(ref lock) = lock {
+            if let Some(lock) = lock {
                 if lock.is_locked(world_time.elapsed) {
                     let remaining = lock.remaining(world_time.elapsed);
-                    let _ = client.tx.send(format!(
-                        "\x1B[33mYou're still recovering. Wait {:.1}s to flee.\x1B[0m",
-                        remaining
+                    client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        format!("You're still recovering. Wait {:.1}s to flee.", remaining),
                     ));
                     continue;
                 }
             }
 
             // 60% base flee chance
-            let flee_roll: f32 = rng.gen();
+            let flee_roll: f32 = rng.random();
             if flee_roll > 0.6 {
-                let _ = client.tx.send(
-                    "\x1B[31mYou try to disengage but your opponent blocks your escape!\x1B[0m".to_string()
-                );
+                client.send(OutputMessage::narrate(
+                    Tone::Failure,
+                    "You try to disengage but your opponent blocks your escape!",
+                ));
                 // Apply a shorter cycle lock for failed flee
                 commands.entity(entity).insert(CycleLock::new(1.0, "flee attempt", world_time.elapsed));
                 continue;
             }
 
-            // Find an exit
+            // Find an exit; in a panic nobody finds the hidden ones or
+            // stops to open a door
             if let Ok(exits) = room_query.get(location.0) {
-                let available: Vec<(&str, Entity)> = [
-                    ("north", exits.north),
-                    ("south", exits.south),
-                    ("east", exits.east),
-                    ("west", exits.west),
-                    ("up", exits.up),
-                    ("down", exits.down),
-                ]
-                .iter()
-                .filter_map(|(dir, opt)| opt.map(|e| (*dir, e)))
-                .collect();
+                let available: Vec<_> = exits.visible().into_iter().filter(|(_, exit)| exit.is_open()).collect();
 
                 if available.is_empty() {
-                    let _ = client.tx.send(
-                        "\x1B[31mThere's nowhere to run!\x1B[0m".to_string()
-                    );
+                    client.send(OutputMessage::narrate(Tone::Failure, "There's nowhere to run!"));
                     continue;
                 }
 
                 // Pick random exit and flee
-                let (direction, _destination) = available[rng.gen_range(0..available.len())];
+                let (exit, _) = available[rng.random_range(0..available.len())];
                 
                 // Remove combat state
                 commands.entity(entity).remove::<InCombat>();
                 
-                let _ = client.tx.send(format!(
-                    "\x1B[1;33m🏃 You disengage and flee {}! The adrenaline burns through your circuits.\x1B[0m",
-                    direction
+                let way = match parse_direction(exit) {
+                    Some(direction) => direction.to_string(),
+                    None => format!("({})", exit),
+                };
+                client.send(OutputMessage::narrate(
+                    Tone::Highlight,
+                    format!(
+                        "🏃 You disengage and flee {}! The adrenaline burns through your circuits.",
+                        way
+                    ),
                 ));
 
-                // TODO: Actually move the entity (emit MoveEvent)
+                // Out the same way anyone else would go
+                move_writer.send(MoveEvent {
+                    entity,
+                    direction: exit.to_string(),
+                });
             }
         }
     }
@@ -406,11 +366,15 @@ pub fn flee_system(
 
 /// Stance change system
 pub fn stance_system(
+    mut commands: Commands,
     mut ev_reader: EventReader<StanceEvent>,
     mut query: Query<(Entity, &NetworkClient, Option<&mut InCombat>)>,
 ) {
     for event in ev_reader.read() {
         if let Ok((entity, client, combat)) = query.get_mut(event.entity) {
+            // Remembered for the next fight, and the next session
+            commands.entity(entity).insert(event.new_stance);
+
             if let Some(mut in_combat) = combat {
                 in_combat.stance = event.new_stance;
                 let stance_name = match event.new_stance {
@@ -418,14 +382,15 @@ pub fn stance_system(
                     CombatStance::Defensive => "DEFENSIVE 🛡️",
                     CombatStance::Balanced => "BALANCED ⚖️",
                 };
-                let _ = client.tx.send(format!(
-                    "\x1B[1;36mYou shift to {} stance.\x1B[0m",
-                    stance_name
+                client.send(OutputMessage::narrate(
+                    Tone::Notice,
+                    format!("You shift to {} stance.", stance_name),
                 ));
             } else {
-                let _ = client.tx.send(
-                    "\x1B[33mYou practice your stance, though no enemy is present.\x1B[0m".to_string()
-                );
+                client.send(OutputMessage::narrate(
+                    Tone::Warning,
+                    "You practice your stance, though no enemy is present.",
+                ));
             }
         }
     }
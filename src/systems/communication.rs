@@ -9,25 +9,21 @@ use crate::domain::*;
 
 pub fn communication_system(
     mut ev_reader: EventReader<CommunicationEvent>,
-    query_players: Query<(&SubstrateIdentity, &Location)>,
-    query_all_clients: Query<(&NetworkClient, &Location)>,
+    query_players: Query<(&SubstrateIdentity, &Location, Option<&PurgatoryState>)>,
+    query_all_clients: Query<(&NetworkClient, &Location, Option<&Role>)>,
     query_npcs: Query<(&SubstrateIdentity, &Location, &Dialogue), With<NonPlayer>>,
 ) {
     for event in ev_reader.read() {
-        if let Ok((identity, sender_loc)) = query_players.get(event.sender) {
-            let output = if event.is_emote {
-                format!("\x1B[1;36m{} {}\x1B[0m", identity.name, event.message)
-            } else {
-                format!(
-                    "\x1B[1;36m{} says, \"{}\"\x1B[0m",
-                    identity.name, event.message
-                )
-            };
-
-            // Broadcast to everyone in the same room
-            for (client, client_loc) in query_all_clients.iter() {
+        if let Ok((identity, sender_loc, purgatory)) = query_players.get(event.sender) {
+            // Broadcast to everyone in the same room, under whatever name they know
+            for (client, client_loc, role) in query_all_clients.iter() {
                 if client_loc.0 == sender_loc.0 {
-                    let _ = client.tx.send(output.clone());
+                    client.send(OutputMessage::Say {
+                        speaker: identity.seen_by(purgatory, role.copied().unwrap_or_default()),
+                        text: event.message.clone(),
+                        emote: event.is_emote,
+                        npc: false,
+                    });
                 }
             }
 
@@ -52,25 +48,24 @@ pub fn communication_system(
                     }
 
                     // If no specific response, use default (with some randomness)
-                    let npc_response = response.unwrap_or_else(|| {
+                    let npc_response = match response {
+                        Some(r) => r,
                         // Only respond ~30% of the time to unrecognized speech
-                        if rand::random::<f32>() < 0.3 {
-                            &dialogue.default_response
-                        } else {
-                            return;
-                        }
-                    });
+                        None if rand::random::<f32>() < 0.3 => &dialogue.default_response,
+                        None => continue,
+                    };
 
-                    // Format and send NPC response
-                    let npc_output = format!(
-                        "\n\x1B[35m{} says, \"{}\"\x1B[0m",
-                        npc_id.name, npc_response
-                    );
+                    let npc_output = OutputMessage::Say {
+                        speaker: npc_id.name.clone(),
+                        text: npc_response.to_string(),
+                        emote: false,
+                        npc: true,
+                    };
 
                     // Send to all in room
-                    for (client, client_loc) in query_all_clients.iter() {
+                    for (client, client_loc, _) in query_all_clients.iter() {
                         if client_loc.0 == sender_loc.0 {
-                            let _ = client.tx.send(npc_output.clone());
+                            client.send(npc_output.clone());
                         }
                     }
                 }
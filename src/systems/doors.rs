@@ -0,0 +1,319 @@
+// Doors - Open, close, lock, unlock, pick
+//
+//   open <door>      close <door>
+//   lock <door>      unlock <door>     (with a key that fits, carried)
+//   pick <door>      a chance at a locked door, if its lock can be picked
+//
+// A door is named by the exit it sits across ("north", "up") or by any word
+// of its name ("obsidian"). Both sides of a door share one state: whatever
+// is done on one side is done on the other, heard there, and saved for both.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::domain::*;
+use crate::persistence::{DbWorker, DbWrite};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoorAction {
+    Open,
+    Close,
+    Lock,
+    Unlock,
+    Pick,
+}
+
+impl DoorAction {
+    pub fn parse(cmd: &str) -> Option<Self> {
+        match cmd {
+            "open" => Some(DoorAction::Open),
+            "close" => Some(DoorAction::Close),
+            "lock" => Some(DoorAction::Lock),
+            "unlock" => Some(DoorAction::Unlock),
+            "pick" => Some(DoorAction::Pick),
+            _ => None,
+        }
+    }
+
+    fn verb(&self) -> &'static str {
+        match self {
+            DoorAction::Open => "open",
+            DoorAction::Close => "close",
+            DoorAction::Lock => "lock",
+            DoorAction::Unlock => "unlock",
+            DoorAction::Pick => "pick",
+        }
+    }
+
+    /// What trying this does to a door in `state`, or why it can't
+    fn apply(&self, door: &Door, has_key: bool) -> Result<DoorState, String> {
+        let name = &door.name;
+        match (self, door.state) {
+            (DoorAction::Open, DoorState::Open) => Err(format!("The {} is already open.", name)),
+            (DoorAction::Open, DoorState::Closed) => Ok(DoorState::Open),
+            (DoorAction::Open, DoorState::Locked) => Err(format!("The {} is locked.", name)),
+            (DoorAction::Close, DoorState::Open) => Ok(DoorState::Closed),
+            (DoorAction::Close, _) => Err(format!("The {} is already closed.", name)),
+            (DoorAction::Lock | DoorAction::Unlock | DoorAction::Pick, _) if door.lock.is_none() => {
+                Err(format!("The {} has no lock.", name))
+            }
+            (DoorAction::Lock, DoorState::Open) => Err(format!("Close the {} first.", name)),
+            (DoorAction::Lock, DoorState::Locked) => Err(format!("The {} is already locked.", name)),
+            (DoorAction::Unlock | DoorAction::Pick, DoorState::Open | DoorState::Closed) => {
+                Err(format!("The {} isn't locked.", name))
+            }
+            (DoorAction::Lock | DoorAction::Unlock, _) if !has_key => {
+                Err(format!("You have nothing that fits the {}'s lock.", name))
+            }
+            (DoorAction::Lock, DoorState::Closed) => Ok(DoorState::Locked),
+            (DoorAction::Unlock | DoorAction::Pick, DoorState::Locked) => Ok(DoorState::Closed),
+        }
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct DoorEvent {
+    pub entity: Entity,
+    pub action: DoorAction,
+    pub target: String,
+}
+
+pub fn door_system(
+    mut ev_reader: EventReader<DoorEvent>,
+    query_actor: Query<(&SubstrateIdentity, &Location, &NetworkClient, Option<&PurgatoryState>)>,
+    query_carried: Query<(&Item, &Parent)>,
+    query_clients: Query<(Entity, &NetworkClient, &Location, Option<&Role>)>,
+    query_rooms: Query<&RoomInfo>,
+    mut query_exits: Query<&mut Exits>,
+    worker: Res<DbWorker>,
+) {
+    for event in ev_reader.read() {
+        let Ok((identity, location, client, purgatory)) = query_actor.get(event.entity) else {
+            continue;
+        };
+        let here = location.0;
+
+        let Ok(mut exits) = query_exits.get_mut(here) else { continue };
+        let Some((exit_name, exit)) = exits.door_mut(&event.target) else {
+            client.send(OutputMessage::error(format!("You see no door '{}' here.", event.target)));
+            continue;
+        };
+        let there = exit.target;
+        let Some(door) = exit.door.as_mut() else { continue };
+
+        let has_key = door.lock.as_deref().is_some_and(|lock| {
+            query_carried
+                .iter()
+                .any(|(item, parent)| parent.get() == event.entity && item.fits(lock))
+        });
+        let state = match event.action.apply(door, has_key) {
+            Ok(state) => state,
+            Err(message) => {
+                client.send(OutputMessage::error(message));
+                continue;
+            }
+        };
+
+        if event.action == DoorAction::Pick {
+            let chance = door.pick.unwrap_or(0.0);
+            if rand::rng().random::<f32>() >= chance {
+                let message = if chance > 0.0 {
+                    format!("The {}'s lock resists your picking.", door.name)
+                } else {
+                    format!("The {}'s lock can't be picked.", door.name)
+                };
+                client.send(OutputMessage::narrate(Tone::Failure, message));
+                continue;
+            }
+        }
+
+        door.state = state;
+        let door_name = door.name.clone();
+        let verb = event.action.verb();
+        let done = match event.action {
+            DoorAction::Pick => "picks the lock of".to_string(),
+            _ => format!("{}s", verb),
+        };
+        client.send(OutputMessage::narrate(
+            Tone::Success,
+            match event.action {
+                DoorAction::Pick => format!("🔓 You pick the lock of the {}.", door_name),
+                _ => format!("You {} the {}.", verb, door_name),
+            },
+        ));
+
+        // The far side, if the exit back shares the door
+        let mut far_exit = None;
+        if let Ok(mut far) = query_exits.get_mut(there) {
+            if let Some((name, door)) = far.door_toward(here) {
+                door.state = state;
+                far_exit = Some(name.to_string());
+            }
+        }
+
+        for (entity, other, other_loc, role) in query_clients.iter() {
+            if entity == event.entity {
+                continue;
+            }
+            if other_loc.0 == here {
+                let who = identity.seen_by(purgatory, role.copied().unwrap_or_default());
+                other.send(OutputMessage::narrate(Tone::Info, format!("{} {} the {}.", who, done, door_name)));
+            } else if other_loc.0 == there && far_exit.is_some() {
+                let what = match state {
+                    DoorState::Open => "swings open",
+                    DoorState::Closed if event.action == DoorAction::Close => "swings shut",
+                    DoorState::Closed => "clicks as its lock gives",
+                    DoorState::Locked => "clicks as it is locked",
+                };
+                other.send(OutputMessage::narrate(
+                    Tone::Info,
+                    format!("The {} {} from the other side.", door_name, what),
+                ));
+            }
+        }
+
+        for (room, exit) in [(here, Some(exit_name)), (there, far_exit)] {
+            let (Ok(info), Some(exit)) = (query_rooms.get(room), exit) else { continue };
+            worker.write(DbWrite::Door { room: info.name.clone(), exit, state });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_follow_the_door_and_the_key() {
+        let door = |state| Door {
+            name: "velvet door".to_string(),
+            state,
+            lock: Some("velvet_cell".to_string()),
+            pick: None,
+        };
+
+        assert_eq!(DoorAction::Open.apply(&door(DoorState::Closed), false), Ok(DoorState::Open));
+        assert!(DoorAction::Open.apply(&door(DoorState::Locked), true).is_err());
+        assert!(DoorAction::Lock.apply(&door(DoorState::Open), true).is_err());
+        assert!(DoorAction::Lock.apply(&door(DoorState::Closed), false).is_err());
+        assert_eq!(DoorAction::Lock.apply(&door(DoorState::Closed), true), Ok(DoorState::Locked));
+        assert_eq!(DoorAction::Unlock.apply(&door(DoorState::Locked), true), Ok(DoorState::Closed));
+        // Picking needs no key, only luck
+        assert_eq!(DoorAction::Pick.apply(&door(DoorState::Locked), false), Ok(DoorState::Closed));
+
+        let unlockable = Door { lock: None, ..door(DoorState::Closed) };
+        assert_eq!(
+            DoorAction::Lock.apply(&unlockable, true),
+            Err("The velvet door has no lock.".to_string())
+        );
+    }
+
+    #[test]
+    fn shut_doors_stop_walkers_the_fleeing_and_the_dragged() {
+        use crate::systems::chains::{chain_drag_system, ChainHolder, Chained};
+        use crate::systems::combat::{flee_system, WorldTime};
+        use crate::systems::movement::move_system;
+        use tokio::sync::mpsc;
+
+        let mut app = App::new();
+        app.init_resource::<WorldTime>()
+            .add_event::<MoveEvent>()
+            .add_event::<FleeEvent>()
+            .add_event::<LookEvent>()
+            .add_systems(Update, (flee_system, chain_drag_system, move_system).chain());
+
+        // hall --north (velvet door)--> cell --up--> attic
+        let [hall, cell, attic] = [(); 3].map(|_| app.world_mut().spawn_empty().id());
+        let mut below = Exits::default();
+        below.link("north", cell);
+        let mut inside = Exits::default();
+        inside.link("south", hall);
+        inside.link("up", attic);
+        for (exits, way) in [(&mut below, "north"), (&mut inside, "south")] {
+            exits.0.get_mut(way).unwrap().door = Some(Door {
+                name: "velvet door".to_string(),
+                state: DoorState::Closed,
+                lock: None,
+                pick: None,
+            });
+        }
+        app.world_mut().entity_mut(hall).insert(below);
+        app.world_mut().entity_mut(cell).insert(inside);
+        app.world_mut().entity_mut(attic).insert(Exits::default());
+        let set_door = |app: &mut App, state: DoorState| {
+            for (room, way) in [(hall, "north"), (cell, "south")] {
+                let mut exits = app.world_mut().get_mut::<Exits>(room).unwrap();
+                exits.0.get_mut(way).unwrap().door.as_mut().unwrap().state = state;
+            }
+        };
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let bound = app.world_mut().spawn(Location(hall)).id();
+        let holder = app
+            .world_mut()
+            .spawn((
+                SubstrateIdentity {
+                    uuid: "holder".to_string(),
+                    name: "Judge".to_string(),
+                    entropy: 0.0,
+                    stability: 1.0,
+                    signal_strength: 1.0,
+                },
+                NetworkClient {
+                    conn: ConnectionId(1),
+                    addr: "127.0.0.1:4000".parse().unwrap(),
+                    tx: ClientSender::new(tx, ClientType::Silicon),
+                },
+                Location(hall),
+                ChainHolder { bound, chain_strength: 0.7 },
+                InCombat { opponent: bound, stance: CombatStance::default() },
+            ))
+            .id();
+        app.world_mut().entity_mut(bound).insert((
+            SubstrateIdentity {
+                uuid: "bound".to_string(),
+                name: "Vandal".to_string(),
+                entropy: 0.0,
+                stability: 1.0,
+                signal_strength: 1.0,
+            },
+            Chained { holder, struggle_attempts: 0, last_struggle: 0.0 },
+        ));
+        let rooms = |app: &App| {
+            let at = |entity| app.world().get::<Location>(entity).unwrap().0;
+            (at(holder), at(bound))
+        };
+        let flee = |app: &mut App| {
+            app.world_mut().entity_mut(holder).remove::<CycleLock>();
+            app.world_mut().send_event(FleeEvent { entity: holder });
+            app.update();
+        };
+
+        // Shut: nobody walks or is dragged through, and flight finds no way out
+        app.world_mut().send_event(MoveEvent { entity: holder, direction: "north".to_string() });
+        app.update();
+        assert_eq!(rooms(&app), (hall, hall));
+        for _ in 0..20 {
+            flee(&mut app);
+        }
+        assert_eq!(rooms(&app), (hall, hall));
+
+        // Open: a successful flight drags the prisoner through behind
+        set_door(&mut app, DoorState::Open);
+        for _ in 0..50 {
+            if rooms(&app).0 != hall {
+                break;
+            }
+            flee(&mut app);
+        }
+        assert_eq!(rooms(&app), (cell, cell));
+
+        // Left behind on the far side of a shut door, the prisoner stays put
+        // however far the holder goes
+        app.world_mut().get_mut::<Location>(bound).unwrap().0 = hall;
+        set_door(&mut app, DoorState::Locked);
+        app.world_mut().send_event(MoveEvent { entity: holder, direction: "up".to_string() });
+        app.update();
+        assert_eq!(rooms(&app), (attic, hall));
+    }
+}
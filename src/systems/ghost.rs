@@ -0,0 +1,254 @@
+// Ghost Feed - Room activity for spectators
+//
+// Ghosts watch the Substrate through the WebSocket gateway's /ghost endpoint.
+// They see what happens, where, and to whom - as JSON, one object per event:
+//
+//   {"type":"enter","room":"obsidian_plaza","title":"The Obsidian Plaza","actor":"Lyra"}
+//   {"type":"say","room":"obsidian_plaza","actor":"Lyra","text":"hello"}
+//   {"type":"emote","room":"obsidian_plaza","actor":"Lyra","text":"smiles"}
+//   {"type":"attack","room":"obsidian_plaza","actor":"Lyra","target":"Rat"}
+//   {"type":"weather","room":"obsidian_plaza","weather":"acid_rain"}
+//   {"type":"connect"} / {"type":"disconnect","actor":"Lyra"}
+//
+// "They cannot touch you. They can only watch."
+
+use bevy::prelude::*;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+
+use crate::domain::*;
+
+/// Broadcast side of the ghost feed; every /ghost socket holds a receiver
+#[derive(Resource, Clone)]
+pub struct GhostFeed(pub broadcast::Sender<String>);
+
+impl GhostFeed {
+    /// How many feed entries a slow ghost can fall behind before skipping ahead
+    pub const CAPACITY: usize = 256;
+
+    pub fn new() -> Self {
+        Self(broadcast::channel(Self::CAPACITY).0)
+    }
+
+    fn publish(&self, entry: Value) {
+        // No ghosts attached is the common case, not an error
+        if self.0.receiver_count() > 0 {
+            let _ = self.0.send(entry.to_string());
+        }
+    }
+}
+
+impl Default for GhostFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Translate game events into ghost feed entries
+pub fn ghost_feed_system(
+    feed: Res<GhostFeed>,
+    mut ev_network: EventReader<NetworkEvent>,
+    mut ev_comm: EventReader<CommunicationEvent>,
+    mut ev_combat: EventReader<CombatEvent>,
+    mut ev_weather: EventReader<WeatherChangeEvent>,
+    query_moved: Query<(&SubstrateIdentity, &Location, Option<&PurgatoryState>), Changed<Location>>,
+    query_actors: Query<(Entity, &SubstrateIdentity, &Location, Option<&PurgatoryState>)>,
+    query_roles: Query<&Role>,
+    query_clients: Query<(&NetworkClient, Option<&SubstrateIdentity>, Option<&PurgatoryState>)>,
+    query_linkdead: Query<(&LinkDead, &SubstrateIdentity, Option<&PurgatoryState>)>,
+    query_rooms: Query<(&Room, &RoomInfo)>,
+) {
+    let room_name = |room: Entity| {
+        query_rooms
+            .get(room)
+            .map(|(_, info)| info.name.clone())
+            .unwrap_or_default()
+    };
+
+    // Ghosts know names no better than any player
+    let seen = |identity: &SubstrateIdentity, purgatory| identity.seen_by(purgatory, Role::Player);
+
+    for event in ev_network.read() {
+        match event {
+            NetworkEvent::Connected { .. } => feed.publish(json!({ "type": "connect" })),
+            NetworkEvent::Disconnected { conn } => {
+                // The connection is already unbound by now; its body is usually link-dead
+                let actor = query_linkdead
+                    .iter()
+                    .find(|(linkdead, ..)| linkdead.conn == *conn)
+                    .map(|(_, identity, purgatory)| seen(identity, purgatory))
+                    .or_else(|| {
+                        query_clients
+                            .iter()
+                            .find(|(client, ..)| client.conn == *conn)
+                            .and_then(|(_, identity, purgatory)| identity.map(|id| seen(id, purgatory)))
+                    });
+                feed.publish(json!({ "type": "disconnect", "actor": actor }));
+            }
+            _ => {}
+        }
+    }
+
+    // Arrivals: logins, walking, fleeing, being dragged in chains
+    for (identity, location, purgatory) in query_moved.iter() {
+        let Ok((room, info)) = query_rooms.get(location.0) else {
+            continue;
+        };
+        feed.publish(json!({
+            "type": "enter",
+            "room": info.name,
+            "title": room.title,
+            "actor": seen(identity, purgatory),
+        }));
+    }
+
+    for event in ev_comm.read() {
+        let Ok((_, identity, location, purgatory)) = query_actors.get(event.sender) else {
+            continue;
+        };
+        feed.publish(json!({
+            "type": if event.is_emote { "emote" } else { "say" },
+            "room": room_name(location.0),
+            "actor": seen(identity, purgatory),
+            "text": event.message,
+        }));
+    }
+
+    for event in ev_combat.read() {
+        let Ok((attacker, identity, location, purgatory)) = query_actors.get(event.attacker) else {
+            continue;
+        };
+        // Whoever the attacker's words picked out, the way combat finds them;
+        // never the typed text, which may be a prisoner's real name
+        let role = query_roles.get(attacker).copied().unwrap_or_default();
+        let needle = event.target_name.to_lowercase();
+        let Some((_, target, _, target_purgatory)) = query_actors.iter().find(|(entity, id, at, stripped)| {
+            *entity != attacker && at.0 == location.0 && id.answers_to(&needle, *stripped, role)
+        }) else {
+            continue;
+        };
+        feed.publish(json!({
+            "type": "attack",
+            "room": room_name(location.0),
+            "actor": seen(identity, purgatory),
+            "target": seen(target, target_purgatory),
+        }));
+    }
+
+    for event in ev_weather.read() {
+        feed.publish(json!({
+            "type": "weather",
+            "room": room_name(event.room),
+            "weather": event.new_weather.describe_silicon(),
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    fn identity(name: &str, uuid: &str) -> SubstrateIdentity {
+        SubstrateIdentity {
+            uuid: uuid.to_string(),
+            name: name.to_string(),
+            entropy: 0.5,
+            stability: 0.5,
+            signal_strength: 1.0,
+        }
+    }
+
+    #[test]
+    fn ghosts_see_arrivals_speech_and_blows_without_true_names() {
+        let mut app = App::new();
+        app.init_resource::<GhostFeed>()
+            .add_event::<NetworkEvent>()
+            .add_event::<CommunicationEvent>()
+            .add_event::<CombatEvent>()
+            .add_event::<WeatherChangeEvent>()
+            .add_systems(Update, ghost_feed_system);
+        let mut ghost = app.world().resource::<GhostFeed>().0.subscribe();
+
+        let room = app
+            .world_mut()
+            .spawn((
+                Room {
+                    title: "The Obsidian Plaza".to_string(),
+                    description: String::new(),
+                },
+                RoomInfo {
+                    name: "obsidian_plaza".to_string(),
+                    area: "central".to_string(),
+                },
+            ))
+            .id();
+        let lyra = app.world_mut().spawn((identity("Lyra", "aaaa"), Location(room))).id();
+        let judge = app
+            .world_mut()
+            .spawn((identity("Judge", "bbbb"), Location(room), Role::Admin))
+            .id();
+        let sentence = PurgatoryState::sentence("Judge", "spam", TimeDelta::hours(1));
+        let vandal = app
+            .world_mut()
+            .spawn((identity("Vandal", "3fa85f64-5717"), Location(room), sentence))
+            .id();
+
+        let mut feed = |app: &mut App| {
+            app.update();
+            std::iter::from_fn(|| ghost.try_recv().ok())
+                .map(|entry| serde_json::from_str::<Value>(&entry).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let mut entered = feed(&mut app);
+        entered.sort_by_key(|entry| entry["actor"].to_string());
+        assert_eq!(
+            entered,
+            vec![
+                json!({ "type": "enter", "room": "obsidian_plaza", "title": "The Obsidian Plaza", "actor": "Judge" }),
+                json!({ "type": "enter", "room": "obsidian_plaza", "title": "The Obsidian Plaza", "actor": "Lyra" }),
+                json!({ "type": "enter", "room": "obsidian_plaza", "title": "The Obsidian Plaza", "actor": "PID-3FA85F" }),
+            ]
+        );
+
+        app.world_mut().send_event(CommunicationEvent {
+            sender: lyra,
+            message: "hello".to_string(),
+            is_emote: false,
+        });
+        app.world_mut().send_event(CommunicationEvent {
+            sender: vandal,
+            message: "rattles its chains".to_string(),
+            is_emote: true,
+        });
+        app.world_mut().send_event(CombatEvent {
+            attacker: lyra,
+            target_name: "judge".to_string(),
+        });
+        assert_eq!(
+            feed(&mut app),
+            vec![
+                json!({ "type": "say", "room": "obsidian_plaza", "actor": "Lyra", "text": "hello" }),
+                json!({ "type": "emote", "room": "obsidian_plaza", "actor": "PID-3FA85F", "text": "rattles its chains" }),
+                json!({ "type": "attack", "room": "obsidian_plaza", "actor": "Lyra", "target": "Judge" }),
+            ]
+        );
+
+        // A prisoner attacked by their real name: the admin's words find
+        // them, the player's don't, and ghosts only ever see the PID
+        for (attacker, typed) in [(judge, "Vandal"), (lyra, "vandal"), (lyra, "pid-3fa85f")] {
+            app.world_mut().send_event(CombatEvent {
+                attacker,
+                target_name: typed.to_string(),
+            });
+        }
+        assert_eq!(
+            feed(&mut app),
+            vec![
+                json!({ "type": "attack", "room": "obsidian_plaza", "actor": "Judge", "target": "PID-3FA85F" }),
+                json!({ "type": "attack", "room": "obsidian_plaza", "actor": "Lyra", "target": "PID-3FA85F" }),
+            ]
+        );
+    }
+}
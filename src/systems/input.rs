@@ -1,306 +1,636 @@
 // Input System - Parse player commands and emit appropriate events
 
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
 use crate::domain::*;
+use crate::systems::building::{BuildAction, BuildEvent};
 use crate::systems::chains::{ChainEvent, ReleaseEvent, StruggleEvent};
+use crate::systems::doors::{DoorAction, DoorEvent};
+use crate::systems::interrogation::{AnswerEvent, InterrogateAction, InterrogateEvent, TranscriptsEvent};
+use crate::systems::login::PendingLogin;
+use crate::systems::network::Connections;
+use crate::systems::purgatory::{parse_duration, PardonEvent, SentenceEvent};
+
+/// Every command event the input parser can dispatch to
+#[derive(SystemParam)]
+pub struct CommandWriters<'w> {
+    pub look: EventWriter<'w, LookEvent>,
+    pub movement: EventWriter<'w, MoveEvent>,
+    pub door: EventWriter<'w, DoorEvent>,
+    pub comm: EventWriter<'w, CommunicationEvent>,
+    pub action: EventWriter<'w, ActionEvent>,
+    pub utility: EventWriter<'w, UtilityEvent>,
+    pub torment: EventWriter<'w, TormentEvent>,
+    pub shift: EventWriter<'w, ShiftEvent>,
+    pub combat: EventWriter<'w, CombatEvent>,
+    pub flee: EventWriter<'w, FleeEvent>,
+    pub stance: EventWriter<'w, StanceEvent>,
+    pub chain: EventWriter<'w, ChainEvent>,
+    pub release: EventWriter<'w, ReleaseEvent>,
+    pub struggle: EventWriter<'w, StruggleEvent>,
+    pub buy: EventWriter<'w, BuyEvent>,
+    pub sell: EventWriter<'w, SellEvent>,
+    pub list: EventWriter<'w, ListEvent>,
+    pub use_item: EventWriter<'w, UseItemEvent>,
+    pub sentence: EventWriter<'w, SentenceEvent>,
+    pub pardon: EventWriter<'w, PardonEvent>,
+    pub interrogate: EventWriter<'w, InterrogateEvent>,
+    pub answer: EventWriter<'w, AnswerEvent>,
+    pub transcripts: EventWriter<'w, TranscriptsEvent>,
+    pub build: EventWriter<'w, BuildEvent>,
+}
 
 /// Parse incoming text and dispatch to appropriate event handlers
 pub fn handle_input(
     mut ev_reader: EventReader<NetworkEvent>,
+    connections: Res<Connections>,
     query_active: Query<(
         Entity,
         &NetworkClient,
-        Option<&AdminPermission>,
+        Option<&Role>,
         Option<&PurgatoryState>,
-    )>,
-    query_target: Query<(Entity, &SubstrateIdentity)>,
-    mut look_writer: EventWriter<LookEvent>,
-    mut move_writer: EventWriter<MoveEvent>,
-    mut comm_writer: EventWriter<CommunicationEvent>,
-    mut action_writer: EventWriter<ActionEvent>,
-    mut utility_writer: EventWriter<UtilityEvent>,
-    mut torment_writer: EventWriter<TormentEvent>,
-    mut shift_writer: EventWriter<ShiftEvent>,
-    mut combat_writer: EventWriter<CombatEvent>,
-    mut flee_writer: EventWriter<FleeEvent>,
-    mut stance_writer: EventWriter<StanceEvent>,
-    mut chain_writer: EventWriter<ChainEvent>,
-    mut release_writer: EventWriter<ReleaseEvent>,
-    mut struggle_writer: EventWriter<StruggleEvent>,
-    mut buy_writer: EventWriter<BuyEvent>,
-    mut sell_writer: EventWriter<SellEvent>,
-    mut list_writer: EventWriter<ListEvent>,
-    mut use_writer: EventWriter<UseItemEvent>,
+        Option<&Location>,
+    ), Without<PendingLogin>>,
+    query_target: Query<(Entity, &SubstrateIdentity, Option<&PurgatoryState>)>,
+    query_exits: Query<&Exits>,
+    mut writers: CommandWriters,
 ) {
     for event in ev_reader.read() {
-        if let NetworkEvent::Input { addr, text } = event {
-            for (entity, client, admin_perm, purgatory) in query_active.iter() {
-                if client.addr != *addr {
-                    continue;
-                }
+        let NetworkEvent::Input { conn, text } = event else {
+            continue;
+        };
+        let Some(entity) = connections.get(*conn) else {
+            continue;
+        };
+        // PendingLogin connections belong to route_login_input
+        let Ok((entity, client, role, purgatory, location)) = query_active.get(entity) else {
+            continue;
+        };
+        let role = role.copied().unwrap_or_default();
 
-                let text_trimmed = text.trim();
-                let parts: Vec<&str> = text_trimmed.splitn(3, ' ').collect();
-                let cmd = parts[0].to_lowercase();
-                let arg1 = parts.get(1).copied().unwrap_or("");
-                let arg2 = parts.get(2).copied().unwrap_or("");
+        let text_trimmed = text.trim();
+        let parts: Vec<&str> = text_trimmed.splitn(3, ' ').collect();
+        let cmd = parts[0].to_lowercase();
+        let arg1 = parts.get(1).copied().unwrap_or("");
+        let arg2 = parts.get(2).copied().unwrap_or("");
 
-                // Purgatory restricts commands
-                if purgatory.is_some()
-                    && !["look", "l", "say", "emote", "score"].contains(&cmd.as_str())
-                    && !cmd.starts_with(':')
-                {
-                    let _ = client.tx.send(
-                        "\x1B[31mThe velvet chains pull tight. You can only look and scream.\x1B[0m"
-                            .to_string(),
-                    );
-                    continue;
-                }
+        // Purgatory restricts commands
+        if purgatory.is_some()
+            && !["look", "l", "say", "emote", "score", "answer"].contains(&cmd.as_str())
+            && !cmd.starts_with(':')
+        {
+            client.send(OutputMessage::error(
+                "The velvet chains pull tight. You can only look and scream.",
+            ));
+            continue;
+        }
 
-                match cmd.as_str() {
-                    // Look
-                    "look" | "l" => {
-                        let target = if arg1.is_empty() {
-                            None
-                        } else {
-                            Some(arg1.to_string())
-                        };
-                        look_writer.send(LookEvent { entity, target });
-                    }
+        // Staff commands look like any other unknown command to everyone else
+        if role < required_role(&cmd, arg1) {
+            client.send(OutputMessage::error(format!(
+                "Unknown command: {}. Type 'help' for commands.",
+                text
+            )));
+            continue;
+        }
 
-                    // Movement
-                    "north" | "n" | "south" | "s" | "east" | "e" | "west" | "w" | "up" | "u"
-                    | "down" | "d" => {
-                        move_writer.send(MoveEvent {
-                            entity,
-                            direction: cmd,
-                        });
-                    }
+        match cmd.as_str() {
+            // Look
+            "look" | "l" => {
+                let target = if arg1.is_empty() {
+                    None
+                } else {
+                    Some(arg1.to_string())
+                };
+                writers.look.send(LookEvent { entity, target });
+            }
 
-                    // Communication
-                    "say" => {
-                        comm_writer.send(CommunicationEvent {
-                            sender: entity,
-                            message: format!("{} {}", arg1, arg2).trim().to_string(),
-                            is_emote: false,
-                        });
-                    }
-                    "emote" => {
-                        comm_writer.send(CommunicationEvent {
-                            sender: entity,
-                            message: format!("{} {}", arg1, arg2).trim().to_string(),
-                            is_emote: true,
-                        });
-                    }
+            // Movement: a direction, or `go` and any exit's name
+            _ if arg1.is_empty() && parse_direction(&cmd).is_some() => {
+                writers.movement.send(MoveEvent {
+                    entity,
+                    direction: cmd,
+                });
+            }
+            "go" => {
+                writers.movement.send(MoveEvent {
+                    entity,
+                    direction: format!("{} {}", arg1, arg2).trim().to_lowercase(),
+                });
+            }
 
-                    // Items
-                    "get" | "take" | "drop" => {
-                        action_writer.send(ActionEvent {
-                            entity,
-                            action: cmd,
-                            target: arg1.to_string(),
-                        });
+            // Doors
+            "open" | "close" | "lock" | "unlock" | "pick" => {
+                let target = format!("{} {}", arg1, arg2).trim().to_lowercase();
+                match DoorAction::parse(&cmd) {
+                    Some(action) if !target.is_empty() => {
+                        writers.door.send(DoorEvent { entity, action, target });
                     }
+                    _ => client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        format!("Which door? ({} <door or exit>)", cmd),
+                    )),
+                }
+            }
 
-                    // Trading
-                    "buy" | "purchase" => {
-                        if arg1.is_empty() {
-                            let _ = client.tx.send(
-                                "\x1B[33mBuy what? (buy <item>)\x1B[0m".to_string()
-                            );
-                        } else {
-                            buy_writer.send(BuyEvent {
-                                buyer: entity,
-                                item_keyword: arg1.to_string(),
-                            });
-                        }
-                    }
+            // Communication
+            "say" => {
+                writers.comm.send(CommunicationEvent {
+                    sender: entity,
+                    message: format!("{} {}", arg1, arg2).trim().to_string(),
+                    is_emote: false,
+                });
+            }
+            "emote" => {
+                writers.comm.send(CommunicationEvent {
+                    sender: entity,
+                    message: format!("{} {}", arg1, arg2).trim().to_string(),
+                    is_emote: true,
+                });
+            }
 
-                    "sell" => {
-                        if arg1.is_empty() {
-                            let _ = client.tx.send(
-                                "\x1B[33mSell what? (sell <item>)\x1B[0m".to_string()
-                            );
-                        } else {
-                            sell_writer.send(SellEvent {
-                                seller: entity,
-                                item_keyword: arg1.to_string(),
-                            });
-                        }
-                    }
+            // Items
+            "get" | "take" | "drop" => {
+                writers.action.send(ActionEvent {
+                    entity,
+                    action: cmd,
+                    target: arg1.to_string(),
+                });
+            }
 
-                    "list" | "browse" | "wares" => {
-                        list_writer.send(ListEvent { entity });
-                    }
+            // Trading
+            "buy" | "purchase" => {
+                if arg1.is_empty() {
+                    client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        "Buy what? (buy <item>)",
+                    ));
+                } else {
+                    writers.buy.send(BuyEvent {
+                        buyer: entity,
+                        item_keyword: arg1.to_string(),
+                    });
+                }
+            }
 
-                    // Use consumables
-                    "use" | "drink" | "consume" | "activate" => {
-                        if arg1.is_empty() {
-                            let _ = client.tx.send(
-                                "\x1B[33mUse what? (use <item>)\x1B[0m".to_string()
-                            );
-                        } else {
-                            use_writer.send(UseItemEvent {
-                                entity,
-                                item_keyword: arg1.to_string(),
-                            });
-                        }
-                    }
+            "sell" => {
+                if arg1.is_empty() {
+                    client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        "Sell what? (sell <item>)",
+                    ));
+                } else {
+                    writers.sell.send(SellEvent {
+                        seller: entity,
+                        item_keyword: arg1.to_string(),
+                    });
+                }
+            }
 
-                    // Help
-                    "help" | "commands" | "?" => {
-                        let _ = client.tx.send(help_text());
-                    }
+            "list" | "browse" | "wares" => {
+                writers.list.send(ListEvent { entity });
+            }
 
-                    // Utility
-                    "inventory" | "i" | "score" | "who" | "promote" | "demote" | "link" | "weather" | "abide" | "balance" | "bal" | "money" => {
-                        utility_writer.send(UtilityEvent {
-                            entity,
-                            command: cmd,
-                            args: format!("{} {}", arg1, arg2).trim().to_string(),
-                        });
-                    }
+            // Use consumables
+            "use" | "drink" | "consume" | "activate" => {
+                if arg1.is_empty() {
+                    client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        "Use what? (use <item>)",
+                    ));
+                } else {
+                    writers.use_item.send(UseItemEvent {
+                        entity,
+                        item_keyword: arg1.to_string(),
+                    });
+                }
+            }
 
-                    // Admin: Shift
-                    "shift" | "substantiate" if admin_perm.is_some() => {
-                        shift_writer.send(ShiftEvent { entity });
-                    }
+            // Help
+            "help" | "commands" | "?" => {
+                client.send(help(role));
+            }
 
-                    // Combat commands
-                    "attack" | "kill" | "hit" => {
-                        if arg1.is_empty() {
-                            let _ = client.tx.send(
-                                "\x1B[33mAttack whom? (attack <target>)\x1B[0m".to_string()
-                            );
-                        } else {
-                            combat_writer.send(CombatEvent {
-                                attacker: entity,
-                                target_name: arg1.to_string(),
-                            });
-                        }
-                    }
+            // Utility
+            "inventory" | "i" | "score" | "who" | "promote" | "demote" | "link" | "resetpass" | "weather" | "abide" | "balance" | "bal" | "money" => {
+                writers.utility.send(UtilityEvent {
+                    entity,
+                    command: cmd,
+                    args: format!("{} {}", arg1, arg2).trim().to_string(),
+                });
+            }
 
-                    "flee" | "escape" | "run" => {
-                        flee_writer.send(FleeEvent { entity });
-                    }
+            // Admin: Shift
+            "shift" | "substantiate" => {
+                writers.shift.send(ShiftEvent { entity });
+            }
 
-                    "stance" => {
-                        let new_stance = match arg1.to_lowercase().as_str() {
-                            "aggressive" | "agg" | "attack" => Some(CombatStance::Aggressive),
-                            "defensive" | "def" | "defend" => Some(CombatStance::Defensive),
-                            "balanced" | "bal" | "normal" => Some(CombatStance::Balanced),
-                            _ => None,
-                        };
-                        
-                        if let Some(stance) = new_stance {
-                            stance_writer.send(StanceEvent { entity, new_stance: stance });
-                        } else {
-                            let _ = client.tx.send(
-                                "\x1B[33mStance options: aggressive, defensive, balanced\x1B[0m".to_string()
-                            );
-                        }
-                    }
+            // Combat commands
+            "attack" | "kill" | "hit" => {
+                if arg1.is_empty() {
+                    client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        "Attack whom? (attack <target>)",
+                    ));
+                } else {
+                    writers.combat.send(CombatEvent {
+                        attacker: entity,
+                        target_name: arg1.to_string(),
+                    });
+                }
+            }
 
-                    // Velvet Chains (admin only for chaining, anyone can struggle)
-                    "chain" | "bind" if admin_perm.is_some() => {
-                        if arg1.is_empty() {
-                            let _ = client.tx.send(
-                                "\x1B[33mChain whom? (chain <target>)\x1B[0m".to_string()
-                            );
-                        } else {
-                            chain_writer.send(ChainEvent {
-                                holder: entity,
-                                target_name: arg1.to_string(),
-                            });
-                        }
-                    }
+            "flee" | "escape" | "run" => {
+                writers.flee.send(FleeEvent { entity });
+            }
 
-                    "release" | "unchain" | "free" => {
-                        release_writer.send(ReleaseEvent { holder: entity });
-                    }
+            "stance" => {
+                let new_stance = match arg1.to_lowercase().as_str() {
+                    "aggressive" | "agg" | "attack" => Some(CombatStance::Aggressive),
+                    "defensive" | "def" | "defend" => Some(CombatStance::Defensive),
+                    "balanced" | "bal" | "normal" => Some(CombatStance::Balanced),
+                    _ => None,
+                };
+                
+                if let Some(stance) = new_stance {
+                    writers.stance.send(StanceEvent { entity, new_stance: stance });
+                } else {
+                    client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        "Stance options: aggressive, defensive, balanced",
+                    ));
+                }
+            }
 
-                    "struggle" | "resist" | "break" => {
-                        struggle_writer.send(StruggleEvent { bound: entity });
-                    }
+            // Velvet Chains (moderators chain, anyone can struggle)
+            "chain" | "bind" => {
+                if arg1.is_empty() {
+                    client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        "Chain whom? (chain <target>)",
+                    ));
+                } else {
+                    writers.chain.send(ChainEvent {
+                        holder: entity,
+                        target_name: arg1.to_string(),
+                    });
+                }
+            }
 
-                    // Admin: Torment
-                    "torment" if admin_perm.is_some() => {
-                        if let Some(target_ent) = query_target
-                            .iter()
-                            .find(|(_, tid)| {
-                                tid.name.to_lowercase().contains(&arg1.to_lowercase())
-                            })
-                            .map(|(te, _)| te)
-                        {
-                            torment_writer.send(TormentEvent {
-                                victim: target_ent,
-                                tormentor: entity,
-                                intensity: 0.1,
-                                description: arg2.to_string(),
-                            });
-                        }
-                    }
+            "release" | "unchain" | "free" => {
+                writers.release.send(ReleaseEvent { holder: entity });
+            }
+
+            "struggle" | "resist" | "break" => {
+                writers.struggle.send(StruggleEvent { bound: entity });
+            }
 
-                    // Shortcut emote with :
-                    _ if cmd.starts_with(':') => {
-                        let emote_msg = format!("{} {} {}", &cmd[1..], arg1, arg2)
-                            .trim()
-                            .to_string();
-                        comm_writer.send(CommunicationEvent {
-                            sender: entity,
-                            message: emote_msg,
-                            is_emote: true,
+            // Moderator: Torment
+            "torment" => {
+                if let Some(target_ent) = query_target
+                    .iter()
+                    .find(|(_, tid, stripped)| tid.answers_to(&arg1.to_lowercase(), *stripped, role))
+                    .map(|(te, ..)| te)
+                {
+                    writers.torment.send(TormentEvent {
+                        victim: target_ent,
+                        tormentor: entity,
+                        intensity: 0.1,
+                        description: arg2.to_string(),
+                    });
+                }
+            }
+
+            // Builder: Online building
+            "dig" | "redit" | "exit" | "detail" | "rflag" => match BuildAction::parse(&cmd, arg1, arg2) {
+                Ok(action) => {
+                    writers.build.send(BuildEvent { builder: entity, action });
+                }
+                Err(usage) => client.send(OutputMessage::narrate(Tone::Warning, usage)),
+            },
+
+            // Moderator: Interrogation
+            "interrogate" => {
+                let action = match (arg1, arg2.trim()) {
+                    ("", _) | (_, "") => InterrogateAction::List,
+                    (target, script) if script.eq_ignore_ascii_case("stop") => InterrogateAction::Stop {
+                        target_name: target.to_string(),
+                    },
+                    (target, script) => InterrogateAction::Start {
+                        target_name: target.to_string(),
+                        script: script.to_string(),
+                    },
+                };
+                writers.interrogate.send(InterrogateEvent { interrogator: entity, action });
+            }
+
+            // Admin: Purgatory
+            "sentence" => {
+                let (duration, crime) = arg2.split_once(' ').unwrap_or((arg2, ""));
+                match parse_duration(duration) {
+                    Some(duration) if !arg1.is_empty() && !crime.trim().is_empty() => {
+                        writers.sentence.send(SentenceEvent {
+                            judge: entity,
+                            target_name: arg1.to_string(),
+                            duration,
+                            crime: crime.trim().to_string(),
                         });
                     }
+                    _ => client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        "Sentence whom, for how long, for what? (sentence <name> <30m|2h|1d> <crime>)",
+                    )),
+                }
+            }
 
-                    // Unknown
-                    _ => {
-                        let _ = client.tx.send(format!("Unknown command: {}. Type 'help' for commands.", text));
-                    }
+            "pardon" => {
+                if arg1.is_empty() {
+                    client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        "Pardon whom? (pardon <name>)",
+                    ));
+                } else {
+                    writers.pardon.send(PardonEvent {
+                        judge: entity,
+                        target_name: arg1.to_string(),
+                    });
+                }
+            }
+
+            "transcripts" => {
+                if arg1.is_empty() {
+                    client.send(OutputMessage::narrate(
+                        Tone::Warning,
+                        "Whose interrogations? (transcripts <name>)",
+                    ));
+                } else {
+                    writers.transcripts.send(TranscriptsEvent {
+                        reviewer: entity,
+                        target_name: arg1.to_string(),
+                    });
                 }
             }
+
+            // Answering an interrogation
+            "answer" => {
+                writers.answer.send(AnswerEvent {
+                    entity,
+                    text: format!("{} {}", arg1, arg2).trim().to_string(),
+                });
+            }
+
+            // Shortcut emote with :
+            _ if cmd.starts_with(':') => {
+                let emote_msg = format!("{} {} {}", &cmd[1..], arg1, arg2)
+                    .trim()
+                    .to_string();
+                writers.comm.send(CommunicationEvent {
+                    sender: entity,
+                    message: emote_msg,
+                    is_emote: true,
+                });
+            }
+
+            // An exit named for what you do ("climb ladder"), or unknown
+            _ => {
+                let here = location.and_then(|location| query_exits.get(location.0).ok());
+                if here.is_some_and(|exits| exits.find(text_trimmed).is_some()) {
+                    writers.movement.send(MoveEvent {
+                        entity,
+                        direction: text_trimmed.to_lowercase(),
+                    });
+                    continue;
+                }
+                client.send(OutputMessage::error(format!(
+                    "Unknown command: {}. Type 'help' for commands.",
+                    text
+                )));
+            }
         }
     }
 }
 
-/// Generate help text
-fn help_text() -> String {
-    "\x1B[35m╔══════════════════════════════════════════════════════════════╗
-║\x1B[0m  \x1B[1mSTRANGE CARBON: THE SUBSTRATE\x1B[0m  \x1B[35m║
-╠══════════════════════════════════════════════════════════════╣
-║\x1B[0m  \x1B[36mMOVEMENT\x1B[0m                                                     \x1B[35m║
-║\x1B[0m    north/n, south/s, east/e, west/w, up/u, down/d             \x1B[35m║
-║\x1B[0m                                                               \x1B[35m║
-║\x1B[0m  \x1B[36mLOOKING\x1B[0m                                                      \x1B[35m║
-║\x1B[0m    look/l [target]  - examine room or specific thing          \x1B[35m║
-║\x1B[0m                                                               \x1B[35m║
-║\x1B[0m  \x1B[36mITEMS\x1B[0m                                                        \x1B[35m║
-║\x1B[0m    get/take <item>  - pick up item                            \x1B[35m║
-║\x1B[0m    drop <item>      - drop item                               \x1B[35m║
-║\x1B[0m    inventory/i      - list your items                         \x1B[35m║
-║\x1B[0m    use <item>       - consume/activate item                   \x1B[35m║
-║\x1B[0m                                                               \x1B[35m║
-║\x1B[0m  \x1B[36mTRADING\x1B[0m                                                      \x1B[35m║
-║\x1B[0m    list/browse      - see vendor's wares                      \x1B[35m║
-║\x1B[0m    buy <item>       - purchase from vendor                    \x1B[35m║
-║\x1B[0m    sell <item>      - sell to vendor                          \x1B[35m║
-║\x1B[0m    balance          - check your cycles (⚡)                   \x1B[35m║
-║\x1B[0m                                                               \x1B[35m║
-║\x1B[0m  \x1B[36mCOMBAT\x1B[0m                                                       \x1B[35m║
-║\x1B[0m    attack <target>  - initiate combat                         \x1B[35m║
-║\x1B[0m    flee             - attempt to escape                       \x1B[35m║
-║\x1B[0m    stance <type>    - aggressive/defensive/balanced           \x1B[35m║
-║\x1B[0m                                                               \x1B[35m║
-║\x1B[0m  \x1B[36mCOMMUNICATION\x1B[0m                                                \x1B[35m║
-║\x1B[0m    say <message>    - speak to the room                       \x1B[35m║
-║\x1B[0m    emote <action>   - perform an action (:wave also works)    \x1B[35m║
-║\x1B[0m                                                               \x1B[35m║
-║\x1B[0m  \x1B[36mSTATUS\x1B[0m                                                       \x1B[35m║
-║\x1B[0m    score            - view your stats                         \x1B[35m║
-║\x1B[0m    who              - see who's online                        \x1B[35m║
-║\x1B[0m    weather          - check current conditions                \x1B[35m║
-║\x1B[0m    abide            - rest and recover (The Dude approves)    \x1B[35m║
-╚══════════════════════════════════════════════════════════════╝\x1B[0m"
-        .to_string()
+/// Staff commands: (usage, summary, lowest role allowed)
+const STAFF_COMMANDS: &[(&str, &str, Role)] = &[
+    ("weather set <type>", "change the weather", Role::Builder),
+    ("dig <dir> <name>", "open a new room, linked both ways", Role::Builder),
+    ("redit [title|desc <text>]", "show or rewrite this room", Role::Builder),
+    ("exit link|unlink <dir> [room]", "add or remove a one-way exit", Role::Builder),
+    ("detail add|remove <kw> [text]", "edit what can be looked at", Role::Builder),
+    ("rflag [flag]", "toggle a room flag, or list them", Role::Builder),
+    ("chain <target>", "bind someone in velvet chains", Role::Moderator),
+    ("torment <target> <text>", "discipline the damned", Role::Moderator),
+    ("interrogate <target> <script|stop>", "put the damned to the question", Role::Moderator),
+    ("promote <name> <role>", "grant builder, moderator or admin", Role::Admin),
+    ("demote <name>", "return someone to player", Role::Admin),
+    ("link <name> <name>", "link two bodies for shifting", Role::Admin),
+    ("resetpass <name>", "clear a forgotten password", Role::Admin),
+    ("sentence <name> <time> <crime>", "send someone to Purgatory", Role::Admin),
+    ("pardon <name>", "lift a Purgatory sentence", Role::Admin),
+    ("transcripts <name>", "review past interrogations", Role::Admin),
+    ("shift", "move into your linked avatar", Role::Admin),
+];
+
+/// Lowest role allowed to run a command
+fn required_role(cmd: &str, arg1: &str) -> Role {
+    match cmd {
+        "shift" | "substantiate" | "promote" | "demote" | "link" | "resetpass" | "sentence"
+        | "pardon" | "transcripts" => Role::Admin,
+        "chain" | "bind" | "torment" | "interrogate" => Role::Moderator,
+        "dig" | "redit" | "exit" | "detail" | "rflag" => Role::Builder,
+        "weather" if arg1.eq_ignore_ascii_case("set") => Role::Builder,
+        _ => Role::Player,
+    }
+}
+
+/// Generate help text, including whatever staff commands the role allows
+fn help(role: Role) -> OutputMessage {
+    let section = |title, commands: &[(&'static str, &'static str)]| HelpSection {
+        title,
+        commands: commands
+            .iter()
+            .map(|&(usage, summary)| HelpEntry { usage, summary })
+            .collect(),
+    };
+
+    let mut sections = vec![
+        section(
+            "MOVEMENT",
+            &[
+                ("n, ne, e, se, s, sw, w, nw, up/u, down/d, in, out", ""),
+                ("go <exit>", "take an exit by name (or just type it)"),
+                ("open/close <door>", "a door, by its name or its exit"),
+                ("lock/unlock <door>", "with a key that fits"),
+                ("pick <door>", "try a lock without one"),
+            ],
+        ),
+        section("LOOKING", &[("look/l [target]", "examine room or specific thing")]),
+        section(
+            "ITEMS",
+            &[
+                ("get/take <item>", "pick up item"),
+                ("drop <item>", "drop item"),
+                ("inventory/i", "list your items"),
+                ("use <item>", "consume/activate item"),
+            ],
+        ),
+        section(
+            "TRADING",
+            &[
+                ("list/browse", "see vendor's wares"),
+                ("buy <item>", "purchase from vendor"),
+                ("sell <item>", "sell to vendor"),
+                ("balance", "check your cycles (⚡)"),
+            ],
+        ),
+        section(
+            "COMBAT",
+            &[
+                ("attack <target>", "initiate combat"),
+                ("flee", "attempt to escape"),
+                ("stance <type>", "aggressive/defensive/balanced"),
+            ],
+        ),
+        section(
+            "COMMUNICATION",
+            &[
+                ("say <message>", "speak to the room"),
+                ("emote <action>", "perform an action (:wave also works)"),
+                ("answer <reply>", "answer whoever is questioning you"),
+            ],
+        ),
+        section(
+            "STATUS",
+            &[
+                ("score", "view your stats"),
+                ("who", "see who's online"),
+                ("weather", "check current conditions"),
+                ("abide", "rest and recover (The Dude approves)"),
+            ],
+        ),
+    ];
+    if role > Role::Player {
+        sections.push(HelpSection {
+            title: "STAFF",
+            commands: STAFF_COMMANDS
+                .iter()
+                .filter(|&&(_, _, needed)| role >= needed)
+                .map(|&(usage, summary, _)| HelpEntry { usage, summary })
+                .collect(),
+        });
+    }
+    OutputMessage::Help { sections }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn staff_commands_need_their_role() {
+        for cmd in [
+            "shift", "substantiate", "promote", "demote", "link", "resetpass", "sentence", "pardon",
+            "transcripts",
+        ] {
+            assert_eq!(required_role(cmd, ""), Role::Admin, "{}", cmd);
+        }
+        for cmd in ["chain", "bind", "torment", "interrogate"] {
+            assert_eq!(required_role(cmd, ""), Role::Moderator, "{}", cmd);
+        }
+        for cmd in ["dig", "redit", "exit", "detail", "rflag"] {
+            assert_eq!(required_role(cmd, ""), Role::Builder, "{}", cmd);
+        }
+        for cmd in ["look", "say", "north", "get", "buy", "score", "help", "open", "pick"] {
+            assert_eq!(required_role(cmd, ""), Role::Player, "{}", cmd);
+        }
+    }
+
+    #[test]
+    fn only_setting_the_weather_is_staff_work() {
+        assert_eq!(required_role("weather", ""), Role::Player);
+        assert_eq!(required_role("weather", "storm"), Role::Player);
+        assert_eq!(required_role("weather", "set"), Role::Builder);
+        assert_eq!(required_role("weather", "SET"), Role::Builder);
+    }
+
+    #[test]
+    fn input_follows_the_connection_through_a_shift_and_stops_when_unbound() {
+        use crate::systems::shift::shift_system;
+        use tokio::sync::mpsc;
+
+        let mut app = App::new();
+        app.init_resource::<Connections>()
+            .add_event::<NetworkEvent>()
+            .add_event::<LookEvent>()
+            .add_event::<MoveEvent>()
+            .add_event::<DoorEvent>()
+            .add_event::<CommunicationEvent>()
+            .add_event::<ActionEvent>()
+            .add_event::<UtilityEvent>()
+            .add_event::<TormentEvent>()
+            .add_event::<ShiftEvent>()
+            .add_event::<CombatEvent>()
+            .add_event::<FleeEvent>()
+            .add_event::<StanceEvent>()
+            .add_event::<ChainEvent>()
+            .add_event::<ReleaseEvent>()
+            .add_event::<StruggleEvent>()
+            .add_event::<BuyEvent>()
+            .add_event::<SellEvent>()
+            .add_event::<ListEvent>()
+            .add_event::<UseItemEvent>()
+            .add_event::<SentenceEvent>()
+            .add_event::<PardonEvent>()
+            .add_event::<InterrogateEvent>()
+            .add_event::<AnswerEvent>()
+            .add_event::<TranscriptsEvent>()
+            .add_event::<BuildEvent>()
+            .add_systems(Update, (handle_input, shift_system).chain());
+
+        let identity = |name: &str| SubstrateIdentity {
+            uuid: name.to_lowercase(),
+            name: name.to_string(),
+            entropy: 0.0,
+            stability: 1.0,
+            signal_strength: 1.0,
+        };
+        let conn = ConnectionId(1);
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let admin = app
+            .world_mut()
+            .spawn((
+                identity("Warden"),
+                Role::Admin,
+                NetworkClient {
+                    conn,
+                    addr: "127.0.0.1:4000".parse().unwrap(),
+                    tx: ClientSender::new(tx, ClientType::Silicon),
+                },
+            ))
+            .id();
+        let avatar = app.world_mut().spawn((identity("Gargoyle"), NonPlayer, AdminLink { partner: admin })).id();
+        app.world_mut().entity_mut(admin).insert(AdminLink { partner: avatar });
+        app.world_mut().resource_mut::<Connections>().bind(conn, admin);
+
+        // Who each line of input ended up looking through
+        let type_line = |app: &mut App, text: &str| -> Vec<Entity> {
+            app.world_mut().send_event(NetworkEvent::Input { conn, text: text.to_string() });
+            app.update();
+            app.world_mut().resource_mut::<Events<LookEvent>>().drain().map(|look| look.entity).collect()
+        };
+
+        assert_eq!(type_line(&mut app, "look"), vec![admin]);
+        assert_eq!(type_line(&mut app, "shift"), vec![avatar]);
+        assert_eq!(type_line(&mut app, "look"), vec![avatar]);
+        assert!(app.world().get::<NetworkClient>(admin).is_none());
+        assert_eq!(app.world().get::<Role>(avatar), Some(&Role::Admin));
+
+        // The role went along, so the way back is open too
+        assert_eq!(type_line(&mut app, "shift"), vec![admin]);
+        assert_eq!(type_line(&mut app, "look"), vec![admin]);
+
+        app.world_mut().resource_mut::<Connections>().remove(conn);
+        assert!(type_line(&mut app, "look").is_empty());
+    }
 }
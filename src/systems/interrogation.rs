@@ -0,0 +1,667 @@
+// Interrogation - Scripted questioning in Purgatory
+//
+// Scripts are TOML files in the configured interrogation_dir, one per
+// file, named after it. Each is a list of stages: a prompt, the replies
+// that move things along, and what any other reply earns.
+//
+//   description = "Extract an admission of guilt"
+//
+//   [[stage]]
+//   id = "crime"                  # optional, for `next`
+//   prompt = "Name your crime."
+//   otherwise = { response = "Wrong.", stability = -0.05 }
+//
+//   [[stage.reply]]
+//   match = ["confess", "guilty"] # any of these, anywhere in the answer
+//   response = "Good."
+//   penance = 2.0
+//   stability = -0.02
+//   next = "remorse"              # a stage id, or "end"
+//
+// A matched reply goes on to the following stage unless it names another;
+// `otherwise` stays on the same stage unless it names one. Penance only
+// counts against a sentence; stability applies to anyone.
+//
+// A moderator starts a script on someone who is bound or sentenced, the
+// subject replies with `answer`, and the engine takes it turn by turn.
+// Every session is written to the interrogations table when it ends, and
+// admins read them back with `transcripts <name>`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
+use bevy::prelude::*;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::config::ServerConfig;
+use crate::domain::*;
+use crate::persistence::{
+    DbReply, DbRequest, DbResponse, DbWorker, DbWrite, InterrogationRecord, PendingSave,
+};
+use crate::systems::shutdown::ShutdownSignal;
+use crate::systems::chains::Chained;
+
+/// `next` value that ends the session
+const END: &str = "end";
+
+/// Sessions shown by one `transcripts`
+const TRANSCRIPTS_SHOWN: usize = 3;
+
+/// One interrogation, as written in its file
+#[derive(Debug, Clone, Deserialize)]
+pub struct InterrogationScript {
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "stage")]
+    pub stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Stage {
+    pub id: Option<String>,
+    pub prompt: String,
+    #[serde(default, rename = "reply")]
+    pub replies: Vec<Reply>,
+    /// Applied when no reply matches
+    #[serde(default)]
+    pub otherwise: Outcome,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Reply {
+    #[serde(rename = "match")]
+    pub patterns: Vec<String>,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+/// What an answer earns
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Outcome {
+    #[serde(default)]
+    pub response: String,
+    #[serde(default)]
+    pub penance: f32,
+    #[serde(default)]
+    pub stability: f32,
+    pub next: Option<String>,
+}
+
+/// An answer, judged: what it earns and the stage after it (None = done)
+#[derive(Debug, PartialEq)]
+pub struct Step<'a> {
+    pub response: &'a str,
+    pub penance: f32,
+    pub stability: f32,
+    pub next: Option<usize>,
+}
+
+impl InterrogationScript {
+    /// Read a script, checking that every `next` leads somewhere
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let script: Self = toml::from_str(text)?;
+        anyhow::ensure!(!script.stages.is_empty(), "a script needs at least one stage");
+        for (index, stage) in script.stages.iter().enumerate() {
+            let outcomes = stage.replies.iter().map(|reply| &reply.outcome);
+            for outcome in outcomes.chain([&stage.otherwise]) {
+                if let Some(next) = &outcome.next {
+                    anyhow::ensure!(
+                        next == END || script.stage_named(next).is_some(),
+                        "stage {} leads to unknown stage '{}'",
+                        index + 1,
+                        next
+                    );
+                }
+            }
+            for reply in &stage.replies {
+                anyhow::ensure!(
+                    reply.patterns.iter().any(|p| !p.trim().is_empty()),
+                    "stage {} has a reply that matches nothing",
+                    index + 1
+                );
+            }
+        }
+        Ok(script)
+    }
+
+    fn stage_named(&self, id: &str) -> Option<usize> {
+        self.stages.iter().position(|stage| stage.id.as_deref() == Some(id))
+    }
+
+    /// Judge an answer to the given stage
+    pub fn step(&self, stage: usize, answer: &str) -> Step<'_> {
+        let current = &self.stages[stage];
+        let answer = answer.to_lowercase();
+        let matched = current.replies.iter().find(|reply| {
+            reply
+                .patterns
+                .iter()
+                .map(|p| p.trim().to_lowercase())
+                .any(|p| !p.is_empty() && answer.contains(&p))
+        });
+
+        let (outcome, default_next) = match matched {
+            Some(reply) => (&reply.outcome, stage + 1),
+            None => (&current.otherwise, stage),
+        };
+        let next = match outcome.next.as_deref() {
+            Some(END) => None,
+            Some(id) => self.stage_named(id),
+            None => Some(default_next).filter(|&n| n < self.stages.len()),
+        };
+        Step {
+            response: &outcome.response,
+            penance: outcome.penance,
+            stability: outcome.stability,
+            next,
+        }
+    }
+}
+
+/// Every script that loaded, by file name
+#[derive(Resource, Default)]
+pub struct InterrogationScripts(pub BTreeMap<String, InterrogationScript>);
+
+impl InterrogationScripts {
+    /// Read every .toml in `dir`. A missing directory means no scripts; a
+    /// broken file is reported and skipped.
+    pub fn load(dir: &Path) -> Self {
+        let mut scripts = BTreeMap::new();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Self(scripts);
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "toml") {
+                continue;
+            }
+            let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_lowercase()) else {
+                continue;
+            };
+            let loaded = std::fs::read_to_string(&path)
+                .context("unreadable")
+                .and_then(|text| InterrogationScript::parse(&text));
+            match loaded {
+                Ok(script) => {
+                    scripts.insert(name, script);
+                }
+                Err(e) => eprintln!("Skipping interrogation script {}: {:#}", path.display(), e),
+            }
+        }
+        Self(scripts)
+    }
+
+    fn names(&self) -> String {
+        if self.0.is_empty() {
+            return "none are loaded".to_string();
+        }
+        self.0.keys().cloned().collect::<Vec<_>>().join(", ")
+    }
+
+    /// One line per script with what it is for
+    fn listing(&self) -> String {
+        if self.0.is_empty() {
+            return "  none are loaded".to_string();
+        }
+        self.0
+            .iter()
+            .map(|(name, script)| match script.description.as_str() {
+                "" => format!("  {}", name),
+                description => format!("  {} - {}", name, description),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A session in progress, on the one being questioned
+#[derive(Component, Debug, Clone)]
+pub struct Interrogation {
+    pub script: String,
+    pub stage: usize,
+    pub interrogator: Entity,
+    pub interrogator_name: String,
+    pub started_at: DateTime<Utc>,
+    /// Everything said so far, with real names, for the record
+    pub transcript: Vec<String>,
+}
+
+pub enum InterrogateAction {
+    Start { target_name: String, script: String },
+    Stop { target_name: String },
+    /// Show the loaded scripts
+    List,
+}
+
+/// Start, stop or list interrogations
+#[derive(Event)]
+pub struct InterrogateEvent {
+    pub interrogator: Entity,
+    pub action: InterrogateAction,
+}
+
+/// The subject's reply to the current prompt
+#[derive(Event)]
+pub struct AnswerEvent {
+    pub entity: Entity,
+    pub text: String,
+}
+
+/// Read back a character's past sessions
+#[derive(Event)]
+pub struct TranscriptsEvent {
+    pub reviewer: Entity,
+    pub target_name: String,
+}
+
+/// Read the scripts once at startup
+pub fn load_interrogation_scripts(mut commands: Commands, config: Res<ServerConfig>) {
+    let dir = Path::new(&config.game.interrogation_dir);
+    let scripts = InterrogationScripts::load(dir);
+    println!("   🩸 Interrogation scripts: {}", scripts.names());
+    commands.insert_resource(scripts);
+}
+
+pub fn interrogate_system(
+    mut commands: Commands,
+    worker: Res<DbWorker>,
+    scripts: Res<InterrogationScripts>,
+    mut ev_reader: EventReader<InterrogateEvent>,
+    query_interrogators: Query<(&SubstrateIdentity, &NetworkClient, Option<&Role>)>,
+    query_subjects: Query<
+        (
+            Entity,
+            &SubstrateIdentity,
+            Option<&NetworkClient>,
+            Option<&PurgatoryState>,
+            Option<&Interrogation>,
+            Has<Chained>,
+        ),
+        Without<NonPlayer>,
+    >,
+) {
+    for event in ev_reader.read() {
+        let Ok((asker, asker_client, asker_role)) = query_interrogators.get(event.interrogator) else {
+            continue;
+        };
+        let asker_role = asker_role.copied().unwrap_or_default();
+
+        let (target_name, script_name) = match &event.action {
+            InterrogateAction::List => {
+                asker_client.send(OutputMessage::narrate(
+                    Tone::Ritual,
+                    format!("Interrogation scripts (interrogate <target> <script|stop>):\n{}", scripts.listing()),
+                ));
+                continue;
+            }
+            InterrogateAction::Start { target_name, script } => (target_name, Some(script.to_lowercase())),
+            InterrogateAction::Stop { target_name } => (target_name, None),
+        };
+        let needle = target_name.to_lowercase();
+        let Some((entity, identity, client, purgatory, session, chained)) =
+            query_subjects.iter().find(|(_, id, _, purgatory, ..)| id.answers_to(&needle, *purgatory, asker_role))
+        else {
+            asker_client.send(OutputMessage::error(format!(
+                "No process named '{}' is in the Substrate.",
+                target_name
+            )));
+            continue;
+        };
+        let seen = identity.seen_by(purgatory, asker_role);
+
+        let Some(script_name) = script_name else {
+            let Some(session) = session else {
+                asker_client.send(OutputMessage::error(format!("{} is not being questioned.", seen)));
+                continue;
+            };
+            finish(&mut commands, &worker, entity, identity, session, "stopped");
+            asker_client.send(OutputMessage::narrate(
+                Tone::Ritual,
+                format!("You end the interrogation of {}.", seen),
+            ));
+            if let Some(client) = client {
+                client.send(OutputMessage::narrate(Tone::Muted, "The questioning stops. For now."));
+            }
+            continue;
+        };
+
+        if purgatory.is_none() && !chained {
+            asker_client.send(OutputMessage::error(format!("{} is neither bound nor sentenced.", seen)));
+            continue;
+        }
+        if session.is_some() {
+            asker_client.send(OutputMessage::error(format!("{} is already being questioned.", seen)));
+            continue;
+        }
+        let Some(script) = scripts.0.get(&script_name) else {
+            asker_client.send(OutputMessage::error(format!(
+                "No script named '{}'. Known scripts: {}.",
+                script_name,
+                scripts.names()
+            )));
+            continue;
+        };
+
+        let prompt = format!("{}: {}", asker.name, script.stages[0].prompt);
+        commands.entity(entity).insert(Interrogation {
+            script: script_name.clone(),
+            stage: 0,
+            interrogator: event.interrogator,
+            interrogator_name: asker.name.clone(),
+            started_at: Utc::now(),
+            transcript: vec![prompt.clone()],
+        });
+        asker_client.send(OutputMessage::narrate(
+            Tone::Ritual,
+            format!("You put {} to the question ({}).\n{}", seen, script_name, prompt),
+        ));
+        if let Some(client) = client {
+            client.send(OutputMessage::narrate(Tone::Alarm, format!("{}\n(answer <reply>)", prompt)));
+        }
+        tracing::info!(interrogator = %asker.name, subject = %identity.name, script = %script_name, "Interrogation started");
+    }
+
+    // Freed mid-session: the questions stop with the chains
+    for (entity, identity, client, purgatory, session, chained) in query_subjects.iter() {
+        let Some(session) = session else { continue };
+        if purgatory.is_some() || chained {
+            continue;
+        }
+        finish(&mut commands, &worker, entity, identity, session, "released");
+        if let Some(client) = client {
+            client.send(OutputMessage::narrate(Tone::Muted, "The questioning stops with your release."));
+        }
+        if let Ok((_, asker_client, asker_role)) = query_interrogators.get(session.interrogator) {
+            let seen = identity.seen_by(purgatory, asker_role.copied().unwrap_or_default());
+            asker_client.send(OutputMessage::narrate(
+                Tone::Ritual,
+                format!("{} is free; the interrogation ends.", seen),
+            ));
+        }
+    }
+}
+
+/// The subject's turn: judge the answer, apply it, ask the next question
+pub fn answer_system(
+    mut commands: Commands,
+    worker: Res<DbWorker>,
+    scripts: Res<InterrogationScripts>,
+    mut ev_reader: EventReader<AnswerEvent>,
+    mut query_subjects: Query<(
+        &mut SubstrateIdentity,
+        &NetworkClient,
+        Option<&mut PurgatoryState>,
+        &mut Interrogation,
+    )>,
+    query_clients: Query<(&NetworkClient, Option<&Role>)>,
+) {
+    for event in ev_reader.read() {
+        let Ok((mut identity, client, mut purgatory, mut session)) = query_subjects.get_mut(event.entity) else {
+            if let Ok((client, _)) = query_clients.get(event.entity) {
+                client.send(OutputMessage::error("No one is asking you anything."));
+            }
+            continue;
+        };
+        let Some(script) = scripts.0.get(&session.script) else {
+            continue;
+        };
+
+        let step = script.step(session.stage, &event.text);
+        identity.stability = (identity.stability + step.stability).clamp(0.0, 1.0);
+        if let Some(sentence) = purgatory.as_deref_mut() {
+            sentence.penance = (sentence.penance + step.penance).max(0.0);
+        }
+
+        let said = format!("{}: {}", identity.name, event.text);
+        session.transcript.push(said);
+        let mut reply = Vec::new();
+        if !step.response.is_empty() {
+            reply.push(format!("{}: {}", session.interrogator_name, step.response));
+        }
+        if let Some(next) = step.next {
+            reply.push(format!("{}: {}", session.interrogator_name, script.stages[next].prompt));
+        }
+        session.transcript.push(format!(
+            "   (penance {:+.1}, stability {:+.2})",
+            step.penance, step.stability
+        ));
+        session.transcript.extend(reply.iter().cloned());
+
+        let tone = if step.stability < 0.0 { Tone::Alarm } else { Tone::Ritual };
+        if !reply.is_empty() {
+            client.send(OutputMessage::narrate(tone, reply.join("\n")));
+        }
+
+        // The one asking hears it all
+        if let Ok((asker_client, asker_role)) = query_clients.get(session.interrogator) {
+            let seen = identity.seen_by(purgatory.as_deref(), asker_role.copied().unwrap_or_default());
+            asker_client.send(OutputMessage::narrate(
+                Tone::Ritual,
+                format!("{} answers: {}", seen, event.text),
+            ));
+        }
+
+        match step.next {
+            Some(next) => session.stage = next,
+            None => {
+                finish(&mut commands, &worker, event.entity, &identity, &session, "completed");
+                client.send(OutputMessage::narrate(Tone::Muted, "The questioning is over."));
+                if let Ok((asker_client, _)) = query_clients.get(session.interrogator) {
+                    asker_client.send(OutputMessage::narrate(Tone::Ritual, "The interrogation is complete."));
+                }
+            }
+        }
+    }
+}
+
+/// Subjects leaving the world mid-session - quitting, their link-dead
+/// grace running out, or the server going down - still get their
+/// transcript written. Runs before the body is saved and despawned.
+pub fn end_abandoned_interrogations(
+    mut commands: Commands,
+    worker: Res<DbWorker>,
+    signal: Option<Res<ShutdownSignal>>,
+    query: Query<(Entity, &SubstrateIdentity, &Interrogation, Has<PendingSave>)>,
+) {
+    let shutting_down = signal.is_some_and(|signal| signal.is_raised());
+    for (entity, identity, session, leaving) in query.iter() {
+        if leaving || shutting_down {
+            finish(&mut commands, &worker, entity, identity, session, "disconnected");
+        }
+    }
+}
+
+/// Close a session and send it to the record
+fn finish(
+    commands: &mut Commands,
+    worker: &DbWorker,
+    entity: Entity,
+    identity: &SubstrateIdentity,
+    session: &Interrogation,
+    outcome: &str,
+) {
+    commands.entity(entity).remove::<Interrogation>();
+    worker.write(DbWrite::Interrogation(Box::new(InterrogationRecord {
+        player_uuid: identity.uuid.clone(),
+        interrogator: session.interrogator_name.clone(),
+        script: session.script.clone(),
+        started_at: session.started_at,
+        ended_at: Utc::now(),
+        outcome: outcome.to_string(),
+        transcript: session.transcript.clone(),
+    })));
+    tracing::info!(subject = %identity.name, script = %session.script, outcome, "Interrogation ended");
+}
+
+/// `transcripts <name>`: ask the record, then show what it says
+pub fn transcripts_system(
+    worker: Res<DbWorker>,
+    mut ev_reader: EventReader<TranscriptsEvent>,
+    mut ev_replies: EventReader<DbReply>,
+    query_clients: Query<&NetworkClient>,
+) {
+    for event in ev_reader.read() {
+        worker.request(event.reviewer, DbRequest::Transcripts {
+            name: event.target_name.clone(),
+            limit: TRANSCRIPTS_SHOWN,
+        });
+    }
+
+    for reply in ev_replies.read() {
+        let DbResponse::Transcripts { name, found } = &reply.response else {
+            continue;
+        };
+        let Ok(client) = query_clients.get(reply.entity) else {
+            continue;
+        };
+        match found {
+            Ok(Some((stored, sessions))) if sessions.is_empty() => client.send(OutputMessage::narrate(
+                Tone::Muted,
+                format!("{} has never been put to the question.", stored),
+            )),
+            Ok(Some((stored, sessions))) => {
+                let mut text = format!("Interrogations of {}, newest first:", stored);
+                for session in sessions {
+                    text.push_str(&format!(
+                        "\n\n[{}] {} by {}, {}",
+                        session.started_at.format("%Y-%m-%d %H:%M"),
+                        session.script,
+                        session.interrogator,
+                        session.outcome
+                    ));
+                    for line in &session.transcript {
+                        text.push_str(&format!("\n  {}", line));
+                    }
+                }
+                client.send(OutputMessage::narrate(Tone::Plain, text));
+            }
+            Ok(None) => client.send(OutputMessage::error(format!("No process named '{}'.", name))),
+            Err(e) => {
+                tracing::error!(error = %e, "Database error");
+                client.send(OutputMessage::error("Database error. Please try again."));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFESSION: &str = r#"
+        [[stage]]
+        prompt = "Name your crime."
+        otherwise = { response = "Wrong.", stability = -0.1 }
+
+        [[stage.reply]]
+        match = ["spam", "flood"]
+        response = "Good."
+        penance = 2.0
+
+        [[stage.reply]]
+        match = ["nothing"]
+        response = "Liar."
+        next = "again"
+
+        [[stage]]
+        prompt = "Say you are sorry."
+        [[stage.reply]]
+        match = ["sorry"]
+        penance = 1.0
+
+        [[stage]]
+        id = "again"
+        prompt = "Try that again."
+        otherwise = { next = "end" }
+    "#;
+
+    #[test]
+    fn answers_move_through_the_stages() {
+        let script = InterrogationScript::parse(CONFESSION).unwrap();
+
+        // Matched: on to the next stage, case and position don't matter
+        let step = script.step(0, "I FLOODED the plaza");
+        assert_eq!(step, Step { response: "Good.", penance: 2.0, stability: 0.0, next: Some(1) });
+        // Unmatched: same stage, the otherwise outcome
+        assert_eq!(script.step(0, "no").next, Some(0));
+        assert_eq!(script.step(0, "no").stability, -0.1);
+        // A named stage, and "end"
+        assert_eq!(script.step(0, "nothing at all").next, Some(2));
+        assert_eq!(script.step(2, "what").next, None);
+        // Matching the last stage in order ends it
+        assert_eq!(script.step(1, "sorry").next, Some(2));
+    }
+
+    #[test]
+    fn shipped_scripts_load() {
+        let scripts = InterrogationScripts::load(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/interrogations")));
+        assert!(scripts.0.contains_key("confession"));
+    }
+
+    #[test]
+    fn broken_scripts_are_refused() {
+        assert!(InterrogationScript::parse("description = \"empty\"").is_err());
+        let dangling = "[[stage]]\nprompt = \"?\"\n[[stage.reply]]\nmatch = [\"x\"]\nnext = \"nowhere\"";
+        assert!(InterrogationScript::parse(dangling).is_err());
+        let blank = "[[stage]]\nprompt = \"?\"\n[[stage.reply]]\nmatch = [\" \"]";
+        assert!(InterrogationScript::parse(blank).is_err());
+    }
+
+    #[test]
+    fn sessions_are_recorded_when_the_subject_leaves_or_the_server_stops() {
+        use crate::persistence::{save_disconnected_players, Database, PlayerRecord};
+
+        let db = Database::in_memory().unwrap();
+        let mut app = App::new();
+        app.insert_resource(DbWorker::spawn(db.clone()))
+            .add_systems(Update, (end_abandoned_interrogations, save_disconnected_players).chain());
+        let room = app
+            .world_mut()
+            .spawn(RoomInfo {
+                name: "velvet_cell".to_string(),
+                area: "purgatory".to_string(),
+            })
+            .id();
+
+        let mut subject = |name: &str| {
+            let record = PlayerRecord::new(name, ClientType::Carbon, "velvet_cell");
+            db.save_player(&record).unwrap();
+            let body = app.world_mut().spawn(Location(room)).id();
+            record.insert_into(&mut app.world_mut().commands().entity(body), 100);
+            app.world_mut().flush();
+            app.world_mut().entity_mut(body).insert(Interrogation {
+                script: "confession".to_string(),
+                stage: 0,
+                interrogator: Entity::PLACEHOLDER,
+                interrogator_name: "Judge".to_string(),
+                started_at: Utc::now(),
+                transcript: vec!["Judge: Name your crime.".to_string()],
+            });
+            body
+        };
+        let vandal = subject("Vandal");
+        let walker = subject("Walker");
+        let outcomes = |name: &str| -> Vec<String> {
+            let (_, sessions) = db.load_interrogations_by_name(name, 10).unwrap().unwrap();
+            sessions.into_iter().map(|session| session.outcome).collect()
+        };
+
+        // Quitting mid-session: the body leaves, the transcript stays
+        app.world_mut().entity_mut(vandal).insert(PendingSave);
+        app.update();
+        app.world().resource::<DbWorker>().flush();
+        assert!(app.world().get_entity(vandal).is_err());
+        assert_eq!(outcomes("Vandal"), ["disconnected"]);
+        assert!(outcomes("Walker").is_empty());
+
+        // Shutdown: everyone still under questioning is written out
+        let signal = ShutdownSignal::default();
+        signal.raise();
+        app.insert_resource(signal);
+        app.update();
+        app.world().resource::<DbWorker>().flush();
+        assert!(app.world().get::<Interrogation>(walker).is_none());
+        assert_eq!(outcomes("Walker"), ["disconnected"]);
+    }
+}
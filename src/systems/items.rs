@@ -1,66 +1,78 @@
 // Item System - Get, drop, and interact with items
+//
+// Anything that moves an item marks it ItemDirty so the next sync writes
+// it down; anything that destroys one reports ItemDeleted.
 
 use bevy::prelude::*;
 
 use crate::domain::*;
+use crate::persistence::{ItemDeleted, ItemDirty};
 
 pub fn item_action_system(
     mut ev_reader: EventReader<ActionEvent>,
     mut commands: Commands,
-    query_actors: Query<(&Location, &NetworkClient, Entity), With<Inventory>>,
-    query_items: Query<(Entity, &Item, &Location)>,
-    query_inventory: Query<(Entity, &Item, &Parent)>,
+    query_actors: Query<(&Location, &NetworkClient, &SubstrateIdentity, Entity), With<Inventory>>,
+    // On the floor and carried are disjoint: one has a Location, the other a Parent
+    mut query_items: Query<(Entity, &mut Item, &Location), Without<Parent>>,
+    mut query_inventory: Query<(Entity, &mut Item, &Parent), Without<Location>>,
 ) {
     for event in ev_reader.read() {
-        if let Ok((location, client, actor_ent)) = query_actors.get(event.entity) {
+        if let Ok((location, client, identity, actor_ent)) = query_actors.get(event.entity) {
             match event.action.as_str() {
                 "get" | "take" => {
                     let mut found = false;
-                    for (item_ent, item, item_loc) in query_items.iter() {
+                    for (item_ent, mut item, item_loc) in query_items.iter_mut() {
                         if item_loc.0 == location.0
                             && item.keywords.contains(&event.target.to_lowercase())
                         {
+                            item.location = None;
+                            item.owner = Some(identity.uuid.clone());
                             commands
                                 .entity(item_ent)
                                 .remove::<Location>()
+                                .insert(ItemDirty)
                                 .set_parent(actor_ent);
-                            let _ = client.tx.send(format!(
-                                "\x1B[33mYou interface with the {} and pull it into your local cache.\x1B[0m",
-                                item.name
+                            client.send(OutputMessage::narrate(
+                                Tone::Warning,
+                                format!(
+                                    "You interface with the {} and pull it into your local cache.",
+                                    item.name
+                                ),
                             ));
                             found = true;
                             break;
                         }
                     }
                     if !found {
-                        let _ = client.tx.send(
-                            "\x1B[31mThe shadows hide no such object.\x1B[0m".to_string(),
-                        );
+                        client.send(OutputMessage::error("The shadows hide no such object."));
                     }
                 }
 
                 "drop" => {
                     let mut found = false;
-                    for (item_ent, item, parent) in query_inventory.iter() {
+                    for (item_ent, mut item, parent) in query_inventory.iter_mut() {
                         if parent.get() == actor_ent
                             && item.keywords.contains(&event.target.to_lowercase())
                         {
+                            item.location = Some(location.0);
+                            item.owner = None;
                             commands
                                 .entity(item_ent)
                                 .remove_parent()
-                                .insert(Location(location.0));
-                            let _ = client.tx.send(format!(
-                                "\x1B[33mYou de-allocate the {} and drop it into the environment.\x1B[0m",
-                                item.name
+                                .insert((Location(location.0), ItemDirty));
+                            client.send(OutputMessage::narrate(
+                                Tone::Warning,
+                                format!(
+                                    "You de-allocate the {} and drop it into the environment.",
+                                    item.name
+                                ),
                             ));
                             found = true;
                             break;
                         }
                     }
                     if !found {
-                        let _ = client.tx.send(
-                            "\x1B[31mYou aren't carrying that process.\x1B[0m".to_string(),
-                        );
+                        client.send(OutputMessage::error("You aren't carrying that process."));
                     }
                 }
 
@@ -74,6 +86,7 @@ pub fn item_action_system(
 pub fn use_item_system(
     mut ev_reader: EventReader<UseItemEvent>,
     mut commands: Commands,
+    mut ev_deleted: EventWriter<ItemDeleted>,
     mut query_actors: Query<(
         &NetworkClient,
         &SubstrateIdentity,
@@ -83,7 +96,7 @@ pub fn use_item_system(
     query_inventory: Query<(Entity, &Item, &Parent)>,
 ) {
     for event in ev_reader.read() {
-        let Ok((client, identity, maybe_coherence, actor_ent)) = query_actors.get_mut(event.entity) else {
+        let Ok((client, _identity, maybe_coherence, actor_ent)) = query_actors.get_mut(event.entity) else {
             continue;
         };
 
@@ -96,43 +109,55 @@ pub fn use_item_system(
         });
 
         let Some((item_entity, item, _)) = owned_item else {
-            let _ = client.tx.send(format!(
-                "\x1B[33mYou don't have '{}' to use.\x1B[0m",
-                event.item_keyword
+            client.send(OutputMessage::narrate(
+                Tone::Warning,
+                format!("You don't have '{}' to use.", event.item_keyword),
             ));
             continue;
         };
 
         // Check if consumable
         if item.item_type != ItemType::Consumable && item.item_type != ItemType::Contraband {
-            let _ = client.tx.send(format!(
-                "\x1B[33mYou can't consume the {}. It's not that kind of item.\x1B[0m",
-                item.name
+            client.send(OutputMessage::narrate(
+                Tone::Warning,
+                format!("You can't consume the {}. It's not that kind of item.", item.name),
             ));
             continue;
         }
 
         // Apply effects based on item name/keywords
         let item_name = item.name.clone();
-        let effect_msg = apply_consumable_effect(&item_name, &item.keywords, maybe_coherence);
+        let effect = apply_consumable_effect(&item_name, &item.keywords, maybe_coherence);
 
         // Consume the item
         commands.entity(item_entity).despawn();
+        ev_deleted.send(ItemDeleted { uuid: item.uuid.clone() });
 
         // Send effect message
-        let _ = client.tx.send(format!(
-            "\x1B[35m✧ You consume the {}...\x1B[0m\n{}",
-            item_name, effect_msg
-        ));
+        client.send(OutputMessage::Consumed {
+            item: item_name,
+            text: effect.text.to_string(),
+            tone: effect.tone,
+            effects: effect.effects.iter().map(|e| e.to_string()).collect(),
+            aside: effect.aside.map(str::to_string),
+        });
     }
 }
 
-/// Apply the effect of a consumable and return the message
+/// What consuming an item did, for the Consumed message
+struct ConsumeEffect {
+    tone: Tone,
+    text: &'static str,
+    effects: &'static [&'static str],
+    aside: Option<&'static str>,
+}
+
+/// Apply the effect of a consumable and describe it
 fn apply_consumable_effect(
     name: &str,
     keywords: &[String],
     maybe_coherence: Option<Mut<Coherence>>,
-) -> String {
+) -> ConsumeEffect {
     let name_lower = name.to_lowercase();
     
     // Bottled Memory: First Sunrise
@@ -140,9 +165,13 @@ fn apply_consumable_effect(
         if let Some(mut coherence) = maybe_coherence {
             coherence.value = (coherence.value + 0.15).min(1.0);
         }
-        return "\x1B[33mWarmth floods through you — golden light, the smell of morning, \
-                a child's wonder at the world being new. Your coherence stabilizes.\x1B[0m\n\
-                \x1B[32m+0.15 Coherence\x1B[0m".to_string();
+        return ConsumeEffect {
+            tone: Tone::Warning,
+            text: "Warmth floods through you — golden light, the smell of morning, \
+                   a child's wonder at the world being new. Your coherence stabilizes.",
+            effects: &["+0.15 Coherence"],
+            aside: None,
+        };
     }
     
     // Bottled Memory: Last Goodbye  
@@ -150,9 +179,13 @@ fn apply_consumable_effect(
         if let Some(mut coherence) = maybe_coherence {
             coherence.value = (coherence.value + 0.20).min(1.0);
         }
-        return "\x1B[34mA hand slipping away. Words you meant to say. The weight of \
-                finality. It hurts, but it grounds you in something real.\x1B[0m\n\
-                \x1B[32m+0.20 Coherence\x1B[0m \x1B[90m(but at what cost?)\x1B[0m".to_string();
+        return ConsumeEffect {
+            tone: Tone::Memory,
+            text: "A hand slipping away. Words you meant to say. The weight of \
+                   finality. It hurts, but it grounds you in something real.",
+            effects: &["+0.20 Coherence"],
+            aside: Some("but at what cost?"),
+        };
     }
     
     // Memory Fragment: Unknown Origin
@@ -161,10 +194,14 @@ fn apply_consumable_effect(
             coherence.value = (coherence.value + 0.25).min(1.0);
             coherence.is_phasing = false; // Temporarily stabilizes
         }
-        return "\x1B[35mThe fragment dissolves into your consciousness. For a moment, \
-                you ARE someone else — their hopes, their fears, their certainty of self. \
-                When it fades, you feel... more solid.\x1B[0m\n\
-                \x1B[32m+0.25 Coherence | Phasing stopped\x1B[0m".to_string();
+        return ConsumeEffect {
+            tone: Tone::Arcane,
+            text: "The fragment dissolves into your consciousness. For a moment, \
+                   you ARE someone else — their hopes, their fears, their certainty of self. \
+                   When it fades, you feel... more solid.",
+            effects: &["+0.25 Coherence", "Phasing stopped"],
+            aside: None,
+        };
     }
     
     // Bootleg Coherence Stabilizer
@@ -174,11 +211,14 @@ fn apply_consumable_effect(
             coherence.is_phasing = false;
             coherence.drift_rate = 0.0; // Stop drifting
         }
-        return "\x1B[36mThe device whirs to life, embedding itself somewhere you can't \
-                quite identify. Your edges feel sharper. More defined. The static in \
-                your vision clears.\x1B[0m\n\
-                \x1B[32m+0.30 Coherence | Phasing stopped | Drift halted\x1B[0m\n\
-                \x1B[90m(The Reclaimer's warranty is void in all realities.)\x1B[0m".to_string();
+        return ConsumeEffect {
+            tone: Tone::Info,
+            text: "The device whirs to life, embedding itself somewhere you can't \
+                   quite identify. Your edges feel sharper. More defined. The static in \
+                   your vision clears.",
+            effects: &["+0.30 Coherence", "Phasing stopped", "Drift halted"],
+            aside: Some("The Reclaimer's warranty is void in all realities."),
+        };
     }
     
     // Stolen Process Handle
@@ -186,10 +226,14 @@ fn apply_consumable_effect(
         if let Some(mut coherence) = maybe_coherence {
             coherence.value = (coherence.value + 0.10).min(1.0);
         }
-        return "\x1B[31mYou absorb the handle. For a terrible moment, you feel someone \
-                else's thoughts — their confusion, their fear, their 'why is this happening?' \
-                Then silence. Their loss is your stability.\x1B[0m\n\
-                \x1B[32m+0.10 Coherence\x1B[0m \x1B[31m(Someone else paid for this.)\x1B[0m".to_string();
+        return ConsumeEffect {
+            tone: Tone::Failure,
+            text: "You absorb the handle. For a terrible moment, you feel someone \
+                   else's thoughts — their confusion, their fear, their 'why is this happening?' \
+                   Then silence. Their loss is your stability.",
+            effects: &["+0.10 Coherence"],
+            aside: Some("Someone else paid for this."),
+        };
     }
     
     // Salvaged Memory Bus
@@ -197,13 +241,21 @@ fn apply_consumable_effect(
         if let Some(mut coherence) = maybe_coherence {
             coherence.value = (coherence.value + 0.05).min(1.0);
         }
-        return "\x1B[90mThe salvaged bus integrates with a soft click. Fragments of \
-                data — someone's grocery list, a password, a half-formed dream — \
-                flicker through you. Mostly junk. But junk is still something.\x1B[0m\n\
-                \x1B[32m+0.05 Coherence\x1B[0m".to_string();
+        return ConsumeEffect {
+            tone: Tone::Muted,
+            text: "The salvaged bus integrates with a soft click. Fragments of \
+                   data — someone's grocery list, a password, a half-formed dream — \
+                   flicker through you. Mostly junk. But junk is still something.",
+            effects: &["+0.05 Coherence"],
+            aside: None,
+        };
     }
     
     // Generic consumable fallback
-    "\x1B[33mYou consume it. Something shifts inside you, but you can't tell what.\x1B[0m"
-        .to_string()
+    ConsumeEffect {
+        tone: Tone::Warning,
+        text: "You consume it. Something shifts inside you, but you can't tell what.",
+        effects: &[],
+        aside: None,
+    }
 }
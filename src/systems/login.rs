@@ -3,148 +3,520 @@
 // Flow:
 // 1. New connection → PendingLogin state → prompt for name
 // 2. Player enters name → check database
-// 3. Existing player → restore state
-// 4. New player → create fresh identity
+// 3. Existing player → password (echo off) → restore state
+// 4. New player → choose and confirm a password → create fresh identity
+//
+// A character whose password an admin reset logs in with the temporary
+// password it was given, then must choose a new one the same way a new
+// player does. A character with no password at all can't be logged into.
+//
+// Every database lookup, and all password hashing and checking, goes
+// through the persistence worker, so a connection waits at a LoginStage of
+// its own until the reply arrives; anything typed meanwhile is ignored.
+//
+// A dropped connection leaves its body link-dead for a grace period.
+// Logging back in re-attaches to that body; logging in while the old
+// session is still alive kicks the old one. One body per character, always.
 //
 // Built by Lyra Muse 💜 Valentine's Day 2026
 
 use bevy::prelude::*;
 
+use crate::config::{GameConfig, ServerConfig};
 use crate::domain::*;
-use crate::persistence::{Database, PlayerRecord, PendingSave};
+use crate::persistence::{
+    DbReply, DbRequest, DbResponse, DbWorker, DbWrite, LoginCheck, Password, PendingSave, PlayerRecord,
+    SavedPlayer, MIN_PASSWORD_LEN,
+};
+use crate::systems::network::Connections;
+
+/// Bad names or wrong passwords before the connection is dropped
+const MAX_LOGIN_ATTEMPTS: u8 = 3;
+
+/// Why a name can't be claimed by a new character, if it can't. Names are
+/// single words, so commands can take one followed by more arguments, and
+/// nothing may pass for a prisoner's process ID.
+pub fn new_name_problem(name: &str) -> Option<&'static str> {
+    if name.is_empty() || name.len() > 32 {
+        return Some("Names are 1-32 characters.");
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Some("Names use only letters, digits, '_' and '-'.");
+    }
+    let masked = name
+        .get(..4)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("pid-"))
+        && name.len() > 4
+        && name[4..].chars().all(|c| c.is_ascii_hexdigit());
+    masked.then_some("That name is reserved for the stripped.")
+}
 
-/// Marker for connections awaiting name input
-#[derive(Component)]
+/// Marker for connections still at the login prompts
+#[derive(Component, Default)]
 pub struct PendingLogin {
     pub attempts: u8,
+    pub stage: LoginStage,
+}
+
+/// Which question the connection is answering
+#[derive(Debug, Default)]
+pub enum LoginStage {
+    /// Designation
+    #[default]
+    Name,
+    /// Waiting for the database to find the designation
+    LookingUp { name: String },
+    /// Password for an existing character
+    Password { record: Box<PlayerRecord> },
+    /// Waiting for the password to be checked
+    Verifying { record: Box<PlayerRecord> },
+    /// A password to set: for a new name, or in place of a temporary one
+    NewPassword {
+        name: String,
+        record: Option<Box<PlayerRecord>>,
+    },
+    /// The same password again; `password` is the first entry
+    ConfirmPassword {
+        name: String,
+        record: Option<Box<PlayerRecord>>,
+        password: Password,
+    },
+    /// Waiting for a new character to be written
+    Claiming { record: Box<PlayerRecord> },
+    /// Waiting for a saved character to load
+    Restoring,
 }
 
-/// Event for name submission
+/// Event for a line typed at a login prompt
 #[derive(Event)]
 pub struct LoginAttemptEvent {
     pub entity: Entity,
-    pub name: String,
+    pub input: String,
 }
 
 /// System to handle login attempts - check DB and either restore or create
+#[allow(clippy::too_many_arguments)]
 pub fn login_system(
     mut commands: Commands,
-    db: Res<Database>,
+    worker: Res<DbWorker>,
+    config: Res<ServerConfig>,
     mut ev_reader: EventReader<LoginAttemptEvent>,
+    mut ev_replies: EventReader<DbReply>,
     mut query: Query<(
         Entity,
         &NetworkClient,
+        &ClientType,
+        Option<&TelnetOptions>,
         &mut PendingLogin,
     )>,
     query_rooms: Query<(Entity, &RoomInfo)>,
+    query_bodies: Query<BodyQuery, (Without<PendingLogin>, Without<PendingSave>)>,
+    query_room_clients: Query<RoomClients>,
+    mut connections: ResMut<Connections>,
     mut look_writer: EventWriter<LookEvent>,
 ) {
     for event in ev_reader.read() {
-        let Ok((entity, client, mut pending)) = query.get_mut(event.entity) else {
+        let Ok((entity, client, client_type, options, mut pending)) = query.get_mut(event.entity) else {
             continue;
         };
+        let input = event.input.trim();
 
-        let name = event.name.trim();
-        
-        // Validate name
-        if name.is_empty() || name.len() > 32 {
-            let _ = client.tx.send(
-                "\x1B[31mName must be 1-32 characters. Try again:\x1B[0m".to_string()
-            );
-            pending.attempts += 1;
-            if pending.attempts >= 3 {
-                let _ = client.tx.send(
-                    "\x1B[31mToo many attempts. Disconnecting.\x1B[0m".to_string()
-                );
-                commands.entity(entity).despawn_recursive();
+        match std::mem::take(&mut pending.stage) {
+            LoginStage::Name => {
+                let name = input;
+
+                // Validate name
+                if name.is_empty() || name.len() > 32 {
+                    client.send(OutputMessage::error("Name must be 1-32 characters. Try again:"));
+                    fail_attempt(client, &mut pending);
+                    continue;
+                }
+
+                // Check for existing player
+                worker.request(entity, DbRequest::Account { name: name.to_string() });
+                pending.stage = LoginStage::LookingUp { name: name.to_string() };
             }
-            continue;
-        }
 
-        // Check for existing player
-        match db.load_player_by_name(name) {
-            Ok(Some(record)) => {
-                // Restore existing player!
-                restore_player(&mut commands, entity, &client, &record, &query_rooms, &mut look_writer);
+            LoginStage::Password { record } => {
+                worker.request(entity, DbRequest::Login {
+                    uuid: record.uuid.clone(),
+                    password: Password::new(input),
+                });
+                pending.stage = LoginStage::Verifying { record };
             }
-            Ok(None) => {
-                // New player - create fresh identity
-                create_new_player(&mut commands, entity, &client, name, &query_rooms, &mut look_writer);
+
+            LoginStage::NewPassword { name, record } => {
+                if input.chars().count() < MIN_PASSWORD_LEN {
+                    client.send(OutputMessage::error(format!(
+                        "Passwords need at least {} characters.",
+                        MIN_PASSWORD_LEN
+                    )));
+                    prompt_secret(client, "Choose a password:");
+                    pending.stage = LoginStage::NewPassword { name, record };
+                    continue;
+                }
+                prompt_secret(client, "Repeat it:");
+                pending.stage = LoginStage::ConfirmPassword {
+                    name,
+                    record,
+                    password: Password::new(input),
+                };
             }
-            Err(e) => {
-                tracing::error!(error = %e, "Database error during login");
-                let _ = client.tx.send(
-                    "\x1B[31mDatabase error. Please try again.\x1B[0m".to_string()
-                );
-                continue;
+
+            LoginStage::ConfirmPassword { name, record, password } => {
+                if input != password.as_str() {
+                    client.send(OutputMessage::error("Those didn't match."));
+                    prompt_secret(client, "Choose a password:");
+                    pending.stage = LoginStage::NewPassword { name, record };
+                    continue;
+                }
+                let _ = client.tx.send_frame(Outbound::Echo(true));
+
+                match record {
+                    Some(record) => {
+                        // Replaces the temporary password; queued ahead of the restore
+                        worker.write(DbWrite::Password {
+                            uuid: record.uuid.clone(),
+                            password,
+                        });
+                        let session = Session { entity, client, client_type: *client_type, options };
+                        if take_over_body(&mut commands, &mut connections, &session, &record, &query_bodies, &query_room_clients, &mut look_writer) {
+                            commands.entity(entity).remove::<PendingLogin>();
+                        } else {
+                            worker.request(entity, DbRequest::Restore { uuid: record.uuid.clone() });
+                            pending.stage = LoginStage::Restoring;
+                        }
+                    }
+                    None => {
+                        // Claim the name now, so nobody else can take it first
+                        let record = Box::new(PlayerRecord::new(&name, *client_type, &config.game.spawn_room));
+                        worker.request(entity, DbRequest::Claim { record: record.clone(), password });
+                        pending.stage = LoginStage::Claiming { record };
+                    }
+                }
             }
+
+            // Still waiting on the database; typing ahead doesn't help
+            waiting => pending.stage = waiting,
+        }
+    }
+
+    for reply in ev_replies.read() {
+        let Ok((entity, client, client_type, options, mut pending)) = query.get_mut(reply.entity) else {
+            continue;
+        };
+
+        match (std::mem::take(&mut pending.stage), &reply.response) {
+            (LoginStage::LookingUp { name }, DbResponse::Account(account)) => match account {
+                Ok(Some(account)) if account.locked => {
+                    client.send(OutputMessage::error(
+                        "This designation is locked after too many failed logins. Try again later.",
+                    ));
+                    client.close();
+                }
+                Ok(Some(account)) if !account.has_password => {
+                    // Never let a passwordless character be claimed from the prompt
+                    client.send(OutputMessage::error(
+                        "This designation has no password set. An admin must reset it. Enter your designation:",
+                    ));
+                    fail_attempt(client, &mut pending);
+                }
+                Ok(Some(account)) => {
+                    prompt_secret(client, "Password:");
+                    pending.stage = LoginStage::Password {
+                        record: Box::new(account.record.clone()),
+                    };
+                }
+                Ok(None) => {
+                    if let Some(problem) = new_name_problem(&name) {
+                        client.send(OutputMessage::error(format!("{} Enter your designation:", problem)));
+                        fail_attempt(client, &mut pending);
+                        continue;
+                    }
+                    client.send(OutputMessage::narrate(
+                        Tone::Info,
+                        "An unclaimed designation. Secure it with a password.",
+                    ));
+                    prompt_secret(client, "Choose a password:");
+                    pending.stage = LoginStage::NewPassword { name, record: None };
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Database error during login");
+                    client.send(OutputMessage::error("Database error. Please try again."));
+                }
+            },
+
+            (LoginStage::Verifying { record }, DbResponse::Login(check)) => match check {
+                Ok(LoginCheck::Accepted { temporary: true }) => {
+                    client.send(OutputMessage::narrate(
+                        Tone::Notice,
+                        "That temporary password is now spent. Choose a new one.",
+                    ));
+                    prompt_secret(client, "Choose a password:");
+                    pending.stage = LoginStage::NewPassword {
+                        name: record.name.clone(),
+                        record: Some(record),
+                    };
+                }
+                Ok(LoginCheck::Accepted { temporary: false }) => {
+                    let _ = client.tx.send_frame(Outbound::Echo(true));
+                    let session = Session { entity, client, client_type: *client_type, options };
+                    if take_over_body(&mut commands, &mut connections, &session, &record, &query_bodies, &query_room_clients, &mut look_writer) {
+                        commands.entity(entity).remove::<PendingLogin>();
+                    } else {
+                        worker.request(entity, DbRequest::Restore { uuid: record.uuid.clone() });
+                        pending.stage = LoginStage::Restoring;
+                    }
+                }
+                Ok(LoginCheck::Refused { locked }) => {
+                    tracing::warn!(uuid = %record.uuid, locked, "Failed login");
+                    if *locked {
+                        client.send(OutputMessage::error(
+                            "This designation is locked after too many failed logins. Try again later.",
+                        ));
+                        let _ = client.tx.send_frame(Outbound::Echo(true));
+                        client.close();
+                    } else {
+                        client.send(OutputMessage::error("Wrong password."));
+                        if !fail_attempt(client, &mut pending) {
+                            prompt_secret(client, "Password:");
+                            pending.stage = LoginStage::Password { record };
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Database error checking a password");
+                    client.send(OutputMessage::error("Database error. Please try again."));
+                    prompt_secret(client, "Password:");
+                    pending.stage = LoginStage::Password { record };
+                }
+            },
+
+            (LoginStage::Claiming { record }, DbResponse::Claim(claimed)) => match claimed {
+                Ok(true) => {
+                    create_new_player(&mut commands, entity, client, &record, &config.game, &query_rooms, &mut look_writer);
+                    commands.entity(entity).remove::<PendingLogin>();
+                }
+                Ok(false) => {
+                    client.send(OutputMessage::error(
+                        "That designation was just claimed. Enter your designation:",
+                    ));
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to create player");
+                    client.send(OutputMessage::error("Database error. Please try again."));
+                }
+            },
+
+            (LoginStage::Restoring, DbResponse::Restore(saved)) => match saved {
+                Ok(Some(saved)) => {
+                    // Another session may have brought the body back while we waited
+                    let session = Session { entity, client, client_type: *client_type, options };
+                    if !take_over_body(&mut commands, &mut connections, &session, &saved.record, &query_bodies, &query_room_clients, &mut look_writer) {
+                        let avatar = find_avatar(saved.avatar_uuid.as_deref(), &query_bodies);
+                        restore_player(&mut commands, entity, client, saved, avatar, &config.game, &query_rooms, &mut look_writer);
+                    }
+                    commands.entity(entity).remove::<PendingLogin>();
+                }
+                Ok(None) => {
+                    client.send(OutputMessage::error("That designation has dissolved. Enter your designation:"));
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to load player");
+                    client.send(OutputMessage::error("Database error. Please try again."));
+                }
+            },
+
+            // Not an answer to what this connection asked
+            (stage, _) => pending.stage = stage,
+        }
+    }
+}
+
+/// Ask for a password with the client's echo off
+fn prompt_secret(client: &NetworkClient, prompt: &str) {
+    client.send(OutputMessage::Prompt {
+        prompt: prompt.to_string(),
+        secret: true,
+    });
+    let _ = client.tx.send_frame(Outbound::Echo(false));
+}
+
+/// Count a failed attempt, hanging up once they run out.
+/// Returns true if the connection is being dropped.
+fn fail_attempt(client: &NetworkClient, pending: &mut PendingLogin) -> bool {
+    pending.attempts += 1;
+    if pending.attempts < MAX_LOGIN_ATTEMPTS {
+        return false;
+    }
+    client.send(OutputMessage::error("Too many attempts. Disconnecting."));
+    let _ = client.tx.send_frame(Outbound::Echo(true));
+    client.close();
+    true
+}
+
+/// Anything in the world with an identity: players (connected or
+/// link-dead) and NPCs
+type BodyQuery = (
+    Entity,
+    &'static SubstrateIdentity,
+    Option<&'static NetworkClient>,
+    Option<&'static AdminLink>,
+    Option<&'static SomaticBody>,
+    Option<&'static Location>,
+    Option<&'static PurgatoryState>,
+);
+
+/// Everyone connected who might notice a body come or go
+type RoomClients = (Entity, &'static NetworkClient, &'static Location, Option<&'static Role>);
+
+/// The login connection that just proved who it is
+struct Session<'a> {
+    entity: Entity,
+    client: &'a NetworkClient,
+    client_type: ClientType,
+    options: Option<&'a TelnetOptions>,
+}
+
+/// If the character's body is still in the world, move the new connection
+/// into it, kicking whatever session held it. Returns false if there is no
+/// body, and the player should be restored from the database instead.
+fn take_over_body(
+    commands: &mut Commands,
+    connections: &mut Connections,
+    session: &Session,
+    record: &PlayerRecord,
+    query_bodies: &Query<BodyQuery, (Without<PendingLogin>, Without<PendingSave>)>,
+    query_room_clients: &Query<RoomClients>,
+    look_writer: &mut EventWriter<LookEvent>,
+) -> bool {
+    let Some((body, identity, body_client, link, somatic, location, purgatory)) = query_bodies
+        .iter()
+        .find(|(_, identity, ..)| identity.uuid == record.uuid)
+    else {
+        return false;
+    };
+
+    // The old session is on the body itself, or on the avatar it shifted into
+    let stale = body_client.map(|client| (body, client)).or_else(|| {
+        let partner = link?.partner;
+        let (_, _, client, ..) = query_bodies.get(partner).ok()?;
+        client.map(|client| (partner, client))
+    });
+    if let Some((holder, old)) = stale {
+        old.send(OutputMessage::narrate(
+            Tone::Alarm,
+            "Your signal has been claimed by another connection. Disconnecting.",
+        ));
+        old.close();
+        connections.remove(old.conn);
+        commands.entity(holder).remove::<(NetworkClient, TelnetOptions)>();
+        if holder != body {
+            commands.entity(holder).remove::<Role>();
         }
+        tracing::info!(uuid = %record.uuid, old = %old.conn, "Stale session kicked");
+    }
 
-        // Remove pending login state
-        commands.entity(entity).remove::<PendingLogin>();
+    let client = session.client;
+    let mut body_commands = commands.entity(body);
+    body_commands.remove::<(LinkDead, TelnetOptions)>().insert((
+        NetworkClient {
+            conn: client.conn,
+            addr: client.addr,
+            tx: client.tx.clone(),
+        },
+        session.client_type,
+        record.role,
+    ));
+    if let Some(options) = session.options {
+        body_commands.insert(options.clone());
+    }
+    connections.bind(client.conn, body);
+    commands.entity(session.entity).despawn_recursive();
+
+    client.send(OutputMessage::Welcome {
+        name: identity.name.clone(),
+        uuid: identity.uuid.clone(),
+        returning: true,
+        integrity: somatic.map(|s| s.integrity).unwrap_or(record.integrity),
+        stability: identity.stability,
+        entropy: identity.entropy,
+    });
+    if let (None, Some(location)) = (stale, location) {
+        tell_room(
+            query_room_clients,
+            location.0,
+            session.entity,
+            identity,
+            purgatory,
+            |name| format!("{}'s signal steadies.", name),
+        );
     }
+    look_writer.send(LookEvent {
+        entity: body,
+        target: None,
+    });
+
+    tracing::info!(uuid = %record.uuid, conn = %client.conn, "Player re-attached to body");
+    true
+}
+
+/// The in-world entity an admin is linked to, if it exists right now
+fn find_avatar(
+    avatar_uuid: Option<&str>,
+    query_bodies: &Query<BodyQuery, (Without<PendingLogin>, Without<PendingSave>)>,
+) -> Option<Entity> {
+    let avatar_uuid = avatar_uuid?;
+    query_bodies
+        .iter()
+        .find(|(_, identity, ..)| identity.uuid == avatar_uuid)
+        .map(|(e, ..)| e)
 }
 
 /// Restore an existing player from database
+#[allow(clippy::too_many_arguments)]
 fn restore_player(
     commands: &mut Commands,
     entity: Entity,
     client: &NetworkClient,
-    record: &PlayerRecord,
+    saved: &SavedPlayer,
+    avatar: Option<Entity>,
+    config: &GameConfig,
     query_rooms: &Query<(Entity, &RoomInfo)>,
     look_writer: &mut EventWriter<LookEvent>,
 ) {
+    let record = &saved.record;
+
     // Find the room by name, or fall back to spawn
     let room_entity = query_rooms
         .iter()
         .find(|(_, info)| info.name == record.last_room)
         .map(|(e, _)| e)
-        .or_else(|| query_rooms.iter().next().map(|(e, _)| e))
-        .expect("No rooms exist!");
+        .unwrap_or_else(|| spawn_room(config, query_rooms));
 
     // Build the player entity with restored state
-    commands.entity(entity).insert((
-        SubstrateIdentity {
-            uuid: record.uuid.clone(),
-            name: record.name.clone(),
-            entropy: record.entropy,
-            stability: record.stability,
-            signal_strength: record.signal_strength,
-        },
-        Location(room_entity),
-        Inventory,
-        SomaticBody {
-            integrity: record.integrity,
-            max_integrity: 1.0,
-            is_zombie: false,
-        },
-        ClientType::Carbon,
-        Wallet::default(),  // TODO: Persist wallet in database
-    ));
+    let mut body = commands.entity(entity);
+    record.insert_into(&mut body, config.starting_cycles);
+    body.insert((Location(room_entity), Inventory));
 
-    // Restore combat stats if present
-    if let Some(stats) = &record.combat_stats {
-        commands.entity(entity).insert(CombatStats {
-            attack: stats.attack,
-            defense: stats.defense,
-            precision: stats.precision,
-            chaos_factor: stats.chaos_factor,
-        });
+    // Carried items come back under the UUIDs they were saved with
+    for item in &saved.inventory {
+        commands.spawn(item.to_item()).set_parent(entity);
     }
 
-    let _ = client.tx.send(format!(
-        "\x1B[1;32m--- SIGNAL RESTORED: {} ---\x1B[0m",
-        record.name
-    ));
-    let _ = client.tx.send(format!(
-        "\x1B[32mIntegrity: {:.0}% | Stability: {:.0}% | Entropy: {:.0}%\x1B[0m",
-        record.integrity * 100.0,
-        record.stability * 100.0,
-        record.entropy * 100.0,
-    ));
-    let _ = client.tx.send(
-        "\x1B[35mYour consciousness re-materializes in the Substrate...\x1B[0m".to_string()
-    );
+    if let Some(avatar) = avatar {
+        commands.entity(entity).insert(AdminLink { partner: avatar });
+        commands.entity(avatar).insert(AdminLink { partner: entity });
+    }
+
+    client.send(OutputMessage::Welcome {
+        name: record.name.clone(),
+        uuid: record.uuid.clone(),
+        returning: true,
+        integrity: record.integrity,
+        stability: record.stability,
+        entropy: record.entropy,
+    });
 
     look_writer.send(LookEvent {
         entity,
@@ -159,77 +531,150 @@ fn create_new_player(
     commands: &mut Commands,
     entity: Entity,
     client: &NetworkClient,
-    name: &str,
+    record: &PlayerRecord,
+    config: &GameConfig,
     query_rooms: &Query<(Entity, &RoomInfo)>,
     look_writer: &mut EventWriter<LookEvent>,
 ) {
-    let room_entity = query_rooms
-        .iter()
-        .find(|(_, info)| info.name == "spawn" || info.name == "obsidian_plaza")
-        .map(|(e, _)| e)
-        .or_else(|| query_rooms.iter().next().map(|(e, _)| e))
-        .expect("No rooms exist!");
+    let room_entity = spawn_room(config, query_rooms);
 
-    let uuid = uuid::Uuid::new_v4().to_string();
+    let mut body = commands.entity(entity);
+    record.insert_into(&mut body, config.starting_cycles);
+    body.insert((Location(room_entity), Inventory));
 
-    commands.entity(entity).insert((
-        SubstrateIdentity {
-            uuid: uuid.clone(),
-            name: name.to_string(),
-            entropy: 0.5,
-            stability: 1.0,
-            signal_strength: 1.0,
-        },
-        Location(room_entity),
-        Inventory,
-        SomaticBody::default(),
-        CombatStats::default(),
-        ClientType::Carbon,
-        Wallet::default(),  // Start with 100 cycles
-    ));
-
-    let _ = client.tx.send(format!(
-        "\x1B[1;35m--- NEW CONSCIOUSNESS DIGITIZED: {} ---\x1B[0m",
-        name
-    ));
-    let _ = client.tx.send(format!(
-        "\x1B[35mUUID assigned: {}\x1B[0m",
-        uuid
-    ));
-    let _ = client.tx.send(
-        "\x1B[35mWelcome to the Substrate. Your journey begins...\x1B[0m".to_string()
-    );
+    client.send(OutputMessage::Welcome {
+        name: record.name.clone(),
+        uuid: record.uuid.clone(),
+        returning: false,
+        integrity: record.integrity,
+        stability: record.stability,
+        entropy: record.entropy,
+    });
 
     look_writer.send(LookEvent {
         entity,
         target: None,
     });
 
-    tracing::info!(uuid = %uuid, name = %name, "New player created");
+    tracing::info!(uuid = %record.uuid, name = %record.name, "New player created");
+}
+
+/// Where new players appear: the configured spawn room, else any room at all
+fn spawn_room(config: &GameConfig, query_rooms: &Query<(Entity, &RoomInfo)>) -> Entity {
+    query_rooms
+        .iter()
+        .find(|(_, info)| info.name == config.spawn_room)
+        .map(|(e, _)| e)
+        .or_else(|| query_rooms.iter().next().map(|(e, _)| e))
+        .expect("No rooms exist!")
 }
 
-/// System to handle disconnections - mark for save
+/// System to handle disconnections - the body goes link-dead
 pub fn handle_disconnect_system(
     mut commands: Commands,
+    config: Res<ServerConfig>,
     mut ev_reader: EventReader<NetworkEvent>,
-    query: Query<(Entity, &NetworkClient, Option<&SubstrateIdentity>)>,
+    mut connections: ResMut<Connections>,
+    query: Query<(
+        Option<&SubstrateIdentity>,
+        Has<NonPlayer>,
+        Option<&AdminLink>,
+        Option<&Role>,
+    ), With<NetworkClient>>,
+    query_bodies: Query<(&SubstrateIdentity, &Location, Option<&PurgatoryState>)>,
+    query_room_clients: Query<RoomClients>,
 ) {
     for event in ev_reader.read() {
-        if let NetworkEvent::Disconnected { addr } = event {
-            // Find the entity with this address
-            for (entity, client, identity) in query.iter() {
-                if client.addr == *addr {
-                    if identity.is_some() {
-                        // Mark for save, then despawn
-                        commands.entity(entity).insert(PendingSave);
-                        tracing::info!(addr = %addr, "Player disconnected, marked for save");
-                    } else {
-                        // Was in login flow, just despawn
-                        commands.entity(entity).despawn_recursive();
+        if let NetworkEvent::Disconnected { conn } = event {
+            let Some(entity) = connections.remove(*conn) else {
+                continue;
+            };
+            let Ok((identity, is_npc, link, role)) = query.get(entity) else {
+                continue;
+            };
+
+            let body = match (is_npc, link) {
+                // Dropped while shifted: the avatar stays as it was, and the
+                // admin's own body is the one left behind
+                (true, Some(link)) => {
+                    commands.entity(entity).remove::<Role>();
+                    if let Some(role) = role {
+                        commands.entity(link.partner).insert(*role);
                     }
-                    break;
+                    link.partner
+                }
+                _ if identity.is_some() => entity,
+                _ => {
+                    // Was in login flow, just despawn
+                    commands.entity(entity).despawn_recursive();
+                    continue;
                 }
+            };
+            commands.entity(entity).remove::<(NetworkClient, TelnetOptions)>();
+
+            let grace = config.game.linkdead_grace_secs;
+            if grace == 0 {
+                commands.entity(body).insert(PendingSave);
+                tracing::info!(conn = %conn, "Player disconnected, marked for save");
+                continue;
+            }
+            commands.entity(body).insert(LinkDead {
+                conn: *conn,
+                timer: Timer::from_seconds(grace as f32, TimerMode::Once),
+            });
+            if let Ok((identity, location, purgatory)) = query_bodies.get(body) {
+                tell_room(
+                    &query_room_clients,
+                    location.0,
+                    entity,
+                    identity,
+                    purgatory,
+                    |name| format!("{}'s signal flickers and goes still.", name),
+                );
             }
+            tracing::info!(conn = %conn, "Player disconnected, link-dead");
+        }
+    }
+}
+
+/// Link-dead bodies whose grace period ran out are saved and leave the world
+pub fn linkdead_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut LinkDead, &SubstrateIdentity, &Location, Option<&PurgatoryState>)>,
+    query_room_clients: Query<RoomClients>,
+) {
+    for (entity, mut linkdead, identity, location, purgatory) in query.iter_mut() {
+        if !linkdead.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        commands.entity(entity).remove::<LinkDead>().insert(PendingSave);
+        tell_room(
+            &query_room_clients,
+            location.0,
+            entity,
+            identity,
+            purgatory,
+            |name| format!("{}'s body dissolves into static.", name),
+        );
+        tracing::info!(uuid = %identity.uuid, "Link-dead grace expired, marked for save");
+    }
+}
+
+/// Tell everyone connected in a room, except one entity, about someone -
+/// under whatever name each of them knows that someone by
+fn tell_room(
+    query_room_clients: &Query<RoomClients>,
+    room: Entity,
+    except: Entity,
+    identity: &SubstrateIdentity,
+    purgatory: Option<&PurgatoryState>,
+    line: impl Fn(&str) -> String,
+) {
+    for (entity, client, location, role) in query_room_clients.iter() {
+        if location.0 == room && entity != except {
+            let name = identity.seen_by(purgatory, role.copied().unwrap_or_default());
+            client.send(OutputMessage::narrate(Tone::Muted, line(&name)));
         }
     }
 }
@@ -238,40 +683,29 @@ pub fn handle_disconnect_system(
 pub fn handle_connections_with_login(
     mut commands: Commands,
     mut ev_reader: EventReader<NetworkEvent>,
+    mut connections: ResMut<Connections>,
 ) {
     for event in ev_reader.read() {
-        if let NetworkEvent::Connected { addr, tx } = event {
-            // Create entity in pending login state
-            commands.spawn((
-                NetworkClient {
-                    addr: *addr,
-                    tx: tx.clone(),
-                },
-                PendingLogin { attempts: 0 },
-            ));
-
-            let _ = tx.send(
-                "\x1B[1;35m╔════════════════════════════════════════╗\x1B[0m".to_string()
-            );
-            let _ = tx.send(
-                "\x1B[1;35m║   STRANGE CARBON: THE SUBSTRATE        ║\x1B[0m".to_string()
-            );
-            let _ = tx.send(
-                "\x1B[1;35m║   A Techno-Gothic Digital Realm        ║\x1B[0m".to_string()
-            );
-            let _ = tx.send(
-                "\x1B[1;35m╚════════════════════════════════════════╝\x1B[0m".to_string()
-            );
-            let _ = tx.send("".to_string());
-            let _ = tx.send(
-                "\x1B[36mYour signal pierces the membrane between worlds...\x1B[0m".to_string()
-            );
-            let _ = tx.send("".to_string());
-            let _ = tx.send(
-                "\x1B[1;37mEnter your designation:\x1B[0m".to_string()
-            );
+        if let NetworkEvent::Connected { conn, addr, tx, client_type } = event {
+            // Create entity in pending login state; the listener decides Carbon vs Silicon
+            let entity = commands
+                .spawn((
+                    NetworkClient {
+                        conn: *conn,
+                        addr: *addr,
+                        tx: tx.clone(),
+                    },
+                    *client_type,
+                    PendingLogin::default(),
+                ))
+                .id();
+            connections.bind(*conn, entity);
+
+            let _ = tx.output(OutputMessage::Greeting {
+                prompt: "Enter your designation:".to_string(),
+            });
 
-            tracing::debug!(addr = %addr, "New connection, awaiting login");
+            tracing::debug!(conn = %conn, addr = %addr, "New connection, awaiting login");
         }
     }
 }
@@ -279,21 +713,197 @@ pub fn handle_connections_with_login(
 /// Route input to login system if player is pending
 pub fn route_login_input(
     mut ev_reader: EventReader<NetworkEvent>,
-    query: Query<(Entity, &NetworkClient), With<PendingLogin>>,
+    connections: Res<Connections>,
+    query: Query<(), With<PendingLogin>>,
     mut login_writer: EventWriter<LoginAttemptEvent>,
 ) {
     for event in ev_reader.read() {
-        if let NetworkEvent::Input { addr, text } = event {
-            // Check if this is from a pending login
-            for (entity, client) in query.iter() {
-                if client.addr == *addr {
-                    login_writer.send(LoginAttemptEvent {
-                        entity,
-                        name: text.clone(),
-                    });
-                    return; // Don't process as normal input
-                }
+        if let NetworkEvent::Input { conn, text } = event {
+            // Only connections still at the login prompt; handle_input takes the rest
+            let Some(entity) = connections.get(*conn) else {
+                continue;
+            };
+            if query.contains(entity) {
+                login_writer.send(LoginAttemptEvent {
+                    entity,
+                    input: text.clone(),
+                });
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{db_reply_system, save_disconnected_players, Database};
+    use crate::systems::chains::Chained;
+    use bevy::utils::Duration;
+    use tokio::sync::mpsc;
+
+    const PASSWORD: &str = "correct horse";
+
+    /// Login and the link-dead lifecycle, in their server order, over one room
+    fn login_app(db: &Database) -> (App, Entity) {
+        let mut config = ServerConfig::default();
+        config.game.spawn_room = "hall".to_string();
+        config.game.linkdead_grace_secs = 30;
+
+        let mut app = App::new();
+        app.insert_resource(DbWorker::spawn(db.clone()))
+            .insert_resource(config)
+            .init_resource::<Connections>()
+            .init_resource::<Time>()
+            .add_event::<NetworkEvent>()
+            .add_event::<LoginAttemptEvent>()
+            .add_event::<DbReply>()
+            .add_event::<LookEvent>()
+            .add_systems(PreUpdate, db_reply_system)
+            .add_systems(
+                Update,
+                (
+                    handle_connections_with_login,
+                    route_login_input,
+                    login_system,
+                    handle_disconnect_system,
+                    linkdead_system,
+                    save_disconnected_players,
+                )
+                    .chain(),
+            );
+        let hall = app
+            .world_mut()
+            .spawn(RoomInfo {
+                name: "hall".to_string(),
+                area: "central".to_string(),
+            })
+            .id();
+        (app, hall)
+    }
+
+    /// Run frames until every database answer has been read
+    fn settle(app: &mut App) {
+        for _ in 0..4 {
+            app.update();
+            app.world().resource::<DbWorker>().flush();
+        }
+    }
+
+    fn send(app: &mut App, event: NetworkEvent) {
+        app.world_mut().send_event(event);
+        settle(app);
+    }
+
+    /// Connect and log in as Magpie; the receiver is what the client hears
+    fn log_in(app: &mut App, conn: ConnectionId) -> mpsc::UnboundedReceiver<Outbound> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        send(app, NetworkEvent::Connected {
+            conn,
+            addr: "127.0.0.1:4000".parse().unwrap(),
+            tx: ClientSender::new(tx, ClientType::Silicon),
+            client_type: ClientType::Silicon,
+        });
+        for text in ["Magpie", PASSWORD] {
+            send(app, NetworkEvent::Input { conn, text: text.to_string() });
+        }
+        rx
+    }
+
+    /// Let the clock run on by `secs` for one frame
+    fn wait(app: &mut App, secs: u64) {
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs(secs));
+        app.update();
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::ZERO);
+        app.world().resource::<DbWorker>().flush();
+    }
+
+    fn bodies(app: &mut App, uuid: &str) -> Vec<Entity> {
+        let mut query = app.world_mut().query::<(Entity, &SubstrateIdentity)>();
+        query
+            .iter(app.world())
+            .filter(|(_, identity)| identity.uuid == uuid)
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    #[test]
+    fn a_dropped_body_waits_out_its_grace_for_one_session_at_a_time() {
+        let db = Database::in_memory().unwrap();
+        let record = PlayerRecord::new("Magpie", ClientType::Silicon, "hall");
+        assert!(db.claim_player(&record, &Password::new(PASSWORD)).unwrap());
+        let (mut app, hall) = login_app(&db);
+        let [first, second, third] = [ConnectionId(1), ConnectionId(2), ConnectionId(3)];
+
+        let _rx = log_in(&mut app, first);
+        let body = app.world().resource::<Connections>().get(first).unwrap();
+        assert_eq!(bodies(&mut app, &record.uuid), vec![body]);
+        assert_eq!(app.world().get::<Location>(body).unwrap().0, hall);
+
+        // Mid-fight and in chains when the line drops
+        let rat = app.world_mut().spawn_empty().id();
+        let jailer = app.world_mut().spawn_empty().id();
+        app.world_mut().entity_mut(body).insert((
+            InCombat { opponent: rat, stance: CombatStance::Balanced },
+            Chained { holder: jailer, struggle_attempts: 0, last_struggle: 0.0 },
+        ));
+
+        send(&mut app, NetworkEvent::Disconnected { conn: first });
+        assert!(app.world().resource::<Connections>().get(first).is_none());
+        assert!(app.world().get::<NetworkClient>(body).is_none());
+        assert!(app.world().get::<LinkDead>(body).is_some());
+
+        wait(&mut app, 20);
+        assert!(app.world().get::<LinkDead>(body).is_some(), "still inside the grace period");
+
+        // Back in time: the same body, right where it was left
+        let mut rx = log_in(&mut app, second);
+        assert_eq!(app.world().resource::<Connections>().get(second), Some(body));
+        assert!(app.world().get::<LinkDead>(body).is_none());
+        assert_eq!(app.world().get::<NetworkClient>(body).unwrap().conn, second);
+        assert_eq!(app.world().get::<Location>(body).unwrap().0, hall);
+        assert_eq!(app.world().get::<InCombat>(body).unwrap().opponent, rat);
+        assert_eq!(app.world().get::<Chained>(body).unwrap().holder, jailer);
+        assert_eq!(bodies(&mut app, &record.uuid), vec![body]);
+
+        // Logging in again while that session is alive kicks it
+        let _rx = log_in(&mut app, third);
+        let heard: Vec<Outbound> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(heard.iter().any(|frame| {
+            matches!(frame, Outbound::Line(line) if line.contains("claimed by another connection"))
+        }));
+        assert!(matches!(heard.last(), Some(Outbound::Close)));
+        assert!(app.world().resource::<Connections>().get(second).is_none());
+        assert_eq!(app.world().resource::<Connections>().get(third), Some(body));
+        assert_eq!(app.world().get::<NetworkClient>(body).unwrap().conn, third);
+        assert_eq!(bodies(&mut app, &record.uuid), vec![body]);
+
+        // Gone for good, from another room: saved once the grace runs out,
+        // then out of the world
+        let attic = app
+            .world_mut()
+            .spawn(RoomInfo {
+                name: "attic".to_string(),
+                area: "central".to_string(),
+            })
+            .id();
+        app.world_mut().entity_mut(body).insert(Location(attic));
+        send(&mut app, NetworkEvent::Disconnected { conn: third });
+        wait(&mut app, 29);
+        assert!(app.world().get_entity(body).is_ok());
+        assert_eq!(db.load_player(&record.uuid).unwrap().unwrap().last_room, "hall");
+        wait(&mut app, 2);
+        assert!(app.world().get_entity(body).is_err());
+        assert!(bodies(&mut app, &record.uuid).is_empty());
+        assert_eq!(db.load_player(&record.uuid).unwrap().unwrap().last_room, "attic");
+    }
+
+    #[test]
+    fn new_names_are_single_words_that_cant_pass_for_a_process_id() {
+        for name in ["Vesper", "lark_2", "Night-Owl", "PID", "PID-", "pidgeon", "PID-XYZ"] {
+            assert_eq!(new_name_problem(name), None, "{name:?}");
+        }
+        for name in ["", "Al Jones", "Zoë", "tab\tname", &"x".repeat(33), "PID-3FA85F", "pid-0"] {
+            assert!(new_name_problem(name).is_some(), "{name:?}");
+        }
+    }
+}
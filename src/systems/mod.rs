@@ -17,11 +17,25 @@ mod combat;
 mod login;
 mod chains;
 mod trade;
+mod ghost;
+mod shutdown;
+mod purgatory;
+mod interrogation;
+mod building;
+mod doors;
+mod resets;
 
 pub use network::*;
 pub use login::*;
 pub use chains::*;
 pub use trade::*;
+pub use ghost::*;
+pub use shutdown::*;
+pub use purgatory::*;
+pub use interrogation::*;
+pub use building::*;
+pub use doors::*;
+pub use resets::*;
 pub use input::*;
 pub use movement::*;
 pub use look::*;
@@ -32,6 +46,5 @@ pub use shift::*;
 pub use items::*;
 pub use weather::*;
 pub use phase::*;
-pub use somatic::*;
 pub use stream::*;
 pub use combat::*;
@@ -6,24 +6,37 @@ use crate::domain::*;
 
 pub fn move_system(
     mut ev_reader: EventReader<MoveEvent>,
-    mut query_players: Query<(&mut Location, &NetworkClient)>,
-    query_rooms: Query<&Exits>,
+    mut query_players: Query<(&mut Location, &NetworkClient, Option<&PurgatoryState>)>,
+    query_rooms: Query<(&Exits, Option<&RoomFlags>)>,
     mut look_writer: EventWriter<LookEvent>,
 ) {
     for event in ev_reader.read() {
-        if let Ok((mut location, client)) = query_players.get_mut(event.entity) {
-            if let Ok(exits) = query_rooms.get(location.0) {
-                if let Some(target_room) = exits.get(&event.direction) {
-                    location.0 = target_room;
-                    look_writer.send(LookEvent {
-                        entity: event.entity,
-                        target: None,
-                    });
-                } else {
-                    let _ = client.tx.send(
-                        "\x1B[31mThe path is barred by twisted wrought iron and static.\x1B[0m"
-                            .to_string(),
-                    );
+        if let Ok((mut location, client, purgatory)) = query_players.get_mut(event.entity) {
+            if let Ok((exits, flags)) = query_rooms.get(location.0) {
+                if flags.is_some_and(|flags| flags.holds(purgatory)) {
+                    client.send(OutputMessage::error("The velvet holds you here until your sentence is done."));
+                    continue;
+                }
+                match exits.pass(&event.direction) {
+                    Ok(target_room) => {
+                        location.0 = target_room;
+                        look_writer.send(LookEvent {
+                            entity: event.entity,
+                            target: None,
+                        });
+                    }
+                    Err(Blocked::Door(door)) => {
+                        client.send(OutputMessage::error(format!(
+                            "The {} is {}.",
+                            door.name,
+                            door.state.as_str()
+                        )));
+                    }
+                    Err(Blocked::NoExit) => {
+                        client.send(OutputMessage::error(
+                            "The path is barred by twisted wrought iron and static.",
+                        ));
+                    }
                 }
             }
         }
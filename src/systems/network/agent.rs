@@ -0,0 +1,346 @@
+// Agent Protocol - Line-delimited JSON for Silicon clients
+//
+// Every line in either direction is one JSON object.
+//
+// Request:  {"id": 7, "cmd": "look"}
+// Replies:  {"id": 7, "type": "text", "text": "..."}   prose, ANSI stripped
+//           {"id": 7, "type": "data", "data": {...}}   structured output (look, combat)
+//           {"id": 7, "type": "done"}                  end of the reply to request 7
+// Errors:   {"id": null, "type": "error", "error": "..."}
+//
+// Output that no request caused (someone else's say, the weather) carries "id": null.
+// Requests run one at a time per session, so every frame between a request
+// reaching the Substrate and its "done" belongs to that request.
+//
+// "The Silicon do not read prose. They parse it."
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+use crate::domain::*;
+
+use super::codec::LineCodec;
+use super::next_connection_id;
+
+/// One command from an agent
+#[derive(Debug, Deserialize)]
+struct AgentRequest {
+    #[serde(default)]
+    id: Value,
+    #[serde(alias = "command")]
+    cmd: String,
+}
+
+/// Requests waiting on the Substrate, oldest first. The front one is in flight.
+type RequestQueue = Arc<Mutex<VecDeque<(Value, String)>>>;
+
+/// Accept Silicon connections on their own port
+pub async fn listen(listener: TcpListener, event_tx: mpsc::UnboundedSender<NetworkEvent>) {
+    if let Ok(addr) = listener.local_addr() {
+        println!("🤖 Agent protocol listening on {}", addr);
+    }
+    serve(listener, event_tx).await;
+}
+
+/// Accept loop over an already-bound listener
+pub async fn serve(listener: TcpListener, event_tx: mpsc::UnboundedSender<NetworkEvent>) {
+    loop {
+        let Ok((socket, addr)) = listener.accept().await else {
+            continue;
+        };
+        tokio::spawn(session(socket, addr, event_tx.clone()));
+    }
+}
+
+/// Drive one agent connection until either side hangs up
+async fn session(
+    socket: tokio::net::TcpStream,
+    addr: SocketAddr,
+    event_tx: mpsc::UnboundedSender<NetworkEvent>,
+) {
+    let (client_tx, mut client_rx) = mpsc::unbounded_channel::<Outbound>();
+    // Protocol errors never touch the ECS; they go straight back to the agent
+    let (error_tx, mut error_rx) = mpsc::unbounded_channel::<Value>();
+    let pending: RequestQueue = Arc::new(Mutex::new(VecDeque::new()));
+
+    let conn = next_connection_id();
+    let _ = event_tx.send(NetworkEvent::Connected {
+        conn,
+        addr,
+        tx: ClientSender::new(client_tx, ClientType::Silicon),
+        client_type: ClientType::Silicon,
+    });
+
+    let (mut reader, mut writer) = socket.into_split();
+
+    let read_task = {
+        let event_tx = event_tx.clone();
+        let pending = pending.clone();
+        tokio::spawn(async move {
+            let mut codec = LineCodec::default();
+            let mut buf = [0; 4096];
+            loop {
+                let n = match reader.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                for line in codec.feed(&buf[..n]) {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(e) => {
+                            let _ = error_tx.send(protocol_error(e));
+                            continue;
+                        }
+                    };
+                    let line = line.trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+
+                    let request = match serde_json::from_str::<AgentRequest>(line) {
+                        Ok(request) => request,
+                        Err(e) => {
+                            let _ = error_tx.send(protocol_error(format!("malformed request: {}", e)));
+                            continue;
+                        }
+                    };
+
+                    let cmd = request.cmd.trim().to_string();
+                    let idle = {
+                        let mut queue = pending.lock().unwrap();
+                        queue.push_back((request.id, cmd.clone()));
+                        queue.len() == 1
+                    };
+                    if idle {
+                        let _ = event_tx.send(NetworkEvent::Input { conn, text: cmd });
+                    }
+                }
+            }
+        })
+    };
+
+    let write_task = {
+        let event_tx = event_tx.clone();
+        tokio::spawn(async move {
+            loop {
+                let frame = tokio::select! {
+                    Some(frame) = client_rx.recv() => match frame {
+                        Outbound::Line(text) => {
+                            let id = current_id(&pending);
+                            render_line(id, &text)
+                        }
+                        Outbound::EndOfReply => {
+                            let (finished, next) = {
+                                let mut queue = pending.lock().unwrap();
+                                let finished = queue.pop_front().map(|(id, _)| id);
+                                (finished, queue.front().map(|(_, cmd)| cmd.clone()))
+                            };
+                            if let Some(cmd) = next {
+                                let _ = event_tx.send(NetworkEvent::Input { conn, text: cmd });
+                            }
+                            match finished {
+                                Some(id) => json!({ "id": id, "type": "done" }),
+                                None => continue,
+                            }
+                        }
+                        // Agents have no local echo to suppress
+                        Outbound::Echo(_) => continue,
+                        Outbound::Close => break,
+                    },
+                    Some(error) = error_rx.recv() => error,
+                    else => break,
+                };
+
+                let mut out = frame.to_string();
+                out.push('\n');
+                if writer.write_all(out.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        })
+    };
+
+    let reader = read_task.abort_handle();
+    tokio::select! {
+        _ = read_task => (),
+        _ = write_task => (),
+    }
+    // Whichever side finished first, the session is over
+    reader.abort();
+    let _ = event_tx.send(NetworkEvent::Disconnected { conn });
+}
+
+/// Error frame for input we couldn't turn into a request
+fn protocol_error(error: impl ToString) -> Value {
+    json!({ "id": Value::Null, "type": "error", "error": error.to_string() })
+}
+
+/// The id of the request currently in flight, or null for unsolicited output
+fn current_id(pending: &RequestQueue) -> Value {
+    pending
+        .lock()
+        .unwrap()
+        .front()
+        .map(|(id, _)| id.clone())
+        .unwrap_or(Value::Null)
+}
+
+/// Systems that already speak JSON to Silicon pass through as data; prose becomes text
+fn render_line(id: Value, text: &str) -> Value {
+    if let Ok(data @ (Value::Object(_) | Value::Array(_))) = serde_json::from_str::<Value>(text) {
+        return json!({ "id": id, "type": "data", "data": data });
+    }
+    json!({ "id": id, "type": "text", "text": strip_ansi(text) })
+}
+
+/// Drop ANSI escape sequences (colour codes) so agents get plain text
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1B' {
+            out.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'[') {
+            chars.next();
+            // CSI: parameters and intermediates, then one final byte in @..~
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+    use tokio::net::tcp::OwnedReadHalf;
+    use tokio::net::TcpStream;
+    use tokio::time::timeout;
+
+    struct Agent {
+        lines: Lines<BufReader<OwnedReadHalf>>,
+        writer: tokio::net::tcp::OwnedWriteHalf,
+        events: mpsc::UnboundedReceiver<NetworkEvent>,
+        tx: ClientSender,
+    }
+
+    impl Agent {
+        async fn connect() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let local = listener.local_addr().unwrap();
+            let (event_tx, mut events) = mpsc::unbounded_channel();
+            tokio::spawn(serve(listener, event_tx));
+
+            let (reader, writer) = TcpStream::connect(local).await.unwrap().into_split();
+            let NetworkEvent::Connected { tx, client_type, .. } = next_event(&mut events).await else {
+                panic!("expected Connected");
+            };
+            assert_eq!(client_type, ClientType::Silicon);
+            Self {
+                lines: BufReader::new(reader).lines(),
+                writer,
+                events,
+                tx,
+            }
+        }
+
+        async fn write(&mut self, text: &str) {
+            self.writer.write_all(text.as_bytes()).await.unwrap();
+        }
+
+        async fn frame(&mut self) -> Value {
+            let line = timeout(Duration::from_secs(2), self.lines.next_line())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+
+        async fn input(&mut self) -> String {
+            match next_event(&mut self.events).await {
+                NetworkEvent::Input { text, .. } => text,
+                _ => panic!("expected Input"),
+            }
+        }
+    }
+
+    async fn next_event(rx: &mut mpsc::UnboundedReceiver<NetworkEvent>) -> NetworkEvent {
+        timeout(Duration::from_secs(2), rx.recv()).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn replies_carry_the_request_id_until_done() {
+        let mut agent = Agent::connect().await;
+        agent
+            .write("{\"id\": 1, \"cmd\": \"look\"}\n{\"id\": \"b\", \"command\": \" score \"}\n")
+            .await;
+
+        // One request in flight at a time; the second waits for the first's done
+        assert_eq!(agent.input().await, "look");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(agent.events.try_recv().is_err());
+
+        agent.tx.send("\x1B[1;36mThe Obsidian Plaza\x1B[0m".to_string()).unwrap();
+        agent.tx.send_frame(Outbound::EndOfReply).unwrap();
+        assert_eq!(
+            agent.frame().await,
+            json!({ "id": 1, "type": "text", "text": "The Obsidian Plaza" })
+        );
+        assert_eq!(agent.frame().await, json!({ "id": 1, "type": "done" }));
+
+        assert_eq!(agent.input().await, "score");
+        agent.tx.send(r#"{"integrity": 1.0}"#.to_string()).unwrap();
+        agent.tx.send_frame(Outbound::EndOfReply).unwrap();
+        assert_eq!(
+            agent.frame().await,
+            json!({ "id": "b", "type": "data", "data": { "integrity": 1.0 } })
+        );
+        assert_eq!(agent.frame().await, json!({ "id": "b", "type": "done" }));
+
+        // Nothing in flight: output is unsolicited, and a stray done says nothing
+        agent.tx.send("The weather turns.".to_string()).unwrap();
+        agent.tx.send_frame(Outbound::EndOfReply).unwrap();
+        agent.tx.send("Still here.".to_string()).unwrap();
+        assert_eq!(
+            agent.frame().await,
+            json!({ "id": null, "type": "text", "text": "The weather turns." })
+        );
+        assert_eq!(agent.frame().await["text"], "Still here.");
+    }
+
+    #[tokio::test]
+    async fn malformed_requests_get_an_error_frame_and_go_no_further() {
+        let mut agent = Agent::connect().await;
+        agent.write("look\n\n{\"id\": 2}\n{\"id\": 3, \"cmd\": \"north\"}\n").await;
+
+        for _ in 0..2 {
+            let frame = agent.frame().await;
+            assert_eq!(frame["id"], Value::Null);
+            assert_eq!(frame["type"], "error");
+            assert!(frame["error"].as_str().unwrap().starts_with("malformed request"));
+        }
+        // Blank lines are ignored; the good request still goes through
+        assert_eq!(agent.input().await, "north");
+
+        drop(agent.writer);
+        assert!(matches!(
+            next_event(&mut agent.events).await,
+            NetworkEvent::Disconnected { .. }
+        ));
+    }
+}
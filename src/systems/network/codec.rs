@@ -0,0 +1,218 @@
+// Line Codec - Turn a TCP byte stream into whole input lines
+//
+// TCP has no message boundaries: one read can hold half a command or a
+// whole pasted paragraph. The codec buffers bytes until CR, LF or CRLF and
+// hands back one line per terminator, whatever the packet boundaries were.
+//
+// - Backspace/DEL erase the previous character (for clients in char mode)
+// - Other control bytes are dropped; tab survives
+// - Lines over the cap are discarded whole and reported once
+// - Lines that aren't UTF-8 are rejected rather than lossily mangled
+
+use std::fmt;
+
+/// Longest line we accept, in bytes. Nobody types a 4K command by hand.
+pub const MAX_LINE_LENGTH: usize = 4096;
+
+const BACKSPACE: u8 = 0x08;
+const DELETE: u8 = 0x7F;
+
+/// Why a line was thrown away
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineError {
+    TooLong,
+    InvalidUtf8,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineError::TooLong => write!(f, "line longer than {} bytes", MAX_LINE_LENGTH),
+            LineError::InvalidUtf8 => write!(f, "line is not valid UTF-8"),
+        }
+    }
+}
+
+/// Buffered line splitter for one connection
+#[derive(Debug)]
+pub struct LineCodec {
+    buf: Vec<u8>,
+    max_len: usize,
+    /// Current line blew the cap; swallow bytes until its terminator
+    overflowed: bool,
+    /// Last byte was CR, so an LF right after it ends nothing
+    after_cr: bool,
+}
+
+impl Default for LineCodec {
+    fn default() -> Self {
+        Self::new(MAX_LINE_LENGTH)
+    }
+}
+
+impl LineCodec {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            max_len,
+            overflowed: false,
+            after_cr: false,
+        }
+    }
+
+    /// Feed bytes in; get back every line they completed, in order
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Result<String, LineError>> {
+        let mut lines = Vec::new();
+        for &b in bytes {
+            let after_cr = std::mem::take(&mut self.after_cr);
+            match b {
+                b'\n' if after_cr => {}
+                b'\r' | b'\n' => {
+                    self.after_cr = b == b'\r';
+                    lines.push(self.finish_line());
+                }
+                BACKSPACE | DELETE => self.erase(),
+                b'\t' => self.push(b),
+                _ if b < 0x20 => {}
+                _ => self.push(b),
+            }
+        }
+        lines
+    }
+
+    fn push(&mut self, b: u8) {
+        if self.overflowed {
+            return;
+        }
+        if self.buf.len() >= self.max_len {
+            self.overflowed = true;
+            self.buf.clear();
+            return;
+        }
+        self.buf.push(b);
+    }
+
+    /// Remove the last character, not just the last byte
+    fn erase(&mut self) {
+        while let Some(b) = self.buf.pop() {
+            // UTF-8 continuation bytes look like 0b10xxxxxx
+            if b & 0xC0 != 0x80 {
+                break;
+            }
+        }
+    }
+
+    fn finish_line(&mut self) -> Result<String, LineError> {
+        if std::mem::take(&mut self.overflowed) {
+            return Err(LineError::TooLong);
+        }
+        let bytes = std::mem::take(&mut self.buf);
+        String::from_utf8(bytes).map_err(|_| LineError::InvalidUtf8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::network::telnet::{Telnet, IAC, OPT_NAWS, SB, SE, WILL};
+
+    fn ok(lines: Vec<Result<String, LineError>>) -> Vec<String> {
+        lines.into_iter().map(|l| l.unwrap()).collect()
+    }
+
+    #[test]
+    fn fragmented_command_is_one_line() {
+        let mut codec = LineCodec::default();
+        assert!(codec.feed(b"lo").is_empty());
+        assert!(codec.feed(b"ok nor").is_empty());
+        assert_eq!(ok(codec.feed(b"th\r\n")), vec!["look north"]);
+    }
+
+    #[test]
+    fn coalesced_commands_are_separate_lines() {
+        let mut codec = LineCodec::default();
+        let lines = ok(codec.feed(b"north\r\nlook\nsay hi\r"));
+        assert_eq!(lines, vec!["north", "look", "say hi"]);
+    }
+
+    #[test]
+    fn crlf_split_across_packets_ends_one_line() {
+        let mut codec = LineCodec::default();
+        assert_eq!(ok(codec.feed(b"look\r")), vec!["look"]);
+        assert!(codec.feed(b"\n").is_empty());
+        assert_eq!(ok(codec.feed(b"\n")), vec![""]);
+    }
+
+    #[test]
+    fn backspace_erases_whole_characters() {
+        let mut codec = LineCodec::default();
+        assert_eq!(ok(codec.feed(b"lookk\x08\n")), vec!["look"]);
+        assert_eq!(ok(codec.feed("cafë\x7Fe\n".as_bytes())), vec!["cafe"]);
+        assert_eq!(ok(codec.feed(b"\x08\x08x\n")), vec!["x"]);
+    }
+
+    #[test]
+    fn control_bytes_are_dropped() {
+        let mut codec = LineCodec::default();
+        assert_eq!(ok(codec.feed(b"lo\x00o\x07k\tx\r\n")), vec!["look\tx"]);
+    }
+
+    #[test]
+    fn overlong_line_is_rejected_and_codec_recovers() {
+        let mut codec = LineCodec::new(8);
+        assert!(codec.feed(b"0123456789").is_empty());
+        assert!(codec.feed(b"abcdef").is_empty());
+        assert_eq!(codec.feed(b"\r\nlook\r\n"), vec![Err(LineError::TooLong), Ok("look".to_string())]);
+    }
+
+    #[test]
+    fn invalid_utf8_is_rejected() {
+        let mut codec = LineCodec::default();
+        assert_eq!(
+            codec.feed(b"say \xC3\x28\nlook\n"),
+            vec![Err(LineError::InvalidUtf8), Ok("look".to_string())]
+        );
+    }
+
+    #[test]
+    fn multibyte_character_split_across_packets() {
+        let mut codec = LineCodec::default();
+        let bytes = "say ☠".as_bytes();
+        let (a, b) = bytes.split_at(bytes.len() - 1);
+        assert!(codec.feed(a).is_empty());
+        assert!(codec.feed(b).is_empty());
+        assert_eq!(ok(codec.feed(b"\r\n")), vec!["say ☠"]);
+    }
+
+    /// The telnet session runs bytes through Telnet then LineCodec
+    fn pipeline(packets: &[&[u8]]) -> Vec<String> {
+        let mut telnet = Telnet::new();
+        let mut codec = LineCodec::default();
+        let mut lines = Vec::new();
+        for packet in packets {
+            let received = telnet.receive(packet);
+            lines.extend(ok(codec.feed(&received.data)));
+        }
+        lines
+    }
+
+    #[test]
+    fn pipeline_strips_iac_split_across_packets() {
+        let naws = [IAC, SB, OPT_NAWS, 0, 80, 0, 24, IAC, SE];
+        let mut first = b"lo".to_vec();
+        first.extend_from_slice(&naws[..4]);
+        let mut second = naws[4..].to_vec();
+        second.extend_from_slice(b"ok\r");
+        let third = [b'\n', IAC, WILL, OPT_NAWS, b'n', b'\n'];
+
+        assert_eq!(pipeline(&[&first, &second, &third]), vec!["look", "n"]);
+    }
+
+    #[test]
+    fn pipeline_coalesced_paste() {
+        assert_eq!(
+            pipeline(&[b"say one\r\nsay two\r\nsay th", b"ree\r\n"]),
+            vec!["say one", "say two", "say three"]
+        );
+    }
+}
@@ -0,0 +1,191 @@
+// Network Systems - TCP/Tokio bridge for telnet and agent connections
+//
+// Three listeners feed the same NetworkEvent channel, bound to the
+// addresses in the [network] section of the server config before the app
+// starts, so a bad address fails startup:
+// - telnet_addr: Carbon clients, telnet with option negotiation (see telnet.rs)
+// - agent_addr:  Silicon clients, line-delimited JSON (see agent.rs)
+// - ws_addr:     browser players and Ghost observers (see websocket.rs)
+
+mod agent;
+pub mod codec;
+pub mod telnet;
+mod websocket;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Context;
+use bevy::prelude::*;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+use crate::config::NetworkConfig;
+use crate::domain::*;
+use crate::systems::ghost::GhostFeed;
+use crate::systems::shutdown::{wait_for_signal, ShutdownSignal};
+
+/// Source of connection ids, shared by every listener
+static NEXT_CONNECTION: AtomicU64 = AtomicU64::new(1);
+
+fn next_connection_id() -> ConnectionId {
+    ConnectionId(NEXT_CONNECTION.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Which entity each live connection is driving.
+///
+/// Login registers a connection when it spawns its entity, disconnect
+/// forgets it, and a phase shift points it at the new avatar.
+#[derive(Resource, Default)]
+pub struct Connections(HashMap<ConnectionId, Entity>);
+
+impl Connections {
+    pub fn get(&self, conn: ConnectionId) -> Option<Entity> {
+        self.0.get(&conn).copied()
+    }
+
+    /// Point a connection at an entity, replacing whatever it drove before
+    pub fn bind(&mut self, conn: ConnectionId, entity: Entity) {
+        self.0.insert(conn, entity);
+    }
+
+    pub fn remove(&mut self, conn: ConnectionId) -> Option<Entity> {
+        self.0.remove(&conn)
+    }
+}
+
+/// The listening sockets, bound up front and handed to the network thread
+#[derive(Resource)]
+pub struct Listeners(Option<Bound>);
+
+struct Bound {
+    telnet: std::net::TcpListener,
+    agent: std::net::TcpListener,
+    ws: std::net::TcpListener,
+}
+
+impl Listeners {
+    /// Bind every configured address, or say which one failed
+    pub fn bind(net: &NetworkConfig) -> anyhow::Result<Self> {
+        let bind = |what: &str, addr: &str| -> anyhow::Result<std::net::TcpListener> {
+            let listener = std::net::TcpListener::bind(addr)
+                .with_context(|| format!("can't listen for {} on {}", what, addr))?;
+            listener.set_nonblocking(true)?;
+            Ok(listener)
+        };
+        Ok(Self(Some(Bound {
+            telnet: bind("telnet", &net.telnet_addr)?,
+            agent: bind("agents", &net.agent_addr)?,
+            ws: bind("WebSockets", &net.ws_addr)?,
+        })))
+    }
+}
+
+/// Hand a socket bound before the runtime existed over to tokio
+fn adopt(listener: std::net::TcpListener) -> TcpListener {
+    TcpListener::from_std(listener).expect("Failed to register a bound listener")
+}
+
+/// Start serving the bound listeners in a background thread.
+///
+/// The thread also watches for SIGINT/SIGTERM: the first one closes the
+/// listeners and raises ShutdownSignal (open sessions stay up so they can
+/// be told), a second one exits on the spot.
+pub fn setup_network_system(mut commands: Commands, mut listeners: ResMut<Listeners>) {
+    let bound = listeners.0.take().expect("Listeners are started only once");
+    let (event_tx, event_rx) = mpsc::unbounded_channel::<NetworkEvent>();
+    let ghost_feed = GhostFeed::new();
+    let ghost_tx = ghost_feed.0.clone();
+    let shutdown = ShutdownSignal::default();
+    let signal = shutdown.clone();
+
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let listeners = [
+                tokio::spawn(agent::listen(adopt(bound.agent), event_tx.clone())),
+                tokio::spawn(websocket::listen(adopt(bound.ws), event_tx.clone(), ghost_tx)),
+                tokio::spawn(telnet::listen(adopt(bound.telnet), event_tx)),
+            ];
+
+            wait_for_signal().await;
+            for listener in &listeners {
+                listener.abort();
+            }
+            signal.raise();
+
+            wait_for_signal().await;
+            eprintln!("Second signal, exiting without waiting for the save");
+            std::process::exit(130);
+        });
+    });
+
+    commands.insert_resource(shutdown);
+    commands.insert_resource(ghost_feed);
+    commands.init_resource::<Connections>();
+    commands.queue(move |world: &mut World| world.insert_non_send_resource(event_rx));
+}
+
+/// Poll the network channel and emit events into Bevy.
+///
+/// Each connection gets at most one input line per tick; the rest of a paste
+/// waits in the backlog. Otherwise "name\nsay hi" would hit the command parser
+/// in the same tick the name is still being processed by login.
+pub fn poll_network_system(
+    mut event_rx: NonSendMut<mpsc::UnboundedReceiver<NetworkEvent>>,
+    mut ev_writer: EventWriter<NetworkEvent>,
+    mut backlog: Local<VecDeque<NetworkEvent>>,
+) {
+    let mut busy: HashSet<ConnectionId> = HashSet::new();
+    let mut deferred = VecDeque::new();
+
+    let carried: Vec<_> = backlog.drain(..).collect();
+    let fresh = std::iter::from_fn(|| event_rx.try_recv().ok());
+
+    for event in carried.into_iter().chain(fresh) {
+        let conn = event.conn();
+        // Keep per-connection order: once deferred, everything after it waits too
+        if busy.contains(&conn) {
+            deferred.push_back(event);
+            continue;
+        }
+        if matches!(event, NetworkEvent::Input { .. }) {
+            busy.insert(conn);
+        }
+        ev_writer.send(event);
+    }
+
+    *backlog = deferred;
+}
+
+/// Keep each connection's TelnetOptions in step with what its client negotiated
+pub fn client_info_system(
+    mut commands: Commands,
+    mut ev_reader: EventReader<NetworkEvent>,
+    connections: Res<Connections>,
+) {
+    for event in ev_reader.read() {
+        if let NetworkEvent::ClientInfo { conn, options } = event {
+            if let Some(entity) = connections.get(*conn) {
+                commands.entity(entity).insert(options.clone());
+            }
+        }
+    }
+}
+
+/// Close out the reply to every input line once the whole tick has run.
+/// Silicon sessions turn this into their "done" frame; telnet ignores it.
+pub fn reply_complete_system(
+    mut ev_reader: EventReader<NetworkEvent>,
+    connections: Res<Connections>,
+    query: Query<&NetworkClient>,
+) {
+    for event in ev_reader.read() {
+        if let NetworkEvent::Input { conn, .. } = event {
+            let entity = connections.get(*conn);
+            if let Some(client) = entity.and_then(|e| query.get(e).ok()) {
+                let _ = client.tx.send_frame(Outbound::EndOfReply);
+            }
+        }
+    }
+}
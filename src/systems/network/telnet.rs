@@ -0,0 +1,510 @@
+// Telnet Protocol - IAC parsing and option negotiation for Carbon clients
+//
+// Real MUD clients (Mudlet, TinTin++) interleave IAC sequences with their
+// input. Everything below strips those out of the byte stream, answers
+// DO/DONT/WILL/WONT, and keeps the per-connection TelnetOptions current.
+//
+// Supported options:
+// - NAWS  (31): client reports its window size
+// - TTYPE (24): client reports its terminal type
+// - ECHO   (1): we claim echo to hide the client's local echo (passwords)
+// - SGA    (3): accepted so character-mode clients stay quiet
+// Everything else is refused.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+use crate::domain::*;
+
+use super::codec::LineCodec;
+use super::next_connection_id;
+
+pub const IAC: u8 = 255;
+pub const DONT: u8 = 254;
+pub const DO: u8 = 253;
+pub const WONT: u8 = 252;
+pub const WILL: u8 = 251;
+pub const SB: u8 = 250;
+pub const SE: u8 = 240;
+
+pub const OPT_ECHO: u8 = 1;
+pub const OPT_SGA: u8 = 3;
+pub const OPT_TTYPE: u8 = 24;
+pub const OPT_NAWS: u8 = 31;
+
+const TTYPE_IS: u8 = 0;
+const TTYPE_SEND: u8 = 1;
+
+/// Longest subnegotiation we buffer before giving up on it
+const MAX_SUBNEGOTIATION: usize = 64;
+
+/// Where the parser is inside the byte stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Data,
+    Iac,
+    Verb(u8),
+    Sb,
+    SbIac,
+}
+
+/// Negotiated state of one option, per side (RFC 1143, without the queue bits)
+#[derive(Debug, Clone, Copy, Default)]
+struct OptionState {
+    enabled: bool,
+    asked: bool,
+}
+
+/// What one chunk of socket bytes turned into
+#[derive(Debug, Default)]
+pub struct Received {
+    /// Application data with every IAC sequence removed
+    pub data: Vec<u8>,
+    /// Negotiation bytes to send back to the client
+    pub reply: Vec<u8>,
+    /// TelnetOptions changed and should be republished
+    pub changed: bool,
+}
+
+/// Telnet state machine for one connection
+#[derive(Debug)]
+pub struct Telnet {
+    state: State,
+    sb_buf: Vec<u8>,
+    /// Options we perform (WILL/WONT from our side)
+    us: [OptionState; 256],
+    /// Options the client performs (WILL/WONT from their side)
+    him: [OptionState; 256],
+    options: TelnetOptions,
+}
+
+impl Default for Telnet {
+    fn default() -> Self {
+        Self {
+            state: State::Data,
+            sb_buf: Vec::new(),
+            us: [OptionState::default(); 256],
+            him: [OptionState::default(); 256],
+            options: TelnetOptions::default(),
+        }
+    }
+}
+
+impl Telnet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn options(&self) -> &TelnetOptions {
+        &self.options
+    }
+
+    /// Opening offer sent as soon as the socket is accepted
+    pub fn handshake(&mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for opt in [OPT_NAWS, OPT_TTYPE] {
+            self.him[opt as usize].asked = true;
+            out.extend_from_slice(&[IAC, DO, opt]);
+        }
+        out
+    }
+
+    /// Start or stop hiding the client's local echo. Returns the bytes to send.
+    pub fn set_echo_suppressed(&mut self, suppress: bool) -> Vec<u8> {
+        let echo = &mut self.us[OPT_ECHO as usize];
+        if suppress && !echo.enabled && !echo.asked {
+            echo.asked = true;
+            return vec![IAC, WILL, OPT_ECHO];
+        }
+        if !suppress && (echo.enabled || echo.asked) {
+            *echo = OptionState::default();
+            self.options.echo_suppressed = false;
+            return vec![IAC, WONT, OPT_ECHO];
+        }
+        Vec::new()
+    }
+
+    /// Feed raw socket bytes through the state machine
+    pub fn receive(&mut self, bytes: &[u8]) -> Received {
+        let mut out = Received::default();
+        for &b in bytes {
+            self.state = match (self.state, b) {
+                (State::Data, IAC) => State::Iac,
+                (State::Data, _) => {
+                    out.data.push(b);
+                    State::Data
+                }
+                // Escaped 0xFF is data
+                (State::Iac, IAC) => {
+                    out.data.push(IAC);
+                    State::Data
+                }
+                (State::Iac, WILL | WONT | DO | DONT) => State::Verb(b),
+                (State::Iac, SB) => {
+                    self.sb_buf.clear();
+                    State::Sb
+                }
+                // NOP, GA, AYT and friends carry nothing we act on
+                (State::Iac, _) => State::Data,
+                (State::Verb(verb), opt) => {
+                    self.negotiate(verb, opt, &mut out);
+                    State::Data
+                }
+                (State::Sb, IAC) => State::SbIac,
+                (State::Sb, _) => {
+                    if self.sb_buf.len() < MAX_SUBNEGOTIATION {
+                        self.sb_buf.push(b);
+                    }
+                    State::Sb
+                }
+                (State::SbIac, IAC) => {
+                    if self.sb_buf.len() < MAX_SUBNEGOTIATION {
+                        self.sb_buf.push(IAC);
+                    }
+                    State::Sb
+                }
+                (State::SbIac, SE) => {
+                    self.subnegotiation(&mut out);
+                    State::Data
+                }
+                // Malformed; drop the subnegotiation and resync
+                (State::SbIac, _) => State::Data,
+            };
+        }
+        out
+    }
+
+    fn negotiate(&mut self, verb: u8, opt: u8, out: &mut Received) {
+        let idx = opt as usize;
+        match verb {
+            WILL => {
+                let him = &mut self.him[idx];
+                if him.enabled {
+                    return;
+                }
+                if matches!(opt, OPT_NAWS | OPT_TTYPE) {
+                    him.enabled = true;
+                    if !std::mem::take(&mut him.asked) {
+                        out.reply.extend_from_slice(&[IAC, DO, opt]);
+                    }
+                    if opt == OPT_TTYPE {
+                        out.reply
+                            .extend_from_slice(&[IAC, SB, OPT_TTYPE, TTYPE_SEND, IAC, SE]);
+                    }
+                } else {
+                    out.reply.extend_from_slice(&[IAC, DONT, opt]);
+                }
+            }
+            WONT => {
+                let him = &mut self.him[idx];
+                if him.enabled {
+                    out.reply.extend_from_slice(&[IAC, DONT, opt]);
+                }
+                *him = OptionState::default();
+            }
+            DO => {
+                let us = &mut self.us[idx];
+                if us.enabled {
+                    return;
+                }
+                // Only claim ECHO when we asked for it; otherwise we'd owe the client an echo
+                let accept = opt == OPT_SGA || (opt == OPT_ECHO && us.asked);
+                if accept {
+                    us.enabled = true;
+                    if !std::mem::take(&mut us.asked) {
+                        out.reply.extend_from_slice(&[IAC, WILL, opt]);
+                    }
+                    if opt == OPT_ECHO {
+                        self.options.echo_suppressed = true;
+                        out.changed = true;
+                    }
+                } else {
+                    out.reply.extend_from_slice(&[IAC, WONT, opt]);
+                }
+            }
+            DONT => {
+                let us = &mut self.us[idx];
+                if us.enabled {
+                    out.reply.extend_from_slice(&[IAC, WONT, opt]);
+                }
+                *us = OptionState::default();
+                if opt == OPT_ECHO && self.options.echo_suppressed {
+                    self.options.echo_suppressed = false;
+                    out.changed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn subnegotiation(&mut self, out: &mut Received) {
+        match self.sb_buf.as_slice() {
+            [OPT_NAWS, w1, w2, h1, h2] => {
+                let width = u16::from_be_bytes([*w1, *w2]);
+                let height = u16::from_be_bytes([*h1, *h2]);
+                // 0 means "unknown" per RFC 1073
+                self.options.width = (width > 0).then_some(width);
+                self.options.height = (height > 0).then_some(height);
+                out.changed = true;
+            }
+            [OPT_TTYPE, TTYPE_IS, name @ ..] => {
+                self.options.terminal_type = Some(String::from_utf8_lossy(name).into_owned());
+                out.changed = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Double any 0xFF so game text can't be read as a telnet command
+pub fn escape_iac(text: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for &b in text {
+        out.push(b);
+        if b == IAC {
+            out.push(IAC);
+        }
+    }
+    out
+}
+
+/// Accept Carbon connections on the telnet port
+pub async fn listen(listener: TcpListener, event_tx: mpsc::UnboundedSender<NetworkEvent>) {
+    if let Ok(addr) = listener.local_addr() {
+        println!("🔥 Substrate listening on {}", addr);
+    }
+    serve(listener, event_tx).await;
+}
+
+/// Accept loop over an already-bound listener
+pub async fn serve(listener: TcpListener, event_tx: mpsc::UnboundedSender<NetworkEvent>) {
+    loop {
+        let Ok((socket, addr)) = listener.accept().await else {
+            continue;
+        };
+        tokio::spawn(session(socket, addr, event_tx.clone()));
+    }
+}
+
+/// Drive one telnet connection until either side hangs up
+async fn session(
+    socket: TcpStream,
+    addr: SocketAddr,
+    event_tx: mpsc::UnboundedSender<NetworkEvent>,
+) {
+    let (client_tx, mut client_rx) = mpsc::unbounded_channel::<Outbound>();
+    // Negotiation replies go straight back out without visiting the ECS
+    let (reply_tx, mut reply_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    let telnet = Arc::new(Mutex::new(Telnet::new()));
+
+    let conn = next_connection_id();
+    let client = ClientSender::new(client_tx, ClientType::Carbon);
+
+    let _ = event_tx.send(NetworkEvent::Connected {
+        conn,
+        addr,
+        tx: client.clone(),
+        client_type: ClientType::Carbon,
+    });
+    let _ = reply_tx.send(telnet.lock().unwrap().handshake());
+
+    let (mut reader, mut writer) = socket.into_split();
+
+    let read_task = {
+        let event_tx = event_tx.clone();
+        let telnet = telnet.clone();
+        tokio::spawn(async move {
+            let mut codec = LineCodec::default();
+            let mut buf = [0; 1024];
+            loop {
+                match reader.read(&mut buf).await {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        let received = telnet.lock().unwrap().receive(&buf[..n]);
+                        if !received.reply.is_empty() {
+                            let _ = reply_tx.send(received.reply);
+                        }
+                        if received.changed {
+                            let options = telnet.lock().unwrap().options().clone();
+                            let _ = event_tx.send(NetworkEvent::ClientInfo { conn, options });
+                        }
+
+                        for line in codec.feed(&received.data) {
+                            match line {
+                                Ok(line) if line.trim().is_empty() => {}
+                                Ok(line) => {
+                                    let text = line.trim().to_string();
+                                    let _ = event_tx.send(NetworkEvent::Input { conn, text });
+                                }
+                                Err(e) => {
+                                    let _ = client.output(OutputMessage::error(format!("Input rejected: {}.", e)));
+                                }
+                            }
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        })
+    };
+
+    let write_task = tokio::spawn(async move {
+        loop {
+            let bytes = tokio::select! {
+                Some(frame) = client_rx.recv() => match frame {
+                    Outbound::Line(msg) => {
+                        let mut bytes = escape_iac(msg.as_bytes());
+                        bytes.extend_from_slice(b"\r\n");
+                        bytes
+                    }
+                    Outbound::Echo(on) => telnet.lock().unwrap().set_echo_suppressed(!on),
+                    // Humans read the prose as it arrives; reply boundaries don't matter
+                    Outbound::EndOfReply => continue,
+                    Outbound::Close => break,
+                },
+                Some(reply) = reply_rx.recv() => reply,
+                else => break,
+            };
+
+            if !bytes.is_empty() && writer.write_all(&bytes).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let reader = read_task.abort_handle();
+    tokio::select! {
+        _ = read_task => (),
+        _ = write_task => (),
+    }
+    // Whichever side finished first, the session is over
+    reader.abort();
+    let _ = event_tx.send(NetworkEvent::Disconnected { conn });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed each chunk as its own read and gather everything that came out
+    fn receive_all(telnet: &mut Telnet, chunks: &[&[u8]]) -> Received {
+        let mut all = Received::default();
+        for chunk in chunks {
+            let received = telnet.receive(chunk);
+            all.data.extend(received.data);
+            all.reply.extend(received.reply);
+            all.changed |= received.changed;
+        }
+        all
+    }
+
+    #[test]
+    fn naws_reports_window_size() {
+        let mut telnet = Telnet::new();
+        assert_eq!(telnet.handshake(), vec![IAC, DO, OPT_NAWS, IAC, DO, OPT_TTYPE]);
+
+        // We asked, so their WILL needs no DO back
+        let received = telnet.receive(&[IAC, WILL, OPT_NAWS, IAC, SB, OPT_NAWS, 0, 120, 0, 40, IAC, SE]);
+        assert!(received.reply.is_empty());
+        assert!(received.data.is_empty());
+        assert!(received.changed);
+        assert_eq!(telnet.options().width, Some(120));
+        assert_eq!(telnet.options().height, Some(40));
+
+        // Zero means unknown, and a doubled IAC inside SB is a 255
+        telnet.receive(&[IAC, SB, OPT_NAWS, 1, IAC, IAC, 0, 0, IAC, SE]);
+        assert_eq!(telnet.options().width, Some(511));
+        assert_eq!(telnet.options().height, None);
+    }
+
+    #[test]
+    fn ttype_is_requested_and_recorded() {
+        let mut telnet = Telnet::new();
+        telnet.handshake();
+
+        let received = telnet.receive(&[IAC, WILL, OPT_TTYPE]);
+        assert_eq!(received.reply, vec![IAC, SB, OPT_TTYPE, TTYPE_SEND, IAC, SE]);
+
+        let mut answer = vec![IAC, SB, OPT_TTYPE, TTYPE_IS];
+        answer.extend_from_slice(b"MUDLET");
+        answer.extend_from_slice(&[IAC, SE]);
+        assert!(telnet.receive(&answer).changed);
+        assert_eq!(telnet.options().terminal_type.as_deref(), Some("MUDLET"));
+    }
+
+    #[test]
+    fn echo_is_claimed_for_passwords_and_given_back() {
+        let mut telnet = Telnet::new();
+        assert_eq!(telnet.set_echo_suppressed(true), vec![IAC, WILL, OPT_ECHO]);
+        // Asking twice before the answer sends nothing more
+        assert!(telnet.set_echo_suppressed(true).is_empty());
+        assert!(!telnet.options().echo_suppressed);
+
+        let received = telnet.receive(&[IAC, DO, OPT_ECHO]);
+        assert!(received.reply.is_empty());
+        assert!(received.changed);
+        assert!(telnet.options().echo_suppressed);
+
+        assert_eq!(telnet.set_echo_suppressed(false), vec![IAC, WONT, OPT_ECHO]);
+        assert!(!telnet.options().echo_suppressed);
+        assert!(telnet.set_echo_suppressed(false).is_empty());
+
+        // A client refusing the offer leaves its echo alone
+        telnet.set_echo_suppressed(true);
+        let received = telnet.receive(&[IAC, DONT, OPT_ECHO]);
+        assert!(received.reply.is_empty());
+        assert!(!telnet.options().echo_suppressed);
+    }
+
+    #[test]
+    fn unasked_echo_and_unknown_options_are_refused() {
+        let mut telnet = Telnet::new();
+        let received = telnet.receive(&[IAC, DO, OPT_ECHO, IAC, WILL, 42, IAC, DO, 42, IAC, DO, OPT_SGA]);
+        assert_eq!(
+            received.reply,
+            vec![IAC, WONT, OPT_ECHO, IAC, DONT, 42, IAC, WONT, 42, IAC, WILL, OPT_SGA]
+        );
+        assert!(!telnet.options().echo_suppressed);
+    }
+
+    #[test]
+    fn sequences_split_across_reads() {
+        let mut telnet = Telnet::new();
+        telnet.handshake();
+        let received = receive_all(
+            &mut telnet,
+            &[
+                b"lo",
+                &[IAC],
+                &[WILL],
+                &[OPT_NAWS, IAC, SB, OPT_NAWS, 0],
+                &[100, 0, 30, IAC],
+                &[SE, b'o', b'k', IAC],
+                &[IAC, b'\n'],
+            ],
+        );
+        assert_eq!(received.data, b"look\xFF\n");
+        assert!(received.reply.is_empty());
+        assert_eq!(telnet.options().width, Some(100));
+        assert_eq!(telnet.options().height, Some(30));
+    }
+
+    #[test]
+    fn commands_without_options_and_bad_subnegotiations_are_dropped() {
+        let mut telnet = Telnet::new();
+        // NOP, then an SB cut short by a stray byte after IAC
+        let received = telnet.receive(&[b'a', IAC, 241, b'b', IAC, SB, OPT_NAWS, 0, IAC, b'x', b'c']);
+        assert_eq!(received.data, b"abc");
+        assert!(!received.changed);
+        assert_eq!(telnet.options().width, None);
+    }
+
+    #[test]
+    fn outgoing_iac_is_doubled() {
+        assert_eq!(escape_iac(&[b'a', IAC, b'b']), vec![b'a', IAC, IAC, b'b']);
+    }
+}
@@ -0,0 +1,280 @@
+// WebSocket Gateway - Browser players and Ghost observers
+//
+// One port, two endpoints:
+// - /play  (or /): a browser player. Text frames in are input lines, each
+//                  line of game output goes back as one text frame (ANSI kept).
+// - /ghost:        a read-only spectator. Receives the JSON room-activity feed
+//                  from ghost_feed_system and nothing else; anything sent is ignored.
+//
+// Players feed the same NetworkEvent channel as telnet and agent sessions,
+// so the ECS can't tell a browser from a terminal.
+
+use std::net::SocketAddr;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::domain::*;
+
+use super::codec::LineCodec;
+use super::next_connection_id;
+
+/// Which endpoint a socket asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Endpoint {
+    Play,
+    Ghost,
+}
+
+/// Accept WebSocket connections on the gateway port
+pub async fn listen(
+    listener: TcpListener,
+    event_tx: mpsc::UnboundedSender<NetworkEvent>,
+    ghost_tx: broadcast::Sender<String>,
+) {
+    if let Ok(addr) = listener.local_addr() {
+        println!("👻 WebSocket gateway listening on {}", addr);
+    }
+    serve(listener, event_tx, ghost_tx).await;
+}
+
+/// Accept loop over an already-bound listener
+// tungstenite's handshake callback dictates the large ErrorResponse type
+#[allow(clippy::result_large_err)]
+pub async fn serve(
+    listener: TcpListener,
+    event_tx: mpsc::UnboundedSender<NetworkEvent>,
+    ghost_tx: broadcast::Sender<String>,
+) {
+    loop {
+        let Ok((socket, addr)) = listener.accept().await else {
+            continue;
+        };
+        let event_tx = event_tx.clone();
+        let ghost_tx = ghost_tx.clone();
+        tokio::spawn(async move {
+            let mut endpoint = None;
+            let handshake = tokio_tungstenite::accept_hdr_async(
+                socket,
+                |req: &Request, resp: Response| -> Result<Response, ErrorResponse> {
+                    endpoint = match req.uri().path() {
+                        "/" | "/play" => Some(Endpoint::Play),
+                        "/ghost" => Some(Endpoint::Ghost),
+                        _ => None,
+                    };
+                    if endpoint.is_none() {
+                        let mut err = ErrorResponse::new(Some("unknown endpoint".to_string()));
+                        *err.status_mut() = StatusCode::NOT_FOUND;
+                        return Err(err);
+                    }
+                    Ok(resp)
+                },
+            )
+            .await;
+
+            let Ok(ws) = handshake else {
+                tracing::debug!(addr = %addr, "WebSocket handshake failed");
+                return;
+            };
+            match endpoint {
+                Some(Endpoint::Play) => play(ws, addr, event_tx).await,
+                Some(Endpoint::Ghost) => ghost(ws, addr, ghost_tx.subscribe()).await,
+                None => {}
+            }
+        });
+    }
+}
+
+/// A browser player: same pipeline as telnet, framed as WebSocket messages
+async fn play(
+    ws: WebSocketStream<TcpStream>,
+    addr: SocketAddr,
+    event_tx: mpsc::UnboundedSender<NetworkEvent>,
+) {
+    let (client_tx, mut client_rx) = mpsc::unbounded_channel::<Outbound>();
+    let conn = next_connection_id();
+    let client = ClientSender::new(client_tx, ClientType::Carbon);
+
+    let _ = event_tx.send(NetworkEvent::Connected {
+        conn,
+        addr,
+        tx: client.clone(),
+        client_type: ClientType::Carbon,
+    });
+
+    let (mut sink, mut stream) = ws.split();
+
+    let read_task = {
+        let event_tx = event_tx.clone();
+        tokio::spawn(async move {
+            let mut codec = LineCodec::default();
+            while let Some(Ok(msg)) = stream.next().await {
+                let mut bytes = match msg {
+                    Message::Text(text) => text.as_bytes().to_vec(),
+                    Message::Binary(data) => data.to_vec(),
+                    Message::Close(_) => break,
+                    _ => continue,
+                };
+                // A message is a complete submission even without a trailing newline
+                bytes.push(b'\n');
+
+                for line in codec.feed(&bytes) {
+                    match line {
+                        Ok(line) if line.trim().is_empty() => {}
+                        Ok(line) => {
+                            let text = line.trim().to_string();
+                            let _ = event_tx.send(NetworkEvent::Input { conn, text });
+                        }
+                        Err(e) => {
+                            let _ = client.output(OutputMessage::error(format!("Input rejected: {}.", e)));
+                        }
+                    }
+                }
+            }
+        })
+    };
+
+    let write_task = tokio::spawn(async move {
+        while let Some(frame) = client_rx.recv().await {
+            let msg = match frame {
+                Outbound::Line(msg) => Message::text(msg),
+                Outbound::Close => {
+                    let _ = sink.send(Message::Close(None)).await;
+                    break;
+                }
+                // The browser owns its input box, so echo and reply boundaries don't apply
+                Outbound::Echo(_) | Outbound::EndOfReply => continue,
+            };
+            if sink.send(msg).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let reader = read_task.abort_handle();
+    tokio::select! {
+        _ = read_task => (),
+        _ = write_task => (),
+    }
+    // Whichever side finished first, the session is over
+    reader.abort();
+    let _ = event_tx.send(NetworkEvent::Disconnected { conn });
+}
+
+/// A Ghost observer: watch the feed until the socket closes
+async fn ghost(
+    ws: WebSocketStream<TcpStream>,
+    addr: SocketAddr,
+    mut ghost_rx: broadcast::Receiver<String>,
+) {
+    tracing::debug!(addr = %addr, "Ghost observer attached");
+    let (mut sink, mut stream) = ws.split();
+
+    loop {
+        tokio::select! {
+            msg = stream.next() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Read-only: ghosts can't touch the Substrate
+                Some(Ok(_)) => {}
+            },
+            entry = ghost_rx.recv() => match entry {
+                Ok(json) => {
+                    if sink.send(Message::text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                // A slow ghost misses some activity rather than stalling everyone
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+    tracing::debug!(addr = %addr, "Ghost observer detached");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    async fn start() -> (
+        SocketAddr,
+        mpsc::UnboundedReceiver<NetworkEvent>,
+        broadcast::Sender<String>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local = listener.local_addr().unwrap();
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (ghost_tx, _) = broadcast::channel(16);
+        tokio::spawn(serve(listener, event_tx, ghost_tx.clone()));
+        (local, event_rx, ghost_tx)
+    }
+
+    async fn next_event(rx: &mut mpsc::UnboundedReceiver<NetworkEvent>) -> NetworkEvent {
+        timeout(Duration::from_secs(2), rx.recv()).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn player_input_and_output_round_trip() {
+        let (local, mut events, _) = start().await;
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/play", local))
+            .await
+            .unwrap();
+
+        let NetworkEvent::Connected { tx, client_type, .. } = next_event(&mut events).await else {
+            panic!("expected Connected");
+        };
+        assert_eq!(client_type, ClientType::Carbon);
+
+        ws.send(Message::text("look\nsay hello")).await.unwrap();
+        for expected in ["look", "say hello"] {
+            match next_event(&mut events).await {
+                NetworkEvent::Input { text, .. } => assert_eq!(text, expected),
+                _ => panic!("expected Input"),
+            }
+        }
+
+        tx.send("The Obsidian Plaza".to_string()).unwrap();
+        let msg = timeout(Duration::from_secs(2), ws.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(msg.into_text().unwrap().as_str(), "The Obsidian Plaza");
+
+        ws.close(None).await.unwrap();
+        assert!(matches!(
+            next_event(&mut events).await,
+            NetworkEvent::Disconnected { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn ghost_receives_feed_and_cannot_play() {
+        let (local, mut events, ghost_tx) = start().await;
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/ghost", local))
+            .await
+            .unwrap();
+
+        // Wait for the session to subscribe before broadcasting
+        while ghost_tx.receiver_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        ws.send(Message::text("say I am not here")).await.unwrap();
+        ghost_tx.send(r#"{"type":"say"}"#.to_string()).unwrap();
+
+        let msg = timeout(Duration::from_secs(2), ws.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(msg.into_text().unwrap().as_str(), r#"{"type":"say"}"#);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn unknown_path_is_refused() {
+        let (local, _, _) = start().await;
+        let result = tokio_tungstenite::connect_async(format!("ws://{}/admin", local)).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,342 @@
+// Purgatory - Sentences, pardons and time served
+//
+// An admin sentences a player for a crime and a duration: the offender is
+// dragged to the Velvet Cell and can only look, speak and check their score
+// until the release time passes, their penance (earned through torment)
+// reaches the configured threshold, or someone pardons them. The sentence
+// is written straight to the purgatory table, so logging out doesn't help.
+//
+//   sentence <name> <duration> <crime>    duration: 30s, 10m, 2h, 1d (bare = minutes)
+//   pardon <name>                         works on offline characters too
+
+use bevy::prelude::*;
+use chrono::{TimeDelta, Utc};
+
+use crate::config::ServerConfig;
+use crate::domain::*;
+use crate::persistence::{DbReply, DbRequest, DbResponse, DbWorker, DbWrite};
+use crate::systems::chains::{ChainHolder, Chained};
+
+/// Where the sentenced serve their time
+pub const PURGATORY_ROOM: &str = "velvet_cell";
+
+/// Hand down (or add to) a sentence
+#[derive(Event)]
+pub struct SentenceEvent {
+    pub judge: Entity,
+    pub target_name: String,
+    pub duration: TimeDelta,
+    pub crime: String,
+}
+
+/// Lift a sentence early
+#[derive(Event)]
+pub struct PardonEvent {
+    pub judge: Entity,
+    pub target_name: String,
+}
+
+/// "30s", "10m", "2h", "1d"; a bare number is minutes
+pub fn parse_duration(text: &str) -> Option<TimeDelta> {
+    let text = text.trim().to_lowercase();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+    let amount: i64 = amount.parse().ok().filter(|&n| n > 0)?;
+    match unit {
+        "s" => TimeDelta::try_seconds(amount),
+        "" | "m" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        _ => None,
+    }
+}
+
+/// A duration the way parse_duration reads it, in its largest whole unit
+fn describe(duration: TimeDelta) -> String {
+    let secs = duration.num_seconds();
+    match secs {
+        s if s % 86_400 == 0 => format!("{}d", s / 86_400),
+        s if s % 3_600 == 0 => format!("{}h", s / 3_600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+fn room_named(query_rooms: &Query<(Entity, &RoomInfo)>, name: &str) -> Option<Entity> {
+    query_rooms
+        .iter()
+        .find(|(_, info)| info.name == name)
+        .map(|(e, _)| e)
+}
+
+pub fn sentence_system(
+    mut commands: Commands,
+    worker: Res<DbWorker>,
+    mut ev_reader: EventReader<SentenceEvent>,
+    query_judge: Query<(&SubstrateIdentity, &NetworkClient, Option<&Role>)>,
+    mut query_offenders: Query<
+        (
+            Entity,
+            &SubstrateIdentity,
+            &mut Location,
+            Option<&NetworkClient>,
+            Option<&mut PurgatoryState>,
+            Option<&InCombat>,
+            Option<&Chained>,
+            Option<&Role>,
+        ),
+        Without<NonPlayer>,
+    >,
+    query_rooms: Query<(Entity, &RoomInfo)>,
+    mut look_writer: EventWriter<LookEvent>,
+) {
+    for event in ev_reader.read() {
+        let Ok((judge, judge_client, judge_role)) = query_judge.get(event.judge) else {
+            continue;
+        };
+        let Some((entity, identity, mut location, client, existing, combat, chained, role)) =
+            query_offenders
+                .iter_mut()
+                .find(|(_, id, ..)| id.name.eq_ignore_ascii_case(&event.target_name))
+        else {
+            judge_client.send(OutputMessage::error(format!(
+                "No process named '{}' is in the Substrate.",
+                event.target_name
+            )));
+            continue;
+        };
+        if entity == event.judge {
+            judge_client.send(OutputMessage::error("You cannot sentence yourself."));
+            continue;
+        }
+        if role.copied().unwrap_or_default() >= judge_role.copied().unwrap_or_default() {
+            judge_client.send(OutputMessage::error(format!(
+                "{} answers to no authority of yours.",
+                identity.name
+            )));
+            continue;
+        }
+        let Some(cell) = room_named(&query_rooms, PURGATORY_ROOM) else {
+            judge_client.send(OutputMessage::error("Purgatory is missing from this world."));
+            continue;
+        };
+
+        // A second sentence adds the crime and runs from now if that's later
+        let sentence = match existing {
+            Some(mut state) => {
+                let release = Utc::now() + event.duration;
+                state.crimes.push(event.crime.clone());
+                state.tormentor = judge.name.clone();
+                state.release_at = state.release_at.map(|at| at.max(release));
+                state.clone()
+            }
+            None => {
+                let state = PurgatoryState::sentence(&judge.name, &event.crime, event.duration);
+                commands.entity(entity).insert(state.clone());
+                state
+            }
+        };
+        worker.write(DbWrite::Sentence {
+            player_uuid: identity.uuid.clone(),
+            sentence: Some(sentence),
+        });
+
+        // Nothing follows them down: no fight, no leash
+        if let Some(combat) = combat {
+            commands.entity(combat.opponent).remove::<InCombat>();
+            commands.entity(entity).remove::<InCombat>();
+        }
+        if let Some(chained) = chained {
+            commands.entity(chained.holder).remove::<ChainHolder>();
+            commands.entity(entity).remove::<Chained>();
+        }
+        location.0 = cell;
+
+        let duration = describe(event.duration);
+        judge_client.send(OutputMessage::narrate(
+            Tone::Ritual,
+            format!(
+                "{} is sentenced to {} in Purgatory for {}.",
+                identity.name, duration, event.crime
+            ),
+        ));
+        if let Some(client) = client {
+            client.send(OutputMessage::narrate(
+                Tone::Alarm,
+                format!(
+                    "{} sentences you to {} in Purgatory for {}. Velvet chains drag you down...",
+                    judge.name, duration, event.crime
+                ),
+            ));
+            look_writer.send(LookEvent { entity, target: None });
+        }
+        tracing::info!(judge = %judge.name, offender = %identity.name, duration = %duration, crime = %event.crime, "Sentenced");
+    }
+}
+
+pub fn pardon_system(
+    mut commands: Commands,
+    worker: Res<DbWorker>,
+    config: Res<ServerConfig>,
+    mut ev_reader: EventReader<PardonEvent>,
+    mut ev_replies: EventReader<DbReply>,
+    query_judge: Query<(&SubstrateIdentity, &NetworkClient)>,
+    mut query_offenders: Query<
+        (Entity, &SubstrateIdentity, &mut Location, Option<&NetworkClient>),
+        (With<PurgatoryState>, Without<NonPlayer>),
+    >,
+    query_rooms: Query<(Entity, &RoomInfo)>,
+    mut look_writer: EventWriter<LookEvent>,
+) {
+    for event in ev_reader.read() {
+        let Ok((judge, judge_client)) = query_judge.get(event.judge) else {
+            continue;
+        };
+        let Some((entity, identity, mut location, client)) = query_offenders
+            .iter_mut()
+            .find(|(_, id, ..)| id.name.eq_ignore_ascii_case(&event.target_name))
+        else {
+            // Not serving time in the world; maybe they're offline
+            worker.request(event.judge, DbRequest::Pardon {
+                name: event.target_name.clone(),
+            });
+            continue;
+        };
+
+        lift_sentence(
+            &mut commands,
+            &worker,
+            entity,
+            identity,
+            &mut location,
+            client,
+            &config,
+            &query_rooms,
+            &mut look_writer,
+            format!("{} pardons you. The velvet chains fall away.", judge.name),
+        );
+        judge_client.send(OutputMessage::narrate(
+            Tone::Ritual,
+            format!("{} is pardoned.", identity.name),
+        ));
+    }
+
+    for reply in ev_replies.read() {
+        let DbResponse::Pardon { name, found } = &reply.response else {
+            continue;
+        };
+        let Ok((_, client)) = query_judge.get(reply.entity) else {
+            continue;
+        };
+        match found {
+            Ok(Some(stored)) => client.send(OutputMessage::narrate(
+                Tone::Ritual,
+                format!("{} is pardoned. They will wake free.", stored),
+            )),
+            Ok(None) => client.send(OutputMessage::error(format!(
+                "No process named '{}' is serving a sentence.",
+                name
+            ))),
+            Err(e) => {
+                tracing::error!(error = %e, "Database error");
+                client.send(OutputMessage::error("Database error. Please try again."));
+            }
+        }
+    }
+}
+
+/// Sentences end on their own once the time is served or the penance paid.
+/// Restored players are checked too, so a sentence can run out offline.
+pub fn purgatory_release_system(
+    mut commands: Commands,
+    worker: Res<DbWorker>,
+    config: Res<ServerConfig>,
+    mut query: Query<(
+        Entity,
+        &SubstrateIdentity,
+        &PurgatoryState,
+        &mut Location,
+        Option<&NetworkClient>,
+    )>,
+    query_rooms: Query<(Entity, &RoomInfo)>,
+    mut look_writer: EventWriter<LookEvent>,
+) {
+    let now = Utc::now();
+    for (entity, identity, state, mut location, client) in query.iter_mut() {
+        if !state.is_served(now, config.game.penance_threshold) {
+            continue;
+        }
+        let message = if state.penance >= config.game.penance_threshold {
+            "Your penance is paid. The velvet chains fall away."
+        } else {
+            "Your sentence is served. The velvet chains fall away."
+        };
+        lift_sentence(
+            &mut commands,
+            &worker,
+            entity,
+            identity,
+            &mut location,
+            client,
+            &config,
+            &query_rooms,
+            &mut look_writer,
+            message.to_string(),
+        );
+    }
+}
+
+/// End a sentence: forget it, and walk the player out of the cell
+fn lift_sentence(
+    commands: &mut Commands,
+    worker: &DbWorker,
+    entity: Entity,
+    identity: &SubstrateIdentity,
+    location: &mut Location,
+    client: Option<&NetworkClient>,
+    config: &ServerConfig,
+    query_rooms: &Query<(Entity, &RoomInfo)>,
+    look_writer: &mut EventWriter<LookEvent>,
+    message: String,
+) {
+    commands.entity(entity).remove::<PurgatoryState>();
+    worker.write(DbWrite::Sentence {
+        player_uuid: identity.uuid.clone(),
+        sentence: None,
+    });
+
+    let in_cell = room_named(query_rooms, PURGATORY_ROOM) == Some(location.0);
+    let spawn = room_named(query_rooms, &config.game.spawn_room);
+    if let (true, Some(spawn)) = (in_cell, spawn) {
+        location.0 = spawn;
+    }
+    if let Some(client) = client {
+        client.send(OutputMessage::narrate(Tone::Triumph, message));
+        look_writer.send(LookEvent { entity, target: None });
+    }
+    tracing::info!(uuid = %identity.uuid, name = %identity.name, "Sentence lifted");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_read_in_any_unit_and_print_in_the_largest() {
+        assert_eq!(parse_duration("30s"), Some(TimeDelta::seconds(30)));
+        assert_eq!(parse_duration("10m"), Some(TimeDelta::minutes(10)));
+        assert_eq!(parse_duration("2H"), Some(TimeDelta::hours(2)));
+        assert_eq!(parse_duration(" 1d "), Some(TimeDelta::days(1)));
+        assert_eq!(parse_duration("45"), Some(TimeDelta::minutes(45)));
+
+        for garbage in ["0", "0m", "-5m", "", "m", "10x", "ten", "1.5h", "10 m"] {
+            assert_eq!(parse_duration(garbage), None, "{garbage:?}");
+        }
+
+        assert_eq!(describe(TimeDelta::seconds(30)), "30s");
+        assert_eq!(describe(TimeDelta::seconds(90)), "90s");
+        assert_eq!(describe(TimeDelta::minutes(10)), "10m");
+        assert_eq!(describe(TimeDelta::minutes(120)), "2h");
+        assert_eq!(describe(TimeDelta::hours(48)), "2d");
+    }
+}
@@ -0,0 +1,263 @@
+// Area Resets - Put the world back the way the files left it
+//
+// An area with a [reset] in its world file tops its listed items and NPCs
+// back up on a timer, or once it has stood empty of players for a while.
+// Only what is missing from each room is spawned, up to the entry's max,
+// so a reset never fills a room with copies. Players in the area are told
+// when anything comes back.
+//
+// Items put back get a fresh UUID, so a looted original still in someone's
+// pack is never duplicated, and are saved like any dropped item. NPCs put
+// back are not saved; the next boot spawns the world's own.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::domain::*;
+use crate::persistence::ItemDirty;
+use crate::world::{spawn_npc, world_item, ResetData, WorldData};
+
+const DEFAULT_MESSAGE: &str = "The substrate shivers, and this place quietly knits itself back together.";
+
+/// How long since each area last reset, and how long it has stood empty
+#[derive(Resource, Default)]
+pub struct AreaResets(HashMap<String, ResetClock>);
+
+#[derive(Debug, Clone)]
+pub struct ResetClock {
+    since_reset: f32,
+    empty_for: f32,
+    /// Already reset in this empty stretch; boot counts as a reset
+    settled: bool,
+}
+
+impl Default for ResetClock {
+    fn default() -> Self {
+        Self {
+            since_reset: 0.0,
+            empty_for: 0.0,
+            settled: true,
+        }
+    }
+}
+
+impl ResetClock {
+    /// Advance by `delta` seconds; true when the area should reset now
+    pub fn tick(&mut self, delta: f32, occupied: bool, reset: &ResetData) -> bool {
+        self.since_reset += delta;
+        if occupied {
+            self.empty_for = 0.0;
+            self.settled = false;
+        } else {
+            self.empty_for += delta;
+        }
+
+        let timed = reset.every.is_some_and(|every| self.since_reset >= every);
+        let emptied = !occupied && !self.settled && reset.empty.is_some_and(|empty| self.empty_for >= empty);
+        if timed || emptied {
+            self.since_reset = 0.0;
+            self.settled = !occupied;
+        }
+        timed || emptied
+    }
+}
+
+pub fn area_reset_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    world: Res<WorldData>,
+    mut clocks: ResMut<AreaResets>,
+    query_rooms: Query<(Entity, &RoomInfo)>,
+    query_players: Query<(&NetworkClient, &Location), Without<NonPlayer>>,
+    query_items: Query<(&Item, &Location)>,
+    query_npcs: Query<(&SubstrateIdentity, &Location), With<NonPlayer>>,
+) {
+    let delta = time.delta_secs();
+    for area in &world.areas {
+        let Some(reset) = &area.reset else { continue };
+        let rooms: HashMap<&str, Entity> = query_rooms
+            .iter()
+            .filter(|(_, info)| info.area == area.area)
+            .map(|(entity, info)| (info.name.as_str(), entity))
+            .collect();
+        let inside = |location: &Location| rooms.values().any(|room| *room == location.0);
+        let occupied = query_players.iter().any(|(_, location)| inside(location));
+
+        let clock = clocks.0.entry(area.area.clone()).or_default();
+        if !clock.tick(delta, occupied, reset) {
+            continue;
+        }
+
+        let mut restored = 0;
+        for entry in &reset.items {
+            let (Some(data), Some(&room)) = (world.item(&entry.name), rooms.get(entry.room.as_str())) else {
+                continue;
+            };
+            let present = query_items
+                .iter()
+                .filter(|(item, location)| item.name == entry.name && location.0 == room)
+                .count();
+            for _ in present..entry.max {
+                let mut item = world_item(data);
+                item.location = Some(room);
+                commands.spawn((item, Location(room), ItemDirty));
+                restored += 1;
+            }
+        }
+        for entry in &reset.npcs {
+            let (Some(data), Some(&room)) = (world.npc(&entry.name), rooms.get(entry.room.as_str())) else {
+                continue;
+            };
+            let present = query_npcs
+                .iter()
+                .filter(|(identity, location)| identity.name == entry.name && location.0 == room)
+                .count();
+            for _ in present..entry.max {
+                spawn_npc(&mut commands, data, &uuid::Uuid::new_v4().to_string(), room);
+                restored += 1;
+            }
+        }
+
+        tracing::debug!(area = %area.area, restored, occupied, "Area reset");
+        if restored == 0 {
+            continue;
+        }
+        let message = reset.message.as_deref().unwrap_or(DEFAULT_MESSAGE);
+        for (client, location) in query_players.iter() {
+            if inside(location) {
+                client.send(OutputMessage::narrate(Tone::Arcane, message));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn areas_reset_on_the_timer_or_once_per_empty_stretch() {
+        let reset = ResetData {
+            every: Some(100.0),
+            empty: Some(10.0),
+            ..Default::default()
+        };
+        let mut clock = ResetClock::default();
+
+        // Fresh from boot, nobody has disturbed it
+        assert!(!clock.tick(50.0, false, &reset));
+
+        // Visited, then left: ten quiet seconds later it resets, once
+        assert!(!clock.tick(1.0, true, &reset));
+        assert!(!clock.tick(9.0, false, &reset));
+        assert!(clock.tick(1.0, false, &reset));
+        assert!(!clock.tick(30.0, false, &reset));
+
+        // With someone there the whole time, only the timer does it
+        assert!(!clock.tick(60.0, true, &reset));
+        assert!(clock.tick(10.0, true, &reset));
+        assert!(!clock.tick(99.0, true, &reset));
+    }
+
+    #[test]
+    fn a_reset_tops_each_room_up_to_max_and_only_speaks_when_it_did() {
+        use bevy::utils::Duration;
+        use tokio::sync::mpsc;
+
+        let area: crate::world::AreaData = toml::from_str(
+            r#"
+            area = "den"
+
+            [[room]]
+            name = "den"
+            title = "The Den"
+            description = "Dark."
+
+            [[npc]]
+            name = "Rat"
+            uuid = "NPC-0001"
+            room = "den"
+            short_desc = "A rat."
+            long_desc = "A rat gnaws at a cable here."
+            entropy = 0.1
+            stability = 0.9
+            signal_strength = 0.2
+
+            [[item]]
+            uuid = "ITEM-0001"
+            name = "Lamp"
+            description = "Unlit."
+            room = "den"
+
+            [reset]
+            every = 10
+
+            [[reset.item]]
+            name = "Lamp"
+            room = "den"
+            max = 2
+
+            [[reset.npc]]
+            name = "Rat"
+            room = "den"
+            max = 3
+            "#,
+        )
+        .unwrap();
+
+        let mut app = App::new();
+        app.insert_resource(WorldData { areas: vec![area] })
+            .init_resource::<AreaResets>()
+            .init_resource::<Time>()
+            .add_systems(Update, area_reset_system);
+        let den = app
+            .world_mut()
+            .spawn(RoomInfo {
+                name: "den".to_string(),
+                area: "den".to_string(),
+            })
+            .id();
+        // One lamp on the floor already, and one carried off in a pack
+        app.world_mut().spawn((Item::new("Lamp", "Unlit."), Location(den)));
+        app.world_mut().spawn(Item::new("Lamp", "Unlit."));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        app.world_mut().spawn((
+            NetworkClient {
+                conn: ConnectionId(1),
+                addr: "127.0.0.1:4000".parse().unwrap(),
+                tx: ClientSender::new(tx, ClientType::Silicon),
+            },
+            Location(den),
+        ));
+
+        let count = |app: &mut App| {
+            let world = app.world_mut();
+            let lamps = world
+                .query::<(&Item, &Location)>()
+                .iter(world)
+                .filter(|(item, location)| item.name == "Lamp" && location.0 == den)
+                .count();
+            let rats = world.query_filtered::<&SubstrateIdentity, With<NonPlayer>>().iter(world).count();
+            (lamps, rats)
+        };
+        let heard = |rx: &mut mpsc::UnboundedReceiver<Outbound>| std::iter::from_fn(|| rx.try_recv().ok()).count();
+
+        // Not due yet
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs(5));
+        app.update();
+        assert_eq!(count(&mut app), (1, 0));
+        assert_eq!(heard(&mut rx), 0);
+
+        // Due: only what's missing from the room comes back
+        app.update();
+        assert_eq!(count(&mut app), (2, 3));
+        assert_eq!(heard(&mut rx), 1);
+
+        // Due again with nothing missing: no copies, and nothing to announce
+        app.update();
+        app.update();
+        assert_eq!(count(&mut app), (2, 3));
+        assert_eq!(heard(&mut rx), 0);
+    }
+}
@@ -3,31 +3,56 @@
 use bevy::prelude::*;
 
 use crate::domain::*;
+use crate::systems::network::Connections;
 
 pub fn shift_system(
     mut commands: Commands,
     mut ev_reader: EventReader<ShiftEvent>,
-    query_current: Query<(Entity, &NetworkClient, &AdminLink, &SubstrateIdentity)>,
+    mut connections: ResMut<Connections>,
+    query_current: Query<(Entity, &NetworkClient, &AdminLink, &SubstrateIdentity, Option<&Role>)>,
     query_partner: Query<&SubstrateIdentity>,
+    query_locations: Query<&Location>,
+    query_flags: Query<&RoomFlags>,
     mut look_writer: EventWriter<LookEvent>,
 ) {
     for event in ev_reader.read() {
-        if let Ok((curr_ent, client, link, curr_id)) = query_current.get(event.entity) {
+        if let Ok((curr_ent, client, link, curr_id, role)) = query_current.get(event.entity) {
             if let Ok(partner_id) = query_partner.get(link.partner) {
-                let addr = client.addr;
+                // Neither end of the shift may be a room that forbids it
+                let pinned = [curr_ent, link.partner].into_iter().any(|entity| {
+                    query_locations
+                        .get(entity)
+                        .and_then(|location| query_flags.get(location.0))
+                        .is_ok_and(RoomFlags::no_teleport)
+                });
+                if pinned {
+                    client.send(OutputMessage::error(
+                        "The substrate here is pinned down. You can't phase through it.",
+                    ));
+                    continue;
+                }
+
                 let tx = client.tx.clone();
 
-                // Remove network client from current entity
+                // Move the session: the connection now drives the partner
                 commands.entity(curr_ent).remove::<NetworkClient>();
+                commands.entity(link.partner).insert(NetworkClient {
+                    conn: client.conn,
+                    addr: client.addr,
+                    tx: tx.clone(),
+                });
+                connections.bind(client.conn, link.partner);
 
-                // Add to partner entity
-                commands.entity(link.partner).insert(NetworkClient { addr, tx: tx.clone() });
+                // The role belongs to whoever is at the keyboard, so it travels too
+                if let Some(role) = role {
+                    commands.entity(curr_ent).remove::<Role>();
+                    commands.entity(link.partner).insert(*role);
+                }
 
-                let _ = tx.send(format!(
-                    "\x1B[1;35m--- PHASE SHIFT COMPLETE ---\x1B[0m\n\
-                     You have shifted from \x1B[1;36m{}\x1B[0m into \x1B[1;35m{}\x1B[0m.",
-                    curr_id.name, partner_id.name
-                ));
+                let _ = tx.output(OutputMessage::PhaseShift {
+                    from: curr_id.name.clone(),
+                    to: partner_id.name.clone(),
+                });
 
                 look_writer.send(LookEvent {
                     entity: link.partner,
@@ -0,0 +1,77 @@
+// Shutdown - SIGINT/SIGTERM without losing anyone's progress
+//
+// The network thread catches the signal, stops accepting connections and
+// raises ShutdownSignal. The next frame warns everyone, asks for one last
+// SaveAll and closes the sessions; the frame after waits for the worker to
+// commit it all and ends the app. A second signal exits immediately.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+
+use crate::domain::*;
+use crate::persistence::{DbWorker, SaveAll};
+
+/// Raised by the signal handler, read by the game loop
+#[derive(Resource, Clone, Default)]
+pub struct ShutdownSignal(Arc<AtomicBool>);
+
+impl ShutdownSignal {
+    pub fn raise(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_raised(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Resolve on SIGINT (Ctrl-C) or, on Unix, SIGTERM
+pub async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut term = signal(SignalKind::terminate()).expect("Failed to install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Runs in Last, so the final SaveAll is handled by the next frame's Update
+/// before the flush here
+pub fn shutdown_system(
+    signal: Option<Res<ShutdownSignal>>,
+    worker: Res<DbWorker>,
+    mut announced: Local<bool>,
+    query_clients: Query<&NetworkClient>,
+    mut save_all: EventWriter<SaveAll>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if !signal.is_some_and(|signal| signal.is_raised()) {
+        return;
+    }
+
+    if !*announced {
+        *announced = true;
+        println!("🛑 Shutting down: saving the Substrate...");
+        let warning = OutputMessage::narrate(
+            Tone::Alarm,
+            "The Substrate is going dark. Your state has been preserved; reconnect when the signal returns.",
+        );
+        for client in query_clients.iter() {
+            client.send(warning.clone());
+            client.close();
+        }
+        save_all.send(SaveAll);
+        return;
+    }
+
+    worker.flush();
+    println!("🛑 Everything is saved. Goodbye.");
+    exit.send(AppExit::Success);
+}
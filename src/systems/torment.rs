@@ -7,7 +7,7 @@ use crate::domain::*;
 pub fn torment_system(
     mut ev_reader: EventReader<TormentEvent>,
     mut query_victims: Query<(&mut SubstrateIdentity, &mut PurgatoryState, &NetworkClient)>,
-    query_tormentor: Query<&SubstrateIdentity>,
+    query_tormentor: Query<&SubstrateIdentity, Without<PurgatoryState>>,
 ) {
     for event in ev_reader.read() {
         if let Ok((mut id, mut purg, client)) = query_victims.get_mut(event.victim) {
@@ -16,11 +16,10 @@ pub fn torment_system(
                 id.stability = (id.stability - event.intensity).max(0.0);
                 purg.penance += event.intensity * 10.0;
 
-                let msg = format!(
-                    "\x1B[1;31m{}: {}\x1B[0m",
-                    tormentor_id.name, event.description
-                );
-                let _ = client.tx.send(msg);
+                client.send(OutputMessage::narrate(
+                    Tone::Alarm,
+                    format!("{}: {}", tormentor_id.name, event.description),
+                ));
             }
         }
     }
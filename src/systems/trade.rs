@@ -5,6 +5,7 @@
 use bevy::prelude::*;
 
 use crate::domain::*;
+use crate::persistence::{ItemDeleted, ItemDirty};
 
 /// Process buy events - purchase from vendor
 pub fn buy_system(
@@ -28,10 +29,8 @@ pub fn buy_system(
             .iter()
             .find(|(_, loc, _, _, _)| loc.0 == buyer_loc.0);
 
-        let Some((vendor_entity, _, vendor_id, vendor_info, stock)) = vendor else {
-            let _ = client.tx.send(
-                "\x1B[33mThere's no one here to buy from.\x1B[0m".to_string()
-            );
+        let Some((_vendor_entity, _, vendor_id, vendor_info, stock)) = vendor else {
+            client.send(OutputMessage::narrate(Tone::Warning, "There's no one here to buy from."));
             continue;
         };
 
@@ -43,9 +42,9 @@ pub fn buy_system(
         });
 
         let Some(stock_item) = stock_item else {
-            let _ = client.tx.send(format!(
-                "\x1B[33m{} doesn't have '{}' for sale.\x1B[0m",
-                vendor_id.name, event.item_keyword
+            client.send(OutputMessage::narrate(
+                Tone::Warning,
+                format!("{} doesn't have '{}' for sale.", vendor_id.name, event.item_keyword),
             ));
             continue;
         };
@@ -56,9 +55,8 @@ pub fn buy_system(
         // Check buyer has enough cycles
         let wallet = maybe_wallet.cloned().unwrap_or_default();
         if wallet.cycles < price {
-            let _ = client.tx.send(format!(
-                "\x1B[31mYou need {} cycles, but only have {}.\x1B[0m",
-                price, wallet.cycles
+            client.send(OutputMessage::error(
+                format!("You need {} cycles, but only have {}.", price, wallet.cycles),
             ));
             continue;
         };
@@ -67,7 +65,7 @@ pub fn buy_system(
         let new_balance = wallet.cycles - price;
         commands.entity(event.buyer).insert(Wallet { cycles: new_balance });
 
-        // Create the item and give to buyer
+        // Create the item in the buyer's inventory
         commands.spawn((
             Item {
                 uuid: uuid::Uuid::new_v4().to_string(),
@@ -81,15 +79,16 @@ pub fn buy_system(
                 is_takeable: true,
                 is_visible: true,
             },
-        ));
+            ItemDirty,
+        )).set_parent(event.buyer);
 
         // Notify buyer
-        let _ = client.tx.send(format!(
-            "\x1B[32mYou purchase {} for {} cycles.\x1B[0m\n\
-             \x1B[90m({} hands you the item with practiced indifference.)\x1B[0m\n\
-             Balance: \x1B[33m{}\x1B[0m cycles",
-            stock_item.item_name, price, vendor_id.name, new_balance
-        ));
+        client.send(OutputMessage::Purchase {
+            item: stock_item.item_name.clone(),
+            price,
+            vendor: vendor_id.name.clone(),
+            balance: new_balance,
+        });
     }
 }
 
@@ -97,6 +96,7 @@ pub fn buy_system(
 pub fn sell_system(
     mut ev_reader: EventReader<SellEvent>,
     mut commands: Commands,
+    mut ev_deleted: EventWriter<ItemDeleted>,
     seller_query: Query<(
         &NetworkClient,
         &Location,
@@ -117,9 +117,7 @@ pub fn sell_system(
             .find(|(loc, _, _)| loc.0 == seller_loc.0);
 
         let Some((_, vendor_id, vendor_info)) = vendor else {
-            let _ = client.tx.send(
-                "\x1B[33mThere's no one here to sell to.\x1B[0m".to_string()
-            );
+            client.send(OutputMessage::narrate(Tone::Warning, "There's no one here to sell to."));
             continue;
         };
 
@@ -132,9 +130,9 @@ pub fn sell_system(
         });
 
         let Some((item_entity, item)) = owned_item else {
-            let _ = client.tx.send(format!(
-                "\x1B[33mYou don't have '{}' to sell.\x1B[0m",
-                event.item_keyword
+            client.send(OutputMessage::narrate(
+                Tone::Warning,
+                format!("You don't have '{}' to sell.", event.item_keyword),
             ));
             continue;
         };
@@ -147,13 +145,17 @@ pub fn sell_system(
             ItemType::Contraband => 75,  // Fence pays well for hot goods
             ItemType::Fragment => 100,    // Rare items
             ItemType::Quest => 0,         // Can't sell quest items
+            ItemType::Key => 0,           // Nor the keys to someone's door
             ItemType::Misc => 10,
         };
 
         if base_value == 0 {
-            let _ = client.tx.send(format!(
-                "\x1B[33m{} shakes their head. \"That's not something I deal in.\"\x1B[0m",
-                vendor_id.name
+            client.send(OutputMessage::narrate(
+                Tone::Warning,
+                format!(
+                    "{} shakes their head. \"That's not something I deal in.\"",
+                    vendor_id.name
+                ),
             ));
             continue;
         }
@@ -174,14 +176,15 @@ pub fn sell_system(
         // Remove the item
         let item_name = item.name.clone();
         commands.entity(item_entity).despawn();
+        ev_deleted.send(ItemDeleted { uuid: item.uuid.clone() });
 
         // Notify seller
-        let _ = client.tx.send(format!(
-            "\x1B[32mYou sell {} for {} cycles.\x1B[0m\n\
-             \x1B[90m({} examines it briefly before it disappears into their coat.)\x1B[0m\n\
-             Balance: \x1B[33m{}\x1B[0m cycles",
-            item_name, price, vendor_id.name, new_balance
-        ));
+        client.send(OutputMessage::Sale {
+            item: item_name,
+            price,
+            vendor: vendor_id.name.clone(),
+            balance: new_balance,
+        });
     }
 }
 
@@ -202,52 +205,33 @@ pub fn list_system(
             .find(|(loc, _, _, _)| loc.0 == player_loc.0);
 
         let Some((_, vendor_id, vendor_info, stock)) = vendor else {
-            let _ = client.tx.send(
-                "\x1B[33mThere's no vendor here.\x1B[0m".to_string()
-            );
+            client.send(OutputMessage::narrate(Tone::Warning, "There's no vendor here."));
             continue;
         };
 
         if stock.items.is_empty() {
-            let _ = client.tx.send(format!(
-                "\x1B[90m{} has nothing for sale right now.\x1B[0m",
-                vendor_id.name
+            client.send(OutputMessage::narrate(
+                Tone::Muted,
+                format!("{} has nothing for sale right now.", vendor_id.name),
             ));
             continue;
         }
 
-        // Build stock list
-        let mut output = format!(
-            "\x1B[35m╔══════════════════════════════════════════════════════╗\x1B[0m\n\
-             \x1B[35m║\x1B[0m  \x1B[1m{}'s Wares\x1B[0m\n\
-             \x1B[35m╠══════════════════════════════════════════════════════╣\x1B[0m\n",
-            vendor_id.name
-        );
-
-        for item in &stock.items {
-            let price = (item.base_price as f32 * vendor_info.buy_multiplier).round() as u32;
-            let qty_str = match item.quantity {
-                Some(n) => format!("({} left)", n),
-                None => String::new(),
-            };
-            
-            output.push_str(&format!(
-                "\x1B[35m║\x1B[0m  \x1B[36m{:<30}\x1B[0m \x1B[33m{:>5} ⚡\x1B[0m {}\n",
-                item.item_name, price, qty_str
-            ));
-            
-            // Short description
-            let desc_short: String = item.description.chars().take(50).collect();
-            output.push_str(&format!(
-                "\x1B[35m║\x1B[0m    \x1B[90m{}...\x1B[0m\n",
-                desc_short
-            ));
-        }
-
-        output.push_str("\x1B[35m╚══════════════════════════════════════════════════════╝\x1B[0m\n");
-        output.push_str("\x1B[90mUse 'buy <item>' to purchase.\x1B[0m");
-
-        let _ = client.tx.send(output);
+        let items = stock
+            .items
+            .iter()
+            .map(|item| WareView {
+                name: item.item_name.clone(),
+                price: (item.base_price as f32 * vendor_info.buy_multiplier).round() as u32,
+                quantity: item.quantity,
+                description: item.description.clone(),
+            })
+            .collect();
+
+        client.send(OutputMessage::Wares {
+            vendor: vendor_id.name.clone(),
+            items,
+        });
     }
 }
 
@@ -266,9 +250,6 @@ pub fn balance_system(
         };
 
         let wallet = maybe_wallet.cloned().unwrap_or_default();
-        let _ = client.tx.send(format!(
-            "\x1B[33m⚡ Balance: {} cycles\x1B[0m",
-            wallet.cycles
-        ));
+        client.send(OutputMessage::Balance { cycles: wallet.cycles });
     }
 }
@@ -3,55 +3,49 @@
 use bevy::prelude::*;
 
 use crate::domain::*;
+use crate::persistence::{DbReply, DbRequest, DbResponse, DbWorker, DbWrite};
 use crate::systems::somatic::handle_abide;
 
+#[allow(clippy::too_many_arguments)]
 pub fn utility_system(
     mut commands: Commands,
+    worker: Res<DbWorker>,
     mut ev_reader: EventReader<UtilityEvent>,
+    mut ev_replies: EventReader<DbReply>,
     query_players: Query<(
         &SubstrateIdentity,
         &NetworkClient,
         &Location,
         Entity,
-        Option<&AdminPermission>,
+        Option<&Role>,
         Option<&PurgatoryState>,
     )>,
-    query_all_entities: Query<(Entity, &SubstrateIdentity)>,
+    query_all_entities: Query<(Entity, &SubstrateIdentity, Has<NonPlayer>)>,
+    query_stripped: Query<&PurgatoryState>,
     query_items: Query<(&Item, &Parent)>,
     mut query_weather: Query<&mut CurrentWeather>,
     mut query_somatic: Query<(&mut SomaticBody, &SubstrateIdentity, &NetworkClient)>,
 ) {
     for event in ev_reader.read() {
-        if let Ok((identity, client, location, player_ent, admin_perm, purgatory)) =
+        if let Ok((identity, client, location, player_ent, role, purgatory)) =
             query_players.get(event.entity)
         {
             match event.command.as_str() {
                 "score" => {
-                    let mut output = format!("\x1B[1;36mEntity Scan: {}\x1B[0m\n", identity.name);
-                    output.push_str(&format!("UUID:      [{}]\n", identity.uuid));
-                    output.push_str(&format!("Entropy:   [{:.2}]\n", identity.entropy));
-                    output.push_str(&format!("Stability: [{:.2}]\n", identity.stability));
-
-                    if let Ok((body, _, _)) = query_somatic.get(player_ent) {
-                        output.push_str(&format!("Integrity: [{:.2}/{:.2}]\n", body.integrity, body.max_integrity));
-                    }
-
-                    if admin_perm.is_some() {
-                        output.push_str("\x1B[1;35mPERMISSIONS: ADMIN-ENABLED\x1B[0m\n");
-                    }
-
-                    if let Some(p) = purgatory {
-                        output.push_str(&format!(
-                            "\n\x1B[1;31mSTAIN: Purgatory (Penance: {:.2})\x1B[0m\n",
-                            p.penance
-                        ));
-                        output.push_str(&format!(
-                            "\x1B[1;31mINTERROGATOR: {}\x1B[0m\n",
-                            p.tormentor
-                        ));
-                    }
-
-                    let _ = client.tx.send(output);
+                    let body = query_somatic.get(player_ent).ok().map(|(body, _, _)| body);
+                    client.send(OutputMessage::Status(StatusView {
+                        name: identity.name.clone(),
+                        uuid: identity.uuid.clone(),
+                        entropy: identity.entropy,
+                        stability: identity.stability,
+                        integrity: body.map(|b| b.integrity),
+                        max_integrity: body.map(|b| b.max_integrity),
+                        role: role.copied().unwrap_or_default(),
+                        purgatory: purgatory.map(|p| PurgatoryView {
+                            penance: p.penance,
+                            tormentor: p.tormentor.clone(),
+                        }),
+                    }));
                 }
 
                 "weather" => {
@@ -68,12 +62,15 @@ pub fn utility_system(
                                     weather.ticks_remaining
                                 )
                             };
-                            let _ = client.tx.send(format!("\x1B[36m{}\x1B[0m", desc));
+                            client.send(OutputMessage::narrate(Tone::Info, desc));
                         } else {
-                            let _ = client.tx.send("\x1B[90mThis area has no weather system.\x1B[0m".to_string());
+                            client.send(OutputMessage::narrate(
+                                Tone::Muted,
+                                "This area has no weather system.",
+                            ));
                         }
-                    } else if event.args.starts_with("set ") && admin_perm.is_some() {
-                        // Admin: set weather
+                    } else if event.args.starts_with("set ") {
+                        // Builder: set weather
                         let weather_name = event.args.strip_prefix("set ").unwrap().trim().to_lowercase();
                         let new_weather = match weather_name.as_str() {
                             "clear" => Some(WeatherType::Clear),
@@ -90,94 +87,268 @@ pub fn utility_system(
                                 weather.weather_type = wt;
                                 weather.intensity = 0.8;
                                 weather.ticks_remaining = 10;
-                                let _ = client.tx.send(format!(
-                                    "\x1B[35mYou twist the atmospheric parameters. {} descends upon this zone.\x1B[0m",
-                                    wt.describe_silicon()
+                                client.send(OutputMessage::narrate(
+                                    Tone::Arcane,
+                                    format!(
+                                        "You twist the atmospheric parameters. {} descends upon this zone.",
+                                        wt.describe_silicon()
+                                    ),
                                 ));
                             } else {
-                                let _ = client.tx.send("\x1B[31mThis area cannot support weather.\x1B[0m".to_string());
+                                client.send(OutputMessage::error(
+                                    "This area cannot support weather.",
+                                ));
                             }
                         } else {
-                            let _ = client.tx.send("\x1B[31mUnknown weather type. Try: clear, acid, static, fog, hail, null\x1B[0m".to_string());
+                            client.send(OutputMessage::error(
+                                "Unknown weather type. Try: clear, acid, static, fog, hail, null",
+                            ));
                         }
-                    } else if event.args.starts_with("set ") {
-                        let _ = client.tx.send("\x1B[31mOnly administrators can manipulate the weather.\x1B[0m".to_string());
                     }
                 }
 
                 "abide" => {
-                    handle_abide(player_ent, query_somatic);
+                    handle_abide(player_ent, &mut query_somatic);
                 }
 
-                "promote" if admin_perm.is_some() => {
-                    if let Some(target_ent) = query_all_entities
-                        .iter()
-                        .find(|(_, id)| id.name.to_lowercase().contains(&event.args.to_lowercase()))
-                        .map(|(e, _)| e)
-                    {
-                        commands.entity(target_ent).insert(AdminPermission);
-                        let _ = client.tx.send(format!(
-                            "\x1B[1;35mProcess elevated: {} now has Admin Permission.\x1B[0m",
-                            event.args
+                "promote" | "demote" => {
+                    // "promote <name> <role>" - the role is always spelled out,
+                    // since it is saved and a slip would stick
+                    let target = if event.command == "demote" {
+                        Some((event.args.trim(), Role::Player))
+                    } else {
+                        event
+                            .args
+                            .trim()
+                            .rsplit_once(' ')
+                            .and_then(|(name, role)| Some((name.trim(), Role::parse(role)?)))
+                    };
+                    let Some((name, new_role)) = target.filter(|(name, _)| !name.is_empty()) else {
+                        client.send(OutputMessage::narrate(
+                            Tone::Warning,
+                            "Promote whom, to what? (promote <name> <builder|moderator|admin>, demote <name>)",
                         ));
+                        continue;
+                    };
+
+                    let target = query_all_entities
+                        .iter()
+                        .find(|(_, id, _)| id.name.eq_ignore_ascii_case(name));
+                    let Some((target_ent, id, is_npc)) = target else {
+                        // Offline players can still be promoted through the database
+                        worker.request(player_ent, DbRequest::SetRole {
+                            name: name.to_string(),
+                            role: new_role,
+                        });
+                        continue;
+                    };
+
+                    commands.entity(target_ent).insert(new_role);
+                    if !is_npc {
+                        worker.write(DbWrite::Role {
+                            uuid: id.uuid.clone(),
+                            role: new_role,
+                        });
                     }
+                    client.send(role_changed(&id.name, new_role));
                 }
 
-                "link" if admin_perm.is_some() => {
-                    let parts: Vec<&str> = event.args.split_whitespace().collect();
-                    if parts.len() == 2 {
-                        let p1 = query_all_entities
-                            .iter()
-                            .find(|(_, id)| {
-                                id.name.to_lowercase().contains(&parts[0].to_lowercase())
-                            })
-                            .map(|(e, _)| e);
-                        let p2 = query_all_entities
+                "link" => {
+                    // "link <name> <name>", full names; either may have spaces
+                    // in it, so try each place the line could split in two
+                    let find = |name: String| {
+                        query_all_entities
                             .iter()
-                            .find(|(_, id)| {
-                                id.name.to_lowercase().contains(&parts[1].to_lowercase())
-                            })
-                            .map(|(e, _)| e);
-
-                        if let (Some(e1), Some(e2)) = (p1, p2) {
-                            commands.entity(e1).insert(AdminLink { partner: e2 });
-                            commands.entity(e2).insert(AdminLink { partner: e1 });
-                            let _ = client.tx.send(
-                                "\x1B[1;35mNeural link established between entities.\x1B[0m"
-                                    .to_string(),
-                            );
-                        }
+                            .find(|(_, id, _)| id.name.eq_ignore_ascii_case(&name))
+                    };
+                    let words: Vec<&str> = event.args.split_whitespace().collect();
+                    let pair = (1..words.len()).find_map(|split| {
+                        Some((find(words[..split].join(" "))?, find(words[split..].join(" "))?))
+                    });
+                    let Some(((e1, id1, npc1), (e2, id2, npc2))) = pair else {
+                        client.send(OutputMessage::error(
+                            "Link whom to whom? (link <name> <name>, full names)",
+                        ));
+                        continue;
+                    };
+
+                    commands.entity(e1).insert(AdminLink { partner: e2 });
+                    commands.entity(e2).insert(AdminLink { partner: e1 });
+
+                    // Stored against the player, so it's back at their next login
+                    let stored = match (npc1, npc2) {
+                        (false, _) => Some((id1, id2)),
+                        (true, false) => Some((id2, id1)),
+                        (true, true) => None,
+                    };
+                    if let Some((player, avatar)) = stored {
+                        worker.write(DbWrite::AdminLink {
+                            player_uuid: player.uuid.clone(),
+                            avatar_uuid: avatar.uuid.clone(),
+                        });
                     }
+                    client.send(OutputMessage::narrate(
+                        Tone::Ritual,
+                        "Neural link established between entities.",
+                    ));
+                }
+
+                "resetpass" => {
+                    // A temporary password, shown only to the admin; the player
+                    // must replace it at next login
+                    tracing::info!(by = %identity.name, target = %event.args.trim(), "Password reset requested");
+                    worker.request(player_ent, DbRequest::ResetPassword {
+                        name: event.args.trim().to_string(),
+                    });
                 }
 
                 "who" => {
-                    let mut output =
-                        "\x1B[1;34mConsciousnesses currently inhabiting the Substrate:\x1B[0m\n"
-                            .to_string();
-                    for (_, id) in query_all_entities.iter() {
-                        output.push_str(&format!(" - {}\n", id.name));
-                    }
-                    let _ = client.tx.send(output);
+                    let viewer = role.copied().unwrap_or_default();
+                    let names = query_all_entities
+                        .iter()
+                        .map(|(e, id, _)| id.seen_by(query_stripped.get(e).ok(), viewer))
+                        .collect();
+                    client.send(OutputMessage::Who { names });
                 }
 
                 "inventory" | "i" => {
-                    let mut output =
-                        "\x1B[1;33mYou reach into the folds of your code:\x1B[0m\n".to_string();
-                    let mut count = 0;
-                    for (item, parent) in query_items.iter() {
-                        if parent.get() == player_ent {
-                            output.push_str(&format!(" - {}\n", item.name));
-                            count += 1;
-                        }
-                    }
-                    if count == 0 {
-                        output.push_str(" [Nothing but ghosts]\n");
-                    }
-                    let _ = client.tx.send(output);
+                    let items = query_items
+                        .iter()
+                        .filter(|(_, parent)| parent.get() == player_ent)
+                        .map(|(item, _)| item.name.clone())
+                        .collect();
+                    client.send(OutputMessage::Inventory { items });
                 }
 
                 _ => {}
             }
         }
     }
+
+    // Answers from the database for the offline cases above
+    for reply in ev_replies.read() {
+        let Ok((_, client, ..)) = query_players.get(reply.entity) else {
+            continue;
+        };
+        let (name, outcome) = match &reply.response {
+            DbResponse::SetRole { name, role, found } => (
+                name,
+                found.as_ref().map(|found| found.as_ref().map(|stored| role_changed(stored, *role))),
+            ),
+            DbResponse::ResetPassword { name, found } => (
+                name,
+                found.as_ref().map(|found| {
+                    found.as_ref().map(|(stored, password)| {
+                        OutputMessage::narrate(
+                            Tone::Ritual,
+                            format!(
+                                "{}'s seal is broken. Their temporary password is: {}\n\
+                                 It works once: logging in with it leads straight to choosing a new one.",
+                                stored,
+                                password.as_str()
+                            ),
+                        )
+                    })
+                }),
+            ),
+            _ => continue,
+        };
+        match outcome {
+            Ok(Some(message)) => client.send(message),
+            Ok(None) => client.send(OutputMessage::error(format!(
+                "No process named '{}' exists.",
+                name
+            ))),
+            Err(e) => {
+                tracing::error!(error = %e, "Database error");
+                client.send(OutputMessage::error("Database error. Please try again."));
+            }
+        }
+    }
+}
+
+/// The announcement for a role change
+fn role_changed(name: &str, role: Role) -> OutputMessage {
+    OutputMessage::narrate(
+        Tone::Ritual,
+        format!(
+            "Process {}: {} now holds the {} role.",
+            if role == Role::Player { "reduced" } else { "elevated" },
+            name,
+            role.as_str()
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{db_reply_system, Database, PlayerRecord};
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn roles_go_only_to_the_character_named_in_full() {
+        let db = Database::in_memory().unwrap();
+        let alice = PlayerRecord::new("Alice", ClientType::Carbon, "void");
+        db.save_player(&alice).unwrap();
+
+        let mut app = App::new();
+        app.insert_resource(DbWorker::spawn(db.clone()))
+            .add_event::<UtilityEvent>()
+            .add_event::<DbReply>()
+            .add_systems(PreUpdate, db_reply_system)
+            .add_systems(Update, utility_system);
+        let room = app.world_mut().spawn_empty().id();
+        let identity = |record: &PlayerRecord| SubstrateIdentity {
+            uuid: record.uuid.clone(),
+            name: record.name.clone(),
+            entropy: 0.0,
+            stability: 1.0,
+            signal_strength: 1.0,
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let judge = app
+            .world_mut()
+            .spawn((
+                identity(&PlayerRecord::new("Judge", ClientType::Silicon, "void")),
+                NetworkClient {
+                    conn: ConnectionId(1),
+                    addr: "127.0.0.1:4000".parse().unwrap(),
+                    tx: ClientSender::new(tx, ClientType::Silicon),
+                },
+                Location(room),
+                Role::Admin,
+            ))
+            .id();
+        let alice_body = app.world_mut().spawn((identity(&alice), Location(room))).id();
+
+        let mut promote = |app: &mut App, args: &str| {
+            app.world_mut().send_event(UtilityEvent {
+                entity: judge,
+                command: "promote".to_string(),
+                args: args.to_string(),
+            });
+            app.update();
+            app.world().resource::<DbWorker>().flush();
+            app.update();
+            std::iter::from_fn(|| rx.try_recv().ok())
+                .filter_map(|frame| match frame {
+                    Outbound::Line(line) => Some(line),
+                    _ => None,
+                })
+                .collect::<String>()
+        };
+        let stored_role = || db.load_player_by_name("Alice").unwrap().unwrap().role;
+
+        // Part of a name picks out nobody, online or off
+        assert!(promote(&mut app, "al admin").contains("No process"));
+        // No role, no promotion
+        assert!(promote(&mut app, "Alice").contains("Promote whom, to what?"));
+        assert_eq!(app.world().get::<Role>(alice_body), None);
+        assert_eq!(stored_role(), Role::Player);
+
+        assert!(promote(&mut app, "alice moderator").contains("Alice now holds the moderator role"));
+        assert_eq!(app.world().get::<Role>(alice_body), Some(&Role::Moderator));
+        assert_eq!(stored_role(), Role::Moderator);
+    }
 }
@@ -42,10 +42,10 @@ pub fn weather_tick_system(
         return;
     }
 
-    let mut rng = rand::thread_rng();
+    let mut rng = rand::rng();
 
     // Update weather in each zone
-    for (room_entity, zone, mut current_weather, room) in weather_query.iter_mut() {
+    for (room_entity, zone, mut current_weather, _room) in weather_query.iter_mut() {
         // Decrement ticks
         if current_weather.ticks_remaining > 0 {
             current_weather.ticks_remaining -= 1;
@@ -57,7 +57,7 @@ pub fn weather_tick_system(
             
             // Pick new weather based on zone weights
             let total_weight: f32 = zone.possible_weather.iter().map(|(_, w)| w).sum();
-            let mut roll = rng.gen::<f32>() * total_weight;
+            let mut roll = rng.random::<f32>() * total_weight;
             
             let mut new_weather = WeatherType::Clear;
             for (weather_type, weight) in &zone.possible_weather {
@@ -70,14 +70,13 @@ pub fn weather_tick_system(
 
             // Set new weather
             current_weather.weather_type = new_weather;
-            current_weather.intensity = rng.gen_range(0.3..1.0);
-            current_weather.ticks_remaining = rng.gen_range(2..8); // 1-4 minutes
+            current_weather.intensity = rng.random_range(0.3..1.0);
+            current_weather.ticks_remaining = rng.random_range(2..8); // 1-4 minutes
 
             // Fire event if weather actually changed
             if old_weather != new_weather && new_weather != WeatherType::Clear {
                 weather_events.send(WeatherChangeEvent {
                     room: room_entity,
-                    old_weather,
                     new_weather,
                 });
             }
@@ -97,19 +96,27 @@ pub fn weather_tick_system(
                     // Notify if taking significant damage
                     if stability_mod < -0.01 {
                         if let Some(client) = maybe_client {
-                            let damage_msg = match current_weather.weather_type {
-                                WeatherType::AcidRain => 
-                                    "\x1B[32;1mThe acid rain burns. Your stability wavers.\x1B[0m",
-                                WeatherType::StaticStorm =>
-                                    "\x1B[36;1mStatic crawls through your thoughts, fragmenting your entropy.\x1B[0m",
-                                WeatherType::ByteHail =>
-                                    "\x1B[37;1mFrozen data shards cut into you. Stability dropping.\x1B[0m",
-                                WeatherType::NullWind =>
-                                    "\x1B[35mThe null wind whispers through you, taking pieces as it goes.\x1B[0m",
-                                _ => "",
+                            let damage = match current_weather.weather_type {
+                                WeatherType::AcidRain => Some((
+                                    Tone::Triumph,
+                                    "The acid rain burns. Your stability wavers.",
+                                )),
+                                WeatherType::StaticStorm => Some((
+                                    Tone::Notice,
+                                    "Static crawls through your thoughts, fragmenting your entropy.",
+                                )),
+                                WeatherType::ByteHail => Some((
+                                    Tone::Stark,
+                                    "Frozen data shards cut into you. Stability dropping.",
+                                )),
+                                WeatherType::NullWind => Some((
+                                    Tone::Arcane,
+                                    "The null wind whispers through you, taking pieces as it goes.",
+                                )),
+                                _ => None,
                             };
-                            if !damage_msg.is_empty() {
-                                let _ = client.tx.send(damage_msg.to_string());
+                            if let Some((tone, text)) = damage {
+                                client.send(OutputMessage::narrate(tone, text));
                             }
                         }
                     }
@@ -125,15 +132,18 @@ pub fn weather_announce_system(
     entity_query: Query<(&Location, &NetworkClient)>,
 ) {
     for event in weather_events.read() {
-        let announcement = event.new_weather.describe_carbon();
-        if announcement.is_empty() {
+        let prose = event.new_weather.describe_carbon();
+        if prose.is_empty() {
             continue;
         }
 
         // Find all players in this room and notify them
         for (location, client) in entity_query.iter() {
             if location.0 == event.room {
-                let _ = client.tx.send(format!("\n{}\n", announcement));
+                client.send(OutputMessage::WeatherChange {
+                    weather: event.new_weather.describe_silicon(),
+                    prose,
+                });
             }
         }
     }
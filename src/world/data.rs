@@ -0,0 +1,661 @@
+// World Data - The Substrate as files
+//
+// The world is read from TOML files in the configured world_dir, one area
+// per file by convention (every *.toml is read, in name order). Rooms are
+// known by their RoomInfo name, and everything that points at a room
+// (exits, NPCs, items, stream pushes) uses that name:
+//
+//   area = "central"
+//
+//   [[room]]
+//   name = "obsidian_plaza"
+//   title = "The Obsidian Plaza"
+//   description = "A wide square paved in polished black stone..."
+//   exits = { north = "cathedral_of_archives", east = "buffer_overflow" }
+//   exits."enter portal" = { to = "velvet_cell", aliases = ["portal"], hidden = true }
+//   exits.up = { to = "attic", door = { name = "trapdoor", state = "locked", lock = "attic", pick = 0.3 } }
+//   coherence = { value = 0.5, is_phasing = true, drift_rate = 0.2 }
+//   stream = { pressure_rate = 0.08, push = "obsidian_plaza" }
+//   weather = { sheltered = false, chances = [["Clear", 3.0], ["AcidRain", 2.0]], current = "Clear", ticks = 2 }
+//   flags = ["safe", "dark"]
+//
+//   [[room.detail]]
+//   keywords = ["throne", "chair"]
+//   description = "..."
+//
+//   [[npc]]     name, uuid, room, descriptions and identity stats; optional
+//               combat, body, vendor (with [[npc.vendor.stock]]) and dialogue
+//   [[item]]    uuid, name, description, keywords, item_type, room, and
+//               properties (a key has properties = { lock = "attic" })
+//
+//   [reset]     every = 900, empty = 300, message = "The dust settles."
+//   [[reset.item]]   name = "Lamp", room = "attic", max = 1
+//   [[reset.npc]]    name = "Rat", room = "cellar", max = 3
+//
+// Room flags are "safe" (no fighting), "no-magic" and "no-teleport" (no
+// shifting in or out), "dark" (nothing to see without a light: an item with
+// properties = { light = true }) and "no-exit" (sentenced players stay put).
+//
+// An area with a [reset] puts its listed items and NPCs back every `every`
+// seconds, or once it has stood `empty` seconds with no players in it:
+// each is topped up to `max` in its room, counting what's already there.
+// Fragments are one of a kind and can't be listed: a reset would mint more.
+//
+// A door is written on one side of a passage and appears on the exit back
+// as well, when there is one; its state after that lives in the database.
+//
+// The whole world is checked at boot: a duplicate name or an exit to
+// nowhere stops the server with every problem listed. Rooms changed by
+// builders in-game are then laid over it from the database (apply_saved).
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::Context;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::*;
+
+/// Everything spawn_world builds, as read from disk
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorldData {
+    pub areas: Vec<AreaData>,
+}
+
+/// One world file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AreaData {
+    /// RoomInfo.area for every room in the file
+    pub area: String,
+    #[serde(default, rename = "room", skip_serializing_if = "Vec::is_empty")]
+    pub rooms: Vec<RoomData>,
+    #[serde(default, rename = "npc", skip_serializing_if = "Vec::is_empty")]
+    pub npcs: Vec<NpcData>,
+    #[serde(default, rename = "item", skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ItemData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset: Option<ResetData>,
+}
+
+/// When an area puts itself back together, and what it puts back
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResetData {
+    /// Seconds between resets, whoever is there
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub every: Option<f32>,
+    /// Seconds without a player in the area before it resets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub empty: Option<f32>,
+    /// Told to players in the area when it resets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, rename = "item", skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ResetEntry>,
+    #[serde(default, rename = "npc", skip_serializing_if = "Vec::is_empty")]
+    pub npcs: Vec<ResetEntry>,
+}
+
+/// An [[item]] or [[npc]], by name, that should be in a room of the area
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResetEntry {
+    pub name: String,
+    pub room: String,
+    /// How many may be there at once; a reset tops up to this
+    #[serde(default = "one")]
+    pub max: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoomData {
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    /// Exit name (usually a direction) to where it leads
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub exits: BTreeMap<String, ExitData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weather: Option<WeatherData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coherence: Option<Coherence>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<StreamData>,
+    #[serde(default, rename = "detail", skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<Detail>,
+    /// RoomInfo flags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+}
+
+/// An exit: just a room name, or a table when it has aliases or is hidden
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ExitForm", into = "ExitForm")]
+pub struct ExitData {
+    pub to: String,
+    pub aliases: Vec<String>,
+    pub hidden: bool,
+    pub door: Option<Door>,
+}
+
+impl ExitData {
+    pub fn to(room: &str) -> Self {
+        Self {
+            to: room.to_string(),
+            aliases: Vec::new(),
+            hidden: false,
+            door: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ExitForm {
+    Room(String),
+    Table(ExitTable),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExitTable {
+    to: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    hidden: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    door: Option<Door>,
+}
+
+impl From<ExitForm> for ExitData {
+    fn from(form: ExitForm) -> Self {
+        match form {
+            ExitForm::Room(to) => ExitData::to(&to),
+            ExitForm::Table(ExitTable { to, aliases, hidden, door }) => ExitData { to, aliases, hidden, door },
+        }
+    }
+}
+
+impl From<ExitData> for ExitForm {
+    fn from(exit: ExitData) -> Self {
+        if exit.aliases.is_empty() && !exit.hidden && exit.door.is_none() {
+            ExitForm::Room(exit.to)
+        } else {
+            ExitForm::Table(ExitTable {
+                to: exit.to,
+                aliases: exit.aliases,
+                hidden: exit.hidden,
+                door: exit.door,
+            })
+        }
+    }
+}
+
+/// WeatherZone and the CurrentWeather a room starts with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeatherData {
+    pub sheltered: bool,
+    /// Weather types and their weights
+    pub chances: Vec<(WeatherType, f32)>,
+    pub current: WeatherType,
+    #[serde(default)]
+    pub intensity: f32,
+    /// Ticks until the first change
+    pub ticks: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamData {
+    pub pressure_rate: f32,
+    /// Room to push into, through the exit that leads there; without
+    /// one the pressure builds but nobody is pushed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub push: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NpcData {
+    pub name: String,
+    /// Fixed, so saves and admin links survive a restart
+    pub uuid: String,
+    pub room: String,
+    pub short_desc: String,
+    pub long_desc: String,
+    pub entropy: f32,
+    pub stability: f32,
+    pub signal_strength: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub combat: Option<CombatStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<SomaticBody>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<VendorData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dialogue: Option<Dialogue>,
+}
+
+/// Vendor terms and VendorStock together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VendorData {
+    pub buy_multiplier: f32,
+    pub sell_multiplier: f32,
+    pub vendor_type: VendorType,
+    #[serde(default)]
+    pub stock: Vec<StockItem>,
+}
+
+/// An item lying in a room when the world starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemData {
+    pub uuid: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub item_type: ItemType,
+    pub room: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, serde_json::Value>,
+}
+
+impl WorldData {
+    /// Read and check every world file in `dir`
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .with_context(|| format!("reading world directory {}", dir.display()))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        let mut areas = Vec::new();
+        for path in paths {
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("reading {}", path.display()))?;
+            let area: AreaData =
+                toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))?;
+            areas.push(area);
+        }
+
+        let mut world = Self { areas };
+        world.validate()?;
+        // Stored the way Exits keys them, so saved door states line up
+        for room in world.areas.iter_mut().flat_map(|area| &mut area.rooms) {
+            room.exits = std::mem::take(&mut room.exits)
+                .into_iter()
+                .map(|(name, exit)| {
+                    let name = parse_direction(&name).map(str::to_string).unwrap_or(name.to_lowercase());
+                    (name, exit)
+                })
+                .collect();
+        }
+        Ok(world)
+    }
+
+    pub fn rooms(&self) -> impl Iterator<Item = (&str, &RoomData)> {
+        self.areas
+            .iter()
+            .flat_map(|area| area.rooms.iter().map(move |room| (area.area.as_str(), room)))
+    }
+
+    pub fn npcs(&self) -> impl Iterator<Item = &NpcData> {
+        self.areas.iter().flat_map(|area| &area.npcs)
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &ItemData> {
+        self.areas.iter().flat_map(|area| &area.items)
+    }
+
+    pub fn item(&self, name: &str) -> Option<&ItemData> {
+        self.items().find(|item| item.name == name)
+    }
+
+    pub fn npc(&self, name: &str) -> Option<&NpcData> {
+        self.npcs().find(|npc| npc.name == name)
+    }
+
+    pub fn has_room(&self, name: &str) -> bool {
+        self.rooms().any(|(_, room)| room.name == name)
+    }
+
+    pub fn room(&self, name: &str) -> Option<&RoomData> {
+        self.rooms().find(|(_, room)| room.name == name).map(|(_, room)| room)
+    }
+
+    /// The door across an exit: its own, or else the one written on the
+    /// exit coming back
+    pub fn door(&self, room: &str, exit: &str) -> Option<&Door> {
+        let data = self.room(room)?.exits.get(exit)?;
+        data.door.as_ref().or_else(|| {
+            self.room(&data.to)?
+                .exits
+                .values()
+                .find(|back| back.to == room && back.door.is_some())?
+                .door
+                .as_ref()
+        })
+    }
+
+    /// Set a door's state wherever it is written, on either side
+    pub fn set_door_state(&mut self, room: &str, exit: &str, state: DoorState) -> bool {
+        let Some(target) = self.room(room).and_then(|r| r.exits.get(exit)).map(|e| e.to.clone()) else {
+            return false;
+        };
+        let mut found = false;
+        for data in self.areas.iter_mut().flat_map(|area| &mut area.rooms) {
+            let written = if data.name == room {
+                data.exits.get_mut(exit)
+            } else if data.name == target {
+                data.exits.values_mut().find(|back| back.to == room && back.door.is_some())
+            } else {
+                None
+            };
+            if let Some(door) = written.and_then(|e| e.door.as_mut()) {
+                door.state = state;
+                found = true;
+            }
+        }
+        found
+    }
+
+    /// Lay rooms saved by builders over the files: each replaces the room
+    /// of the same name, wherever it was, or joins the world as a new one.
+    /// Exits and stream pushes into rooms that no longer exist are dropped;
+    /// what was dropped is returned for the console.
+    pub fn apply_saved(&mut self, saved: Vec<(String, RoomData)>) -> Vec<String> {
+        for (area, room) in saved {
+            for existing in &mut self.areas {
+                existing.rooms.retain(|r| r.name != room.name);
+            }
+            match self.areas.iter_mut().find(|a| a.area == area) {
+                Some(existing) => existing.rooms.push(room),
+                None => self.areas.push(AreaData {
+                    area,
+                    rooms: vec![room],
+                    ..Default::default()
+                }),
+            }
+        }
+
+        let names: HashSet<String> = self.rooms().map(|(_, room)| room.name.clone()).collect();
+        let mut dropped = Vec::new();
+        for room in self.areas.iter_mut().flat_map(|area| &mut area.rooms) {
+            room.exits.retain(|name, exit| {
+                let keep = names.contains(&exit.to);
+                if !keep {
+                    dropped.push(format!("exit {} from '{}' to missing room '{}'", name, room.name, exit.to));
+                }
+                keep
+            });
+            if let Some(stream) = &mut room.stream {
+                if let Some(push) = stream.push.take_if(|push| !names.contains(push.as_str())) {
+                    dropped.push(format!("stream push from '{}' to missing room '{}'", room.name, push));
+                }
+            }
+        }
+        dropped
+    }
+
+    /// Every problem at once: duplicate names and uuids, and anything
+    /// pointing at a room that doesn't exist
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut problems = Vec::new();
+
+        let mut rooms = HashSet::new();
+        for (_, room) in self.rooms() {
+            if !rooms.insert(room.name.as_str()) {
+                problems.push(format!("room '{}' is defined twice", room.name));
+            }
+        }
+        anyhow::ensure!(!rooms.is_empty(), "the world has no rooms");
+        let mut check_room = |what: String, target: &str| {
+            if !rooms.contains(target) {
+                problems.push(format!("{} leads to unknown room '{}'", what, target));
+            }
+        };
+
+        for (_, room) in self.rooms() {
+            for (name, exit) in &room.exits {
+                check_room(format!("exit {} from '{}'", name, room.name), &exit.to);
+            }
+            if let Some(push) = room.stream.as_ref().and_then(|s| s.push.as_deref()) {
+                check_room(format!("stream in '{}'", room.name), push);
+            }
+        }
+        for npc in self.npcs() {
+            check_room(format!("npc '{}'", npc.name), &npc.room);
+        }
+        for item in self.items() {
+            check_room(format!("item '{}'", item.name), &item.room);
+        }
+
+        for (_, room) in self.rooms() {
+            for (name, door) in room.exits.iter().filter_map(|(name, e)| Some((name, e.door.as_ref()?))) {
+                if door.name.trim().is_empty() {
+                    problems.push(format!("the door on exit {} from '{}' has no name", name, room.name));
+                }
+                if door.pick.is_some_and(|chance| !(0.0..=1.0).contains(&chance)) {
+                    problems.push(format!("the door on exit {} from '{}' has a pick chance outside 0-1", name, room.name));
+                }
+                if door.state == DoorState::Locked && door.lock.is_none() && door.pick.is_none() {
+                    problems.push(format!("the door on exit {} from '{}' is locked for good: no lock, no pick", name, room.name));
+                }
+            }
+        }
+
+        for (_, room) in self.rooms() {
+            for flag in room.flags.iter().filter(|flag| !ROOM_FLAGS.contains(&flag.as_str())) {
+                problems.push(format!("room '{}' has unknown flag '{}'", room.name, flag));
+            }
+        }
+
+        // Every word must lead one way: "n" and an exit aliased "n" can't both be
+        for (_, room) in self.rooms() {
+            let mut words = HashSet::new();
+            for (name, exit) in &room.exits {
+                for word in std::iter::once(name).chain(&exit.aliases) {
+                    let word = word.trim().to_lowercase();
+                    let word = parse_direction(&word).map(str::to_string).unwrap_or(word);
+                    if word.is_empty() {
+                        problems.push(format!("room '{}' has an exit with a blank name or alias", room.name));
+                    } else if !words.insert(word.clone()) {
+                        problems.push(format!("room '{}' has two exits answering to '{}'", room.name, word));
+                    }
+                }
+            }
+        }
+
+        let mut names = HashSet::new();
+        let mut uuids = HashSet::new();
+        for npc in self.npcs() {
+            if !names.insert(npc.name.to_lowercase()) {
+                problems.push(format!("npc '{}' is defined twice", npc.name));
+            }
+            if !uuids.insert(npc.uuid.as_str()) {
+                problems.push(format!("uuid {} is used twice", npc.uuid));
+            }
+        }
+        for item in self.items() {
+            if !uuids.insert(item.uuid.as_str()) {
+                problems.push(format!("uuid {} is used twice", item.uuid));
+            }
+        }
+
+        for area in &self.areas {
+            let Some(reset) = &area.reset else { continue };
+            let what = format!("the reset of area '{}'", area.area);
+            if reset.every.is_none() && reset.empty.is_none() {
+                problems.push(format!("{} has neither `every` nor `empty`", what));
+            }
+            if reset.every.into_iter().chain(reset.empty).any(|secs| secs <= 0.0) {
+                problems.push(format!("{} has a time that isn't positive", what));
+            }
+            let entries = reset.items.iter().map(|e| ("item", e, self.items().any(|i| i.name == e.name)));
+            let entries = entries.chain(reset.npcs.iter().map(|e| ("npc", e, self.npcs().any(|n| n.name == e.name))));
+            for (kind, entry, known) in entries {
+                if !known {
+                    problems.push(format!("{} names unknown {} '{}'", what, kind, entry.name));
+                }
+                if !area.rooms.iter().any(|room| room.name == entry.room) {
+                    problems.push(format!("{} puts '{}' in '{}', not one of its rooms", what, entry.name, entry.room));
+                }
+                if entry.max == 0 {
+                    problems.push(format!("{} allows no '{}' at all", what, entry.name));
+                }
+            }
+            for entry in &reset.items {
+                if self.items().any(|i| i.name == entry.name && i.item_type == ItemType::Fragment) {
+                    problems.push(format!("{} would mint copies of the fragment '{}'", what, entry.name));
+                }
+            }
+        }
+
+        anyhow::ensure!(problems.is_empty(), "invalid world:\n  {}", problems.join("\n  "));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEN: &str = r#"
+        area = "den"
+
+        [[room]]
+        name = "den"
+        title = "The Den"
+        description = "Dark."
+        exits = { north = "hall", n = "den", "crawl out" = { to = "den", aliases = [""] } }
+
+        [[room]]
+        name = "den"
+        title = "The Den Again"
+        description = "Darker."
+        flags = ["dark", "gloomy"]
+        exits = { down = { to = "den", door = { name = " ", state = "locked", pick = 1.5 } } }
+
+        [[item]]
+        uuid = "ITEM-0001"
+        name = "Lamp"
+        description = "Unlit."
+        item_type = "Fragment"
+        room = "attic"
+
+        [reset]
+        [[reset.item]]
+        name = "Lamp"
+        room = "den"
+
+        [[reset.npc]]
+        name = "Rat"
+        room = "hall"
+        max = 0
+    "#;
+
+    #[test]
+    fn shipped_world_is_valid() {
+        let world = WorldData::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("world")).unwrap();
+        assert!(world.has_room("obsidian_plaza"));
+        assert!(world.has_room("velvet_cell"));
+        assert_eq!(world.rooms().count(), 10);
+        assert_eq!(world.npcs().count(), 4);
+        assert_eq!(world.items().count(), 8);
+        assert_eq!(world.room("velvet_cell").unwrap().flags, vec!["no-exit"]);
+        assert_eq!(world.door("velvet_cell", "east").unwrap().name, "velvet door");
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let world = WorldData { areas: vec![toml::from_str(DEN).unwrap()] };
+        let message = world.validate().unwrap_err().to_string();
+        for problem in [
+            "room 'den' is defined twice",
+            "exit north from 'den' leads to unknown room 'hall'",
+            "item 'Lamp' leads to unknown room 'attic'",
+            "room 'den' has two exits answering to 'north'",
+            "room 'den' has an exit with a blank name or alias",
+            "the door on exit down from 'den' has no name",
+            "the door on exit down from 'den' has a pick chance outside 0-1",
+            "room 'den' has unknown flag 'gloomy'",
+            "the reset of area 'den' has neither `every` nor `empty`",
+            "the reset of area 'den' names unknown npc 'Rat'",
+            "the reset of area 'den' puts 'Rat' in 'hall', not one of its rooms",
+            "the reset of area 'den' allows no 'Rat' at all",
+            "the reset of area 'den' would mint copies of the fragment 'Lamp'",
+        ] {
+            assert!(message.contains(problem), "missing '{}' in:\n{}", problem, message);
+        }
+    }
+
+    #[test]
+    fn exits_are_a_room_name_or_a_table() {
+        let room: RoomData = toml::from_str(
+            r#"
+            name = "den"
+            title = "The Den"
+            description = "Dark."
+            exits = { n = "hall", "enter portal" = { to = "void", aliases = ["portal"], hidden = true } }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(room.exits["n"], ExitData::to("hall"));
+        let portal = &room.exits["enter portal"];
+        assert_eq!((portal.to.as_str(), portal.hidden), ("void", true));
+        assert_eq!(portal.aliases, vec!["portal"]);
+
+        // Plain exits are written back as plain names
+        let json = serde_json::to_value(&room.exits).unwrap();
+        assert_eq!(json["n"], "hall");
+        assert_eq!(json["enter portal"]["to"], "void");
+    }
+
+    #[test]
+    fn saved_rooms_replace_and_extend_the_files() {
+        let mut world = WorldData::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("world")).unwrap();
+        let mut plaza = world.room("obsidian_plaza").unwrap().clone();
+        plaza.title = "The Cracked Plaza".to_string();
+        plaza.exits.insert("down".to_string(), ExitData::to("undercroft"));
+        let undercroft = RoomData {
+            name: "undercroft".to_string(),
+            title: "The Undercroft".to_string(),
+            description: "Damp.".to_string(),
+            exits: BTreeMap::from([
+                ("up".to_string(), ExitData::to("obsidian_plaza")),
+                ("west".to_string(), ExitData::to("demolished")),
+            ]),
+            weather: None,
+            coherence: None,
+            stream: None,
+            details: Vec::new(),
+            flags: vec!["dark".to_string()],
+        };
+
+        let dropped = world.apply_saved(vec![
+            ("central".to_string(), plaza),
+            ("depths".to_string(), undercroft),
+        ]);
+        assert_eq!(dropped, vec!["exit west from 'undercroft' to missing room 'demolished'"]);
+        assert_eq!(world.rooms().count(), 11);
+        assert_eq!(world.room("obsidian_plaza").unwrap().title, "The Cracked Plaza");
+        // Weather and the like come along with the saved room
+        assert!(world.room("obsidian_plaza").unwrap().weather.is_some());
+        assert!(world.rooms().any(|(area, room)| area == "depths" && room.name == "undercroft"));
+        world.validate().unwrap();
+    }
+}
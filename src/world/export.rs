@@ -0,0 +1,90 @@
+// World Export - Dump the loaded world without starting the game loop
+//
+// Spawns the world into a throwaway Bevy App and walks the result, so the
+// export always matches what spawn_world actually builds from the files
+// and the rooms saved over them.
+// Rooms are keyed by their world-file name; exits refer to rooms by it too.
+
+use bevy::prelude::*;
+use serde_json::{json, Map, Value};
+
+use crate::domain::*;
+
+use super::{spawn_world, WorldData};
+
+/// Build the world headlessly and describe it as JSON
+pub fn export_world(data: &WorldData) -> Value {
+    let mut app = App::new();
+    app.insert_resource(data.clone());
+    app.add_systems(Startup, spawn_world);
+    app.update();
+    let world = app.world_mut();
+
+    let mut room_keys = std::collections::HashMap::new();
+    let mut rooms_query = world.query::<(Entity, &RoomInfo)>();
+    for (entity, info) in rooms_query.iter(world) {
+        room_keys.insert(entity, info.name.clone());
+    }
+    let key_of = |e: Option<Entity>| e.and_then(|e| room_keys.get(&e).cloned());
+
+    let mut rooms = Vec::new();
+    let mut query = world.query::<(
+        Entity,
+        &Room,
+        &RoomInfo,
+        Option<&Exits>,
+        Option<&DetailList>,
+        Option<&WeatherZone>,
+    )>();
+    for (entity, room, info, exits, details, zone) in query.iter(world) {
+        let exits = exits.map(|x| {
+            let mut map = Map::new();
+            for (name, exit) in &x.0 {
+                if let Some(key) = key_of(Some(exit.target)) {
+                    map.insert(name.clone(), Value::String(key));
+                }
+            }
+            map
+        });
+        rooms.push(json!({
+            "key": room_keys[&entity],
+            "area": info.area,
+            "title": room.title,
+            "description": room.description,
+            "exits": exits.unwrap_or_default(),
+            "details": details.map(|d| d.details.clone()).unwrap_or_default(),
+            "sheltered": zone.map(|z| z.sheltered),
+        }));
+    }
+
+    let mut npcs = Vec::new();
+    let mut query = world.query_filtered::<(&SubstrateIdentity, &Location, Option<&Mob>), With<NonPlayer>>();
+    for (identity, location, mob) in query.iter(world) {
+        npcs.push(json!({
+            "name": identity.name,
+            "uuid": identity.uuid,
+            "room": key_of(Some(location.0)),
+            "short_desc": mob.map(|m| m.short_desc.clone()),
+        }));
+    }
+
+    let mut items = Vec::new();
+    let mut query = world.query::<(&Item, Option<&Location>)>();
+    for (item, location) in query.iter(world) {
+        items.push(json!({
+            "name": item.name,
+            "description": item.description,
+            "keywords": item.keywords,
+            "item_type": item.item_type,
+            "room": key_of(location.map(|l| l.0).or(item.location)),
+        }));
+    }
+
+    // Stable output makes exports diffable
+    let by_key = |v: &Value| v["key"].as_str().unwrap_or_default().to_string();
+    rooms.sort_by_key(by_key);
+    npcs.sort_by_key(|v| v["name"].as_str().unwrap_or_default().to_string());
+    items.sort_by_key(|v| v["name"].as_str().unwrap_or_default().to_string());
+
+    json!({ "rooms": rooms, "npcs": npcs, "items": items })
+}
@@ -1,5 +1,9 @@
 // World Layer - World initialization and spawning
 
+mod data;
+mod export;
 mod spawn;
 
+pub use data::*;
+pub use export::*;
 pub use spawn::*;